@@ -0,0 +1,199 @@
+use std::{path::PathBuf, str::FromStr};
+
+/// Options passed on the command line
+#[derive(Clone)]
+pub struct Args {
+    /// How many random demo rows to seed a new database with, `0` starts empty
+    pub sample_rows: usize,
+    /// Write a trace of ECS operations to [`LOG_PATH`]
+    pub verbose: bool,
+    /// Height in lines of each viewer row outside of compact mode
+    pub row_height: u16,
+    /// Blank lines above the content of each viewer cell outside of compact mode
+    pub cell_padding: u16,
+    /// Program to open FilePath cells with instead of the platform default
+    pub open_with: Option<String>,
+    /// Database loaded on startup and saved on quit, created if it doesn't exist.
+    /// Given either as `--db <path>` or as the only positional argument.
+    pub db_path: PathBuf,
+    /// CSV file whose rows are added to the database on startup
+    pub import: Option<PathBuf>,
+    /// Report what `import` would add, and what it would skip, without changing the database
+    /// or starting the viewer
+    pub dry_run: bool,
+    /// Start in read-only mode, where nothing in the database can be changed
+    pub read_only: bool,
+    /// Schema file whose columns are added to the database, or update the config of the ones
+    /// it already has, before it's saved without starting the viewer
+    pub import_schema: Option<PathBuf>,
+    /// Where to write just the database's column definitions without starting the viewer
+    pub export_schema: Option<PathBuf>,
+}
+
+pub const LOG_PATH: &str = "ddreplayer.log";
+pub const DB_PATH: &str = "replays.ron";
+/// How many rows `--demo` seeds a new database with
+pub const DEMO_ROWS: usize = 10;
+
+impl Args {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Args {
+            sample_rows: 0,
+            verbose: false,
+            row_height: 4,
+            cell_padding: 1,
+            open_with: None,
+            db_path: PathBuf::from(DB_PATH),
+            import: None,
+            dry_run: false,
+            read_only: false,
+            import_schema: None,
+            export_schema: None,
+        };
+
+        let mut positional = None;
+        while let Some(arg) = args.next() {
+            match &*arg {
+                "--sample" => parsed.sample_rows = parse_value(&arg, args.next())?,
+                "--demo" => parsed.sample_rows = DEMO_ROWS,
+                "--verbose" => parsed.verbose = true,
+                "--row-height" => parsed.row_height = parse_value(&arg, args.next())?,
+                "--cell-padding" => parsed.cell_padding = parse_value(&arg, args.next())?,
+                "--open-with" => parsed.open_with = Some(parse_value(&arg, args.next())?),
+                "--db" => parsed.db_path = parse_value(&arg, args.next())?,
+                "--import" => parsed.import = Some(parse_value(&arg, args.next())?),
+                "--dry-run" => parsed.dry_run = true,
+                "--read-only" => parsed.read_only = true,
+                "--import-schema" => parsed.import_schema = Some(parse_value(&arg, args.next())?),
+                "--export-schema" => parsed.export_schema = Some(parse_value(&arg, args.next())?),
+                _ if arg.starts_with('-') => return Err(format!("unrecognised argument `{arg}`")),
+                _ if positional.is_some() => return Err(format!("unexpected argument `{arg}`")),
+                _ => positional = Some(PathBuf::from(arg)),
+            }
+        }
+        if let Some(path) = positional {
+            parsed.db_path = path;
+        }
+
+        // Nothing imported could be saved
+        if parsed.read_only && parsed.import.is_some() {
+            return Err("`--import` can't be used with `--read-only`".to_string());
+        }
+
+        if parsed.read_only && parsed.import_schema.is_some() {
+            return Err("`--import-schema` can't be used with `--read-only`".to_string());
+        }
+        // The schema flags exit once they're done, before anything would be imported
+        if parsed.import.is_some()
+            && (parsed.import_schema.is_some() || parsed.export_schema.is_some())
+        {
+            return Err(
+                "`--import` can't be used with `--import-schema` or `--export-schema`".to_string(),
+            );
+        }
+
+        if parsed.dry_run && parsed.import.is_none() {
+            return Err("`--dry-run` needs an `--import` to check".to_string());
+        }
+
+        // Padding is applied above and below the content so both have to fit
+        if u32::from(parsed.cell_padding) * 2 + 1 > u32::from(parsed.row_height) {
+            return Err(format!(
+                "a `--cell-padding` of {} doesn't fit in a `--row-height` of {}",
+                parsed.cell_padding, parsed.row_height
+            ));
+        }
+
+        Ok(parsed)
+    }
+}
+
+fn parse_value<T: FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("`{flag}` expects a value"))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{value}` passed to `{flag}`"))
+}
+
+#[test]
+fn db_path_can_be_positional() {
+    let parse = |args: &[&str]| Args::parse(args.iter().map(|arg| arg.to_string()));
+
+    assert_eq!(parse(&[]).unwrap().db_path, PathBuf::from(DB_PATH));
+    assert_eq!(
+        parse(&["speedruns.ron", "--verbose"]).unwrap().db_path,
+        PathBuf::from("speedruns.ron")
+    );
+    assert_eq!(
+        parse(&["a.ron", "b.ron"]).err().unwrap(),
+        "unexpected argument `b.ron`"
+    );
+    assert_eq!(
+        parse(&["--dbb"]).err().unwrap(),
+        "unrecognised argument `--dbb`"
+    );
+    assert_eq!(
+        parse(&["--read-only", "--import", "runs.csv"])
+            .err()
+            .unwrap(),
+        "`--import` can't be used with `--read-only`"
+    );
+    assert_eq!(
+        parse(&["--dry-run"]).err().unwrap(),
+        "`--dry-run` needs an `--import` to check"
+    );
+    assert!(
+        parse(&["--import", "runs.csv", "--dry-run"])
+            .unwrap()
+            .dry_run
+    );
+}
+
+#[test]
+fn schema_flags_stand_alone() {
+    let parse = |args: &[&str]| Args::parse(args.iter().map(|arg| arg.to_string()));
+
+    let args = parse(&["--import-schema", "in.ron", "--export-schema", "out.ron"]).unwrap();
+    assert_eq!(args.import_schema, Some(PathBuf::from("in.ron")));
+    assert_eq!(args.export_schema, Some(PathBuf::from("out.ron")));
+    assert!(parse(&["--read-only", "--export-schema", "out.ron"]).is_ok());
+    assert_eq!(
+        parse(&["--read-only", "--import-schema", "in.ron"])
+            .err()
+            .unwrap(),
+        "`--import-schema` can't be used with `--read-only`"
+    );
+    assert_eq!(
+        parse(&["--import", "runs.csv", "--export-schema", "out.ron"])
+            .err()
+            .unwrap(),
+        "`--import` can't be used with `--import-schema` or `--export-schema`"
+    );
+}
+
+#[test]
+fn only_demo_mode_seeds_rows() {
+    let parse = |args: &[&str]| Args::parse(args.iter().map(|arg| arg.to_string())).unwrap();
+
+    assert_eq!(parse(&[]).sample_rows, 0);
+    assert_eq!(parse(&["--demo"]).sample_rows, DEMO_ROWS);
+    assert_eq!(parse(&["--sample", "3"]).sample_rows, 3);
+}
+
+#[test]
+fn padding_has_to_fit_the_row() {
+    let parse = |args: &[&str]| Args::parse(args.iter().map(|arg| arg.to_string()));
+
+    assert!(parse(&["--row-height", "3", "--cell-padding", "1"]).is_ok());
+    assert_eq!(
+        parse(&["--row-height", "2", "--cell-padding", "1"])
+            .err()
+            .unwrap(),
+        "a `--cell-padding` of 1 doesn't fit in a `--row-height` of 2"
+    );
+    // Big enough to overflow if doubled as a `u16`
+    assert_eq!(
+        parse(&["--cell-padding", "40000"]).err().unwrap(),
+        "a `--cell-padding` of 40000 doesn't fit in a `--row-height` of 4"
+    );
+}
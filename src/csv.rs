@@ -0,0 +1,76 @@
+/// Splits CSV `text` into records of fields. Fields may be wrapped in `"` to contain commas,
+/// newlines or `""` escaped quotes, and records may end in either `\n` or `\r\n`.
+pub fn parse(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if field.is_empty() => {
+                let start = line;
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => {
+                            line += (c == '\n') as usize;
+                            field.push(c);
+                        }
+                        None => return Err(format!("unterminated quote starting on line {start}")),
+                    }
+                }
+                if let Some(&c) = chars.peek()
+                    && !matches!(c, ',' | '\n' | '\r')
+                {
+                    return Err(format!(
+                        "unexpected `{c}` after a quoted field on line {line}"
+                    ));
+                }
+            }
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => (),
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+                line += 1;
+            }
+            c => field.push(c),
+        }
+    }
+
+    // The last record doesn't need a trailing newline
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[test]
+fn parses_quoted_fields() {
+    let text = "Name,Notes\r\n\"Smith, J\",\"said \"\"hi\"\"\nthen left\"\n,\nlast,row";
+    assert_eq!(
+        parse(text).unwrap(),
+        [
+            vec!["Name", "Notes"],
+            vec!["Smith, J", "said \"hi\"\nthen left"],
+            vec!["", ""],
+            vec!["last", "row"],
+        ]
+    );
+
+    assert_eq!(
+        parse("a,\"b\nc").unwrap_err(),
+        "unterminated quote starting on line 1"
+    );
+    assert_eq!(
+        parse("a\n\"b\"c").unwrap_err(),
+        "unexpected `c` after a quoted field on line 2"
+    );
+}
@@ -0,0 +1,158 @@
+use std::collections::VecDeque;
+
+use ddreplayer::{ReplayDB, replay_format::RowFile};
+use decentralecs::Entity;
+
+/// How many edits can be undone before the oldest ones are forgotten
+const MAX_UNDO: usize = 100;
+
+/// Undo/redo stacks of whole row snapshots. Snapshots hold owned [`Value`](ddreplayer::Value)s
+/// rather than raw component bytes so evicting one frees its `String`s like any other value.
+#[derive(Default)]
+pub struct History {
+    undo: VecDeque<Edit>,
+    redo: Vec<Edit>,
+}
+
+/// One change to a single row
+struct Edit {
+    entity: Entity,
+    /// The row's natural order key, used when it has to be respawned
+    order: f64,
+    /// The row before the change, `None` if the change spawned it
+    before: Option<RowFile>,
+    /// The row after the change, `None` if the change despawned it
+    after: Option<RowFile>,
+}
+
+impl History {
+    /// Records a change to `entity` that has already been made, `before` being
+    /// [`ReplayDB::row_file`] from just before or `None` if `entity` was just spawned
+    pub fn record(&mut self, db: &ReplayDB, entity: Entity, before: Option<RowFile>) {
+        self.push(Edit {
+            entity,
+            order: db.order_key(entity).unwrap_or(f64::INFINITY),
+            before,
+            after: Some(db.row_file(entity)),
+        });
+    }
+
+    /// Records that `entity` is about to be despawned
+    pub fn record_despawn(&mut self, db: &ReplayDB, entity: Entity) {
+        self.push(Edit {
+            entity,
+            order: db.order_key(entity).unwrap_or(f64::INFINITY),
+            before: Some(db.row_file(entity)),
+            after: None,
+        });
+    }
+
+    fn push(&mut self, edit: Edit) {
+        tracing::debug!(entity = ?edit.entity, "recorded edit");
+        self.redo.clear();
+        self.undo.push_back(edit);
+        if self.undo.len() > MAX_UNDO {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Reverts the most recent edit, returning `false` if there's nothing to undo
+    pub fn undo(&mut self, db: &mut ReplayDB) -> bool {
+        let Some(edit) = self.undo.pop_back() else {
+            return false;
+        };
+        let edit = self.apply(db, edit, |edit| (&edit.after, &edit.before));
+        self.redo.push(edit);
+        true
+    }
+
+    /// Reapplies the most recently undone edit, returning `false` if there's nothing to redo
+    pub fn redo(&mut self, db: &mut ReplayDB) -> bool {
+        let Some(edit) = self.redo.pop() else {
+            return false;
+        };
+        let edit = self.apply(db, edit, |edit| (&edit.before, &edit.after));
+        self.undo.push_back(edit);
+        true
+    }
+
+    /// Moves `edit`'s row from the first state `sides` returns to the second
+    fn apply(
+        &mut self,
+        db: &mut ReplayDB,
+        mut edit: Edit,
+        sides: impl Fn(&Edit) -> (&Option<RowFile>, &Option<RowFile>),
+    ) -> Edit {
+        let (from, to) = sides(&edit);
+        let alive = from.is_some().then_some(edit.entity);
+        let restored = db.restore_row(alive, edit.order, to.as_ref());
+
+        // A respawned row gets a new entity so every other edit to it has to follow along
+        if let Some(entity) = restored
+            && entity != edit.entity
+        {
+            let old = edit.entity;
+            for other in self.undo.iter_mut().chain(&mut self.redo) {
+                if other.entity == old {
+                    other.entity = entity;
+                }
+            }
+            edit.entity = entity;
+        }
+        edit
+    }
+}
+
+#[test]
+fn undo_redo_restores_rows() {
+    use ddreplayer::Value;
+
+    let mut db = ReplayDB::new();
+    let mut history = History::default();
+    let name = db.labels()[0].clone();
+
+    let entity = db.spawn();
+    history.record(&db, entity, None);
+    let before = db.row_file(entity);
+    db.insert_value(&name, entity, Value::Text("first".to_string()));
+    history.record(&db, entity, Some(before));
+    history.record_despawn(&db, entity);
+    db.despawn(entity);
+    assert!(db.ordered_entities().is_empty());
+
+    // Undoing the despawn respawns the row as a new entity that the older edits follow
+    assert!(history.undo(&mut db));
+    let [entity] = db.ordered_entities().try_into().unwrap();
+    assert_eq!(
+        db.get_value(&name, entity),
+        Some(Value::Text("first".to_string()))
+    );
+    assert!(history.undo(&mut db));
+    assert_eq!(db.get_value(&name, entity), None);
+    assert!(history.undo(&mut db));
+    assert!(db.ordered_entities().is_empty());
+    assert!(!history.undo(&mut db));
+
+    assert!(history.redo(&mut db));
+    assert!(history.redo(&mut db));
+    let [entity] = db.ordered_entities().try_into().unwrap();
+    assert_eq!(
+        db.get_value(&name, entity),
+        Some(Value::Text("first".to_string()))
+    );
+
+    // A new edit forgets everything that was undone
+    history.record(&db, entity, Some(db.row_file(entity)));
+    assert!(!history.redo(&mut db));
+}
+
+#[test]
+fn history_is_bounded() {
+    let mut db = ReplayDB::new();
+    let mut history = History::default();
+    for _ in 0..MAX_UNDO + 5 {
+        let entity = db.spawn();
+        history.record(&db, entity, None);
+    }
+    assert_eq!(history.undo.len(), MAX_UNDO);
+}
@@ -0,0 +1,184 @@
+use std::{collections::HashMap, fmt::Debug, hash::Hash, path::Path};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+/// Where key bindings are read from, next to the database file
+pub const KEYS_FILE: &str = "ddreplayer-keys.ron";
+
+/// Viewer actions whose key can be changed in [`KEYS_FILE`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum ViewerAction {
+    PrevRow,
+    NextRow,
+    FirstRow,
+    LastRow,
+    PrevPage,
+    NextPage,
+    PrevColumn,
+    NextColumn,
+    EditEntity,
+    NewEntity,
+    Help,
+    Quit,
+}
+
+/// Editor actions whose key can be changed in [`KEYS_FILE`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum EditorAction {
+    PrevField,
+    NextField,
+    /// Moves to the next field or presses the focused button, e.g. Save Changes
+    Press,
+    Back,
+}
+
+const VIEWER_DEFAULTS: &[(ViewerAction, KeyCode)] = &[
+    (ViewerAction::PrevRow, KeyCode::Up),
+    (ViewerAction::NextRow, KeyCode::Down),
+    (ViewerAction::FirstRow, KeyCode::Home),
+    (ViewerAction::LastRow, KeyCode::End),
+    (ViewerAction::PrevPage, KeyCode::PageUp),
+    (ViewerAction::NextPage, KeyCode::PageDown),
+    (ViewerAction::PrevColumn, KeyCode::Left),
+    (ViewerAction::NextColumn, KeyCode::Right),
+    (ViewerAction::EditEntity, KeyCode::Char('e')),
+    (ViewerAction::NewEntity, KeyCode::Char('n')),
+    (ViewerAction::Help, KeyCode::Char('?')),
+    (ViewerAction::Quit, KeyCode::Esc),
+];
+const EDITOR_DEFAULTS: &[(EditorAction, KeyCode)] = &[
+    (EditorAction::PrevField, KeyCode::Up),
+    (EditorAction::NextField, KeyCode::Down),
+    (EditorAction::Press, KeyCode::Enter),
+    (EditorAction::Back, KeyCode::Esc),
+];
+
+/// The on disk representation of [`KeyBindings`], e.g. `(viewer: { NextRow: Char('j') })`.
+/// Actions that are left out keep their default key.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct KeyBindingsFile {
+    viewer: HashMap<ViewerAction, KeyCode>,
+    editor: HashMap<EditorAction, KeyCode>,
+}
+
+/// Which action each key performs in the viewer and the editor
+#[derive(Debug)]
+pub struct KeyBindings {
+    viewer: HashMap<KeyCode, ViewerAction>,
+    editor: HashMap<KeyCode, EditorAction>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self::from_file(KeyBindingsFile::default()).unwrap()
+    }
+}
+
+impl KeyBindings {
+    pub fn load_from_path(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("failed to read `{}`: {err}", path.display()))?;
+        let file = ron::from_str(&text)
+            .map_err(|err| format!("failed to parse `{}`: {err}", path.display()))?;
+        Self::from_file(file)
+            .map_err(|err| format!("invalid key bindings `{}`: {err}", path.display()))
+    }
+
+    fn from_file(file: KeyBindingsFile) -> Result<Self, String> {
+        Ok(Self {
+            viewer: bind("viewer", VIEWER_DEFAULTS, file.viewer)?,
+            editor: bind("editor", EDITOR_DEFAULTS, file.editor)?,
+        })
+    }
+
+    /// The viewer action `key` is bound to. Keys held with Ctrl never are, so they stay free
+    /// for the fixed Ctrl shortcuts.
+    pub fn viewer(&self, key: &KeyEvent) -> Option<ViewerAction> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            return None;
+        }
+        self.viewer.get(&key.code).copied()
+    }
+
+    /// The key `action` is bound to in the viewer
+    pub fn viewer_key(&self, action: ViewerAction) -> KeyCode {
+        self.viewer
+            .iter()
+            .find_map(|(&key, &bound)| (bound == action).then_some(key))
+            .unwrap()
+    }
+
+    /// The key `action` is bound to in the editor
+    pub fn editor_key(&self, action: EditorAction) -> KeyCode {
+        self.editor
+            .iter()
+            .find_map(|(&key, &bound)| (bound == action).then_some(key))
+            .unwrap()
+    }
+
+    /// The editor action `key` is bound to, see [`KeyBindings::viewer`]
+    pub fn editor(&self, key: &KeyEvent) -> Option<EditorAction> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            return None;
+        }
+        self.editor.get(&key.code).copied()
+    }
+}
+
+/// Maps each key to its action, using `overrides` over `defaults`
+fn bind<A: Copy + Eq + Hash + Debug>(
+    mode: &str,
+    defaults: &[(A, KeyCode)],
+    overrides: HashMap<A, KeyCode>,
+) -> Result<HashMap<KeyCode, A>, String> {
+    let mut keys = HashMap::new();
+    for &(action, default) in defaults {
+        let key = overrides.get(&action).copied().unwrap_or(default);
+        if let Some(other) = keys.insert(key, action) {
+            return Err(format!(
+                "{key:?} is bound to both {other:?} and {action:?} in the {mode}"
+            ));
+        }
+    }
+    Ok(keys)
+}
+
+#[test]
+fn overrides_keep_other_defaults() {
+    let file = ron::from_str("(viewer: { NextRow: Char('j'), PrevRow: Char('k') })").unwrap();
+    let keys = KeyBindings::from_file(file).unwrap();
+    let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+    assert_eq!(
+        keys.viewer(&press(KeyCode::Char('j'))),
+        Some(ViewerAction::NextRow)
+    );
+    assert_eq!(keys.viewer(&press(KeyCode::Down)), None);
+    assert_eq!(
+        keys.viewer(&press(KeyCode::Char('e'))),
+        Some(ViewerAction::EditEntity)
+    );
+    assert_eq!(
+        keys.editor(&press(KeyCode::Enter)),
+        Some(EditorAction::Press)
+    );
+    assert_eq!(
+        keys.viewer(&KeyEvent::new(KeyCode::Char('j'), KeyModifiers::CONTROL)),
+        None
+    );
+}
+
+#[test]
+fn rejects_duplicate_keys() {
+    let file = ron::from_str("(editor: { Back: Enter })").unwrap();
+    assert_eq!(
+        KeyBindings::from_file(file).unwrap_err(),
+        "Enter is bound to both Press and Back in the editor"
+    );
+
+    // The same key can be used once in each mode
+    let file = ron::from_str("(viewer: { Quit: Char('q') }, editor: { Back: Char('q') })").unwrap();
+    assert!(KeyBindings::from_file(file).is_ok());
+}
@@ -0,0 +1,2903 @@
+//! The replay database behind the ddreplayer viewer, usable without the TUI: rows are entities
+//! with a typed component for each column, see [`ReplayDB`].
+
+use core::alloc;
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    num::{IntErrorKind, ParseIntError},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use decentralecs::{ColumnsApi, Entity, WithEntities, World};
+use rand::Rng;
+use ratatui::style::Color;
+use replay_format::{ColumnFile, FORMAT_VERSION, FileVersion, ReplayDBFile, RowFile, SchemaFile};
+use serde::{Deserialize, Serialize};
+use typed_table::TypedTable;
+
+mod csv;
+pub mod replay_format;
+mod typed_table;
+
+/// Digits shown after the decimal point in Decimal cells
+pub const DECIMAL_PLACES: usize = 3;
+
+/// Most bars drawn in a column's histogram, see [`ReplayDB::histogram`]
+const HISTOGRAM_BUCKETS: usize = 10;
+
+/// How many of a new CSV column's values [`suggest_kind`] looks at
+const IMPORT_SAMPLE: usize = 100;
+
+/// What a ticked Unit field holds in the editor, unticked ones are empty
+pub const UNIT_SET: &str = "x";
+
+/// Drawn before the path in FilePath cells
+pub const FILE_ICON: &str = "🗎";
+
+/// Drawn after the path in FilePath cells whose file doesn't exist
+pub const MISSING_FILE_MARKER: &str = " (missing)";
+
+/// Overwrites `entity`'s component in one of the `i64` timestamp tables
+fn set_timestamp(table: &mut TypedTable, world: &mut World<'static>, entity: Entity, secs: i64) {
+    table.forget(world, entity);
+    table.insert(world, entity, secs);
+}
+
+/// Parses what the user typed into a Number field
+fn parse_number(text: &str) -> Result<i16, String> {
+    text.trim()
+        .parse()
+        .map_err(|err: ParseIntError| match err.kind() {
+            IntErrorKind::Empty => "a number is required".to_string(),
+            IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => {
+                format!("must be between {} and {}", i16::MIN, i16::MAX)
+            }
+            _ => "not a whole number".to_string(),
+        })
+}
+
+/// Parses what the user typed into a Decimal field, NaN and infinities aren't allowed
+fn parse_decimal(text: &str) -> Result<f64, String> {
+    match text.trim().parse::<f64>() {
+        _ if text.trim().is_empty() => Err("a number is required".to_string()),
+        Ok(d) if d.is_finite() => Ok(d),
+        Ok(_) | Err(_) => Err("not a number".to_string()),
+    }
+}
+
+/// Suggests a kind for a CSV column that isn't in the schema from some of its non-empty
+/// `values`: the first of Bool, Number and Decimal that all of them parse as, otherwise Text.
+/// There's no date kind so dates are suggested as Text.
+fn suggest_kind(values: &[String]) -> LabelDataKind {
+    [
+        LabelDataKind::Bool,
+        LabelDataKind::Number,
+        LabelDataKind::Decimal,
+    ]
+    .into_iter()
+    .find(|kind| !values.is_empty() && values.iter().all(|value| Value::parse(kind, value).is_ok()))
+    .unwrap_or(LabelDataKind::Text)
+}
+
+/// Whether `c` is one of the separators `mask` fills in, see [`LabelConfig::mask`]
+pub fn is_mask_separator(mask: &str, c: char) -> bool {
+    c != '#' && c != 'A' && mask.contains(c)
+}
+
+/// Whether `c` can fill the `slot` of an input mask, see [`LabelConfig::mask`]
+fn fits_mask_slot(slot: char, c: char) -> bool {
+    match slot {
+        '#' => c.is_ascii_digit(),
+        'A' => c.is_alphabetic(),
+        literal => c == literal,
+    }
+}
+
+/// Fits partly typed `text` into `mask`, filling in its separators as the characters after
+/// them are typed. Separators already in `text` are skipped so they can be typed or left out.
+/// `None` if a character doesn't fit its slot or there are more than the mask has room for.
+pub fn apply_mask(mask: &str, text: &str) -> Option<String> {
+    let mut chars = text
+        .chars()
+        .filter(|&c| !is_mask_separator(mask, c))
+        .peekable();
+    let mut masked = String::new();
+    for slot in mask.chars() {
+        let Some(&c) = chars.peek() else {
+            break;
+        };
+        if let '#' | 'A' = slot {
+            if !fits_mask_slot(slot, c) {
+                return None;
+            }
+            chars.next();
+            masked.push(c);
+        } else {
+            masked.push(slot);
+        }
+    }
+    chars.peek().is_none().then_some(masked)
+}
+
+/// Seconds since the unix epoch
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_secs() as i64)
+}
+
+/// How an entity is identified to the user, matching the `?entity` in log lines
+pub fn entity_id(entity: Entity) -> String {
+    format!("{entity:?}")
+}
+
+/// Formats seconds since the unix epoch as a UTC `YYYY-MM-DD HH:MM` date
+pub fn format_timestamp(secs: i64) -> String {
+    let days = secs.div_euclid(86400);
+    let secs_of_day = secs.rem_euclid(86400);
+
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}",
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60
+    )
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+/// Rows of replays, each an entity holding a typed component for every column (label) it has
+/// a value in, along with per-cell notes and hidden row order and timestamps
+pub struct ReplayDB {
+    world: World<'static>,
+    labels: Vec<Label>,
+    /// Storage for each of `labels`, at the same index so a [`ColumnHandle`] finds both
+    columns: Vec<Column>,
+    /// Free-text notes attached to individual cells
+    notes: HashMap<(Entity, Label), String>,
+    /// Hidden `f64` column of sort keys giving rows a user controlled natural order
+    order: TypedTable,
+    /// Hidden `i64` column of when each entity was spawned, see [`unix_now`]
+    created: TypedTable,
+    /// Hidden `i64` column of when each entity was last changed, see [`unix_now`]
+    modified: TypedTable,
+    /// Set by anything that changes what [`ReplayDB::save_to_path`] would write, cleared by
+    /// whoever saves with [`ReplayDB::mark_saved`]
+    dirty: bool,
+}
+
+/// Where to place a new row relative to an existing one
+#[derive(Copy, Clone, Debug)]
+pub enum RowPlacement {
+    Above,
+    Below,
+}
+
+/// Index of a label in [`ReplayDB::labels`] and its storage in [`ReplayDB::columns`]. Finding
+/// one compares against every label so loops over every column should use
+/// [`ReplayDB::handles`] rather than looking each label up. Only valid until the labels are
+/// next added, removed or reordered.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ColumnHandle(usize);
+
+impl ColumnHandle {
+    /// The label's index in [`ReplayDB::labels`]
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// The storage for a single [`Label`] along with settings that aren't part of its identity
+struct Column {
+    table: TypedTable,
+    config: LabelConfig,
+}
+
+impl Column {
+    fn new(world: &mut World<'static>, kind: &LabelDataKind) -> Result<Self, String> {
+        Ok(Self {
+            table: TypedTable::new(world, kind.layout())
+                .map_err(|err| format!("can't store {kind:?} values: {err}"))?,
+            config: LabelConfig::default(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LabelConfig {
+    /// Rendered in the viewer for `LabelDataKind::Unit` cells that have the component
+    pub unit_present_glyph: String,
+    /// Rendered in the viewer for `LabelDataKind::Unit` cells that lack the component
+    pub unit_absent_glyph: String,
+    /// Colour of `unit_present_glyph`, a name like `red` or a hex code like `#fbbf24`
+    pub unit_color: String,
+    /// Hidden columns are left out of the viewer but keep all of their values
+    pub visible: bool,
+    /// Pinned columns are drawn first and stay put while the rest scroll sideways
+    pub pinned: bool,
+    /// Values in a locked column can't be added, changed or removed from the UI
+    pub locked: bool,
+    /// Every entity has this label, and Text and FilePath values in it can't be saved blank
+    pub required: bool,
+    /// The most characters a Text or FilePath value in this column can be saved with
+    pub max_len: Option<usize>,
+    /// The smallest Number or Decimal value this column takes, any the type can hold if unset
+    pub min: Option<f64>,
+    /// The largest Number or Decimal value this column takes, any the type can hold if unset
+    pub max: Option<f64>,
+    /// No two entities can be saved with the same value in this column
+    pub unique: bool,
+    /// Text values in this column can span several lines, edited in their own popup
+    pub multiline: bool,
+    /// What the editor fills this column's field with for entities that don't have a value
+    /// yet, written as it would be typed. Any default ticks a Unit field.
+    pub default: Option<String>,
+    /// The shape Text and FilePath values in this column are typed in and saved with, `#` for
+    /// a digit, `A` for a letter and anything else a separator the editor fills in, e.g.
+    /// `##-##-####`
+    pub mask: Option<String>,
+}
+
+impl Default for LabelConfig {
+    fn default() -> Self {
+        Self {
+            unit_present_glyph: "✓".to_string(),
+            unit_absent_glyph: "".to_string(),
+            unit_color: "#fbbf24".to_string(),
+            visible: true,
+            pinned: false,
+            locked: false,
+            required: false,
+            max_len: None,
+            min: None,
+            max: None,
+            unique: false,
+            multiline: false,
+            default: None,
+            mask: None,
+        }
+    }
+}
+
+impl LabelConfig {
+    /// The parsed `unit_color`, which is checked when the database is loaded
+    pub fn unit_color(&self) -> Color {
+        self.unit_color.parse().unwrap_or(Color::Reset)
+    }
+
+    /// Checks `text` against the rules for Text and FilePath values in this column
+    fn check_text(&self, text: &str) -> Result<(), String> {
+        if self.required && text.trim().is_empty() {
+            return Err("can't be left blank".to_string());
+        }
+        if let Some(max_len) = self.max_len
+            && text.chars().count() > max_len
+        {
+            return Err(format!("must be at most {max_len} characters"));
+        }
+        if let Some(mask) = &self.mask
+            && !text.is_empty()
+            && (text.chars().count() != mask.chars().count()
+                || !mask
+                    .chars()
+                    .zip(text.chars())
+                    .all(|(slot, c)| fits_mask_slot(slot, c)))
+        {
+            return Err(format!("must look like `{mask}`"));
+        }
+        Ok(())
+    }
+
+    /// Checks a Number or Decimal `value` is within this column's bounds
+    fn check_range(&self, value: f64) -> Result<(), String> {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) if !(min..=max).contains(&value) => {
+                Err(format!("must be between {min} and {max}"))
+            }
+            (Some(min), None) if value < min => Err(format!("must be at least {min}")),
+            (None, Some(max)) if value > max => Err(format!("must be at most {max}")),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for ReplayDB {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ReplayDB {
+    fn drop(&mut self) {
+        for entity in self.ordered_entities() {
+            for label in self.labels.clone() {
+                self.remove_value(&label, entity);
+            }
+        }
+    }
+}
+
+impl ReplayDB {
+    /// Creates the default schema without any entities
+    pub fn new() -> Self {
+        let labels = [
+            Label {
+                name: "Name".to_string(),
+                data: LabelDataKind::Text,
+            },
+            Label {
+                name: "800 Split".to_string(),
+                data: LabelDataKind::Number,
+            },
+            Label {
+                name: "PB".to_string(),
+                data: LabelDataKind::Unit,
+            },
+            Label {
+                name: "Replay".to_string(),
+                data: LabelDataKind::FilePath,
+            },
+        ];
+
+        // Rows without a name are hard to tell apart
+        Self::with_columns(labels.into_iter().map(|label| {
+            let config = LabelConfig {
+                required: label.name == "Name",
+                ..LabelConfig::default()
+            };
+            (label, config)
+        }))
+        .unwrap()
+    }
+
+    /// Spawns `rows` random entities to try the app out with. Only meant for a database fresh
+    /// from [`ReplayDB::new`], as it relies on the default schema.
+    pub fn seed_demo_data(&mut self, rows: usize) {
+        let mut rng = rand::rng();
+        let [name, split, pb, _] = self.labels.clone().try_into().unwrap();
+        for _ in 0..rows {
+            let entity = self.spawn();
+            tracing::trace!(?entity, "seeding sample entity");
+
+            let len = rng.random_range(1..8);
+            self.insert_value(&name, entity, Value::Text("a".repeat(len)));
+            self.insert_value(&split, entity, Value::Number(rng.random_range(-100..=182)));
+            if rng.random() {
+                self.insert_value(&pb, entity, Value::Unit);
+            }
+        }
+    }
+
+    /// Creates an empty database with a column for each label, failing if one of them can't
+    /// be stored
+    pub fn with_columns(
+        columns: impl IntoIterator<Item = (Label, LabelConfig)>,
+    ) -> Result<Self, String> {
+        let mut world = World::new();
+
+        let order = TypedTable::new(&mut world, alloc::Layout::new::<f64>())?;
+        let created = TypedTable::new(&mut world, alloc::Layout::new::<i64>())?;
+        let modified = TypedTable::new(&mut world, alloc::Layout::new::<i64>())?;
+
+        let (labels, columns) = columns
+            .into_iter()
+            .map(|(label, config)| {
+                let mut column = Column::new(&mut world, &label.data)
+                    .map_err(|err| format!("column `{}` {err}", label.name))?;
+                column.config = config;
+                Ok((label, column))
+            })
+            .collect::<Result<Vec<_>, String>>()?
+            .into_iter()
+            .unzip();
+
+        Ok(Self {
+            world,
+            labels,
+            columns,
+            notes: HashMap::new(),
+            order,
+            created,
+            modified,
+            dirty: false,
+        })
+    }
+
+    /// Loads the database at `path`, or creates an empty one with `sample_rows` demo rows if
+    /// there isn't one. Demo rows are only ever added to a new database, never mixed into a
+    /// saved one.
+    pub fn open(path: &Path, sample_rows: usize) -> Result<Self, String> {
+        if path.exists() {
+            return Self::load_from_path(path);
+        }
+        let mut db = Self::new();
+        db.seed_demo_data(sample_rows);
+        Ok(db)
+    }
+
+    /// Where [`ReplayDB::save_to_path`] keeps the previous save of `path`
+    pub fn backup_path(path: &Path) -> PathBuf {
+        path.with_extension("ron.bak")
+    }
+
+    /// Loads a database previously written by [`ReplayDB::save_to_path`], by this or an older
+    /// version
+    pub fn load_from_path(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("failed to read `{}`: {err}", path.display()))?;
+        let FileVersion { version } = ron::from_str(&text)
+            .map_err(|err| format!("failed to parse `{}`: {err}", path.display()))?;
+        if version > FORMAT_VERSION {
+            return Err(format!(
+                "`{}` is format version {version} but only up to {FORMAT_VERSION} is supported, \
+                 it was written by a newer ddreplayer",
+                path.display()
+            ));
+        }
+        // Every older layout still reads as the current one, a change that breaks that
+        // converts the parsed `version` into the new layout here
+        let file = ron::from_str(&text)
+            .map_err(|err| format!("failed to parse `{}`: {err}", path.display()))?;
+        Self::from_file(file).map_err(|err| format!("invalid database `{}`: {err}", path.display()))
+    }
+
+    /// Writes every column, entity and note to `path` as RON. Whatever was at `path` before is
+    /// kept at [`ReplayDB::backup_path`].
+    pub fn save_to_path(&self, path: &Path) -> Result<(), String> {
+        let text = ron::ser::to_string_pretty(&self.to_file(), ron::ser::PrettyConfig::default())
+            .map_err(|err| format!("failed to serialize database: {err}"))?;
+
+        // Write to a sibling first so a failed write can't clobber the previous save, which is
+        // then copied rather than moved so `path` is never missing
+        let tmp_path = path.with_extension("ron.tmp");
+        std::fs::write(&tmp_path, text)
+            .and_then(|()| {
+                if path.exists() {
+                    std::fs::copy(path, Self::backup_path(path))?;
+                }
+                Ok(())
+            })
+            .and_then(|()| std::fs::rename(&tmp_path, path))
+            .map_err(|err| format!("failed to write `{}`: {err}", path.display()))
+    }
+
+    pub fn to_file(&self) -> ReplayDBFile {
+        let columns = self.column_files();
+        let rows = self
+            .ordered_entities()
+            .into_iter()
+            .map(|entity| self.row_file(entity))
+            .collect();
+
+        ReplayDBFile {
+            version: FORMAT_VERSION,
+            columns,
+            rows,
+        }
+    }
+
+    /// Every column's definition in display order
+    pub fn column_files(&self) -> Vec<ColumnFile> {
+        self.labels
+            .iter()
+            .map(|label| ColumnFile {
+                name: label.name.clone(),
+                kind: label.data.clone(),
+                config: self.column(label).config.clone(),
+            })
+            .collect()
+    }
+
+    /// Copies out everything stored for `entity` besides its order key
+    pub fn row_file(&self, entity: Entity) -> RowFile {
+        RowFile {
+            created: self.created_at(entity),
+            modified: self.modified_at(entity),
+            values: self
+                .labels
+                .iter()
+                .filter_map(|label| Some((label.name.clone(), self.get_value(label, entity)?)))
+                .collect(),
+            notes: self
+                .labels
+                .iter()
+                .filter_map(|label| {
+                    let note = self.notes.get(&(entity, label.clone()))?;
+                    Some((label.name.clone(), note.clone()))
+                })
+                .collect(),
+        }
+    }
+
+    /// Overwrites `entity`'s row with `row`, despawning it if `row` is `None` or spawning a new
+    /// entity at `order` if `entity` is `None`. Values and notes for columns that have since
+    /// been deleted or renamed are dropped. Returns the entity now holding `row`.
+    pub fn restore_row(
+        &mut self,
+        entity: Option<Entity>,
+        order: f64,
+        row: Option<&RowFile>,
+    ) -> Option<Entity> {
+        let Some(row) = row else {
+            if let Some(entity) = entity {
+                self.despawn(entity);
+            }
+            return None;
+        };
+
+        let entity = match entity {
+            Some(entity) => {
+                for label in self.labels.clone() {
+                    self.remove_value(&label, entity);
+                }
+                self.notes.retain(|(e, _), _| *e != entity);
+                entity
+            }
+            None => {
+                let entity = self.world.spawn().id();
+                self.set_order_key(entity, order);
+                entity
+            }
+        };
+
+        if let Some(created) = row.created {
+            set_timestamp(&mut self.created, &mut self.world, entity, created);
+        }
+        if let Some(modified) = row.modified {
+            set_timestamp(&mut self.modified, &mut self.world, entity, modified);
+        }
+        for (name, value) in &row.values {
+            let label = self
+                .labels
+                .iter()
+                .find(|label| label.name == *name && value.fits(&label.data))
+                .cloned();
+            if let Some(label) = label {
+                self.insert_value(&label, entity, value.clone());
+            }
+        }
+        for (name, note) in &row.notes {
+            if let Some(label) = self.labels.iter().find(|label| label.name == *name) {
+                self.notes.insert((entity, label.clone()), note.clone());
+            }
+        }
+
+        tracing::debug!(?entity, "restored row");
+        Some(entity)
+    }
+
+    pub fn from_file(file: ReplayDBFile) -> Result<Self, String> {
+        // The viewer assumes there's always at least one column, and one it can show
+        if file.columns.is_empty() {
+            return Err("there are no columns".to_string());
+        }
+        if file.columns.iter().all(|column| !column.config.visible) {
+            return Err("every column is hidden".to_string());
+        }
+
+        let mut labels = HashMap::new();
+        for column in &file.columns {
+            if let LabelDataKind::Enum { variants } = &column.kind {
+                if variants.is_empty() {
+                    return Err(format!("enum column `{}` has no variants", column.name));
+                }
+                if let Some(dupe) = variants
+                    .iter()
+                    .find(|v| variants.iter().filter(|w| w == v).count() > 1)
+                {
+                    return Err(format!(
+                        "enum column `{}` has the variant `{dupe}` twice",
+                        column.name
+                    ));
+                }
+            }
+
+            if column.config.unit_color.parse::<Color>().is_err() {
+                return Err(format!(
+                    "column `{}` has the invalid unit_color `{}`",
+                    column.name, column.config.unit_color
+                ));
+            }
+            if let Some(default) = &column.config.default
+                && let Err(err) = Value::parse(&column.kind, default)
+            {
+                return Err(format!(
+                    "column `{}` has the invalid default `{default}`, it {err}",
+                    column.name
+                ));
+            }
+
+            let label = Label {
+                name: column.name.clone(),
+                data: column.kind.clone(),
+            };
+            if labels.insert(column.name.clone(), label).is_some() {
+                return Err(format!("column `{}` is defined twice", column.name));
+            }
+        }
+
+        let mut db = Self::with_columns(file.columns.into_iter().map(|column| {
+            let label = labels[&column.name].clone();
+            (label, column.config)
+        }))?;
+
+        for (n, row) in file.rows.into_iter().enumerate() {
+            let entity = db.world.spawn().id();
+            db.set_order_key(entity, n as f64);
+            if let Some(created) = row.created {
+                set_timestamp(&mut db.created, &mut db.world, entity, created);
+            }
+            if let Some(modified) = row.modified {
+                set_timestamp(&mut db.modified, &mut db.world, entity, modified);
+            }
+
+            for (name, value) in row.values {
+                let label = labels
+                    .get(&name)
+                    .ok_or_else(|| format!("row {n} has a value for unknown column `{name}`"))?;
+                if !value.fits(&label.data) {
+                    return Err(format!(
+                        "row {n} has a {value:?} value in the {:?} column `{name}`",
+                        label.data
+                    ));
+                }
+                db.insert_value(label, entity, value);
+            }
+
+            for (name, note) in row.notes {
+                let label = labels
+                    .get(&name)
+                    .ok_or_else(|| format!("row {n} has a note for unknown column `{name}`"))?;
+                db.notes.insert((entity, label.clone()), note);
+            }
+        }
+
+        db.check_schema()?;
+        Ok(db)
+    }
+
+    /// Checks every column is stored with the layout its kind reads values back as, and that
+    /// every component in it is the size of that type and, for Enums, names a variant. The
+    /// unsafe reads in [`ReplayDB::value_at`] rely on this, so a database that fails isn't
+    /// opened.
+    fn check_schema(&self) -> Result<(), String> {
+        for (label, column) in self.labels.iter().zip(&self.columns) {
+            let layout = label.data.layout();
+            if column.table.layout() != layout {
+                return Err(format!(
+                    "column `{}` is stored as {:?} but {:?} values need {layout:?}",
+                    label.name,
+                    column.table.layout(),
+                    label.data
+                ));
+            }
+
+            for entity in self.ordered_entities() {
+                let Some(size) = column.table.component_size(&self.world, entity) else {
+                    continue;
+                };
+                if size != layout.size() {
+                    return Err(format!(
+                        "entity {} has a {size} byte value in column `{}`, expected {}",
+                        entity_id(entity),
+                        label.name,
+                        layout.size()
+                    ));
+                }
+                if let LabelDataKind::Enum { variants } = &label.data {
+                    // SAFETY: the layout was checked above and any two bytes are a valid `u16`
+                    let index = unsafe { *column.table.get::<u16>(&self.world, entity).unwrap() };
+                    if index as usize >= variants.len() {
+                        return Err(format!(
+                            "entity {} has variant {index} in column `{}`, which only has {}",
+                            entity_id(entity),
+                            label.name,
+                            variants.len()
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Where `label` and its column are stored, panicking if it isn't one of this database's
+    pub fn handle(&self, label: &Label) -> ColumnHandle {
+        let col = self.labels.iter().position(|other| other == label);
+        ColumnHandle(col.unwrap_or_else(|| panic!("no column for {label:?}")))
+    }
+
+    /// Every label along with its handle, in display order
+    pub fn handles(&self) -> impl Iterator<Item = (ColumnHandle, &Label)> {
+        self.labels
+            .iter()
+            .enumerate()
+            .map(|(col, label)| (ColumnHandle(col), label))
+    }
+
+    fn column(&self, label: &Label) -> &Column {
+        &self.columns[self.handle(label).0]
+    }
+
+    fn column_mut(&mut self, label: &Label) -> &mut Column {
+        let ColumnHandle(col) = self.handle(label);
+        &mut self.columns[col]
+    }
+
+    /// Every label in display order
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    /// The settings of `label`'s column
+    pub fn config(&self, label: &Label) -> &LabelConfig {
+        &self.column(label).config
+    }
+
+    /// Changes the settings of `label`'s column. Values already in the column aren't checked
+    /// against the new rules, see [`ReplayDB::problems`].
+    pub fn config_mut(&mut self, label: &Label) -> &mut LabelConfig {
+        self.dirty = true;
+        &mut self.column_mut(label).config
+    }
+
+    /// Whether `entity` has a value in the column of `handle`, without copying it out like
+    /// [`ReplayDB::value_at`]
+    pub fn has_value_at(&self, ColumnHandle(idx): ColumnHandle, entity: Entity) -> bool {
+        self.columns[idx].table.contains(&self.world, entity)
+    }
+
+    /// The note attached to `entity`'s cell in `label`'s column
+    pub fn note(&self, entity: Entity, label: &Label) -> Option<&str> {
+        self.notes.get(&(entity, label.clone())).map(String::as_str)
+    }
+
+    /// Attaches `note` to `entity`'s cell in `label`'s column in place of any note already
+    /// there, an empty note removes it
+    pub fn set_note(&mut self, entity: Entity, label: &Label, note: String) {
+        self.dirty = true;
+        let cell = (entity, label.clone());
+        if note.is_empty() {
+            self.notes.remove(&cell);
+        } else {
+            self.notes.insert(cell, note);
+        }
+    }
+
+    /// Whether anything has changed since the database was loaded or last saved
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Records that everything has been saved, see [`ReplayDB::is_dirty`]
+    pub fn mark_saved(&mut self) {
+        self.dirty = false;
+    }
+
+    pub fn order_key(&self, entity: Entity) -> Option<f64> {
+        unsafe { self.order.get::<f64>(&self.world, entity).copied() }
+    }
+
+    /// Whether `entity` is one of the database's rows and hasn't been despawned, every row
+    /// has an order key
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.order_key(entity).is_some()
+    }
+
+    pub fn created_at(&self, entity: Entity) -> Option<i64> {
+        unsafe { self.created.get::<i64>(&self.world, entity).copied() }
+    }
+
+    pub fn modified_at(&self, entity: Entity) -> Option<i64> {
+        unsafe { self.modified.get::<i64>(&self.world, entity).copied() }
+    }
+
+    /// Records that `entity` was changed just now
+    pub fn touch(&mut self, entity: Entity) {
+        self.dirty = true;
+        set_timestamp(&mut self.modified, &mut self.world, entity, unix_now());
+    }
+
+    /// Spawns an entity with no components besides its created/modified timestamps
+    fn spawn_timestamped(&mut self) -> Entity {
+        let now = unix_now();
+        let entity = self.world.spawn().id();
+        for table in [&mut self.created, &mut self.modified] {
+            table.insert(&mut self.world, entity, now);
+        }
+        entity
+    }
+
+    fn set_order_key(&mut self, entity: Entity, key: f64) {
+        self.dirty = true;
+        self.order.insert(&mut self.world, entity, key);
+    }
+
+    /// All entities in their natural order, i.e. the order the viewer displays rows in
+    pub fn ordered_entities(&self) -> Vec<Entity> {
+        let mut entities = self
+            .world
+            .join(WithEntities)
+            .map(|entity| (self.order_key(entity).unwrap_or(f64::INFINITY), entity))
+            .collect::<Vec<_>>();
+        entities.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        entities.into_iter().map(|(_, entity)| entity).collect()
+    }
+
+    /// Spawns an empty entity placed after every existing row
+    pub fn spawn(&mut self) -> Entity {
+        let key = self
+            .world
+            .join(WithEntities)
+            .filter_map(|entity| self.order_key(entity))
+            .fold(-1.0, f64::max)
+            + 1.0;
+
+        let entity = self.spawn_timestamped();
+        self.set_order_key(entity, key);
+        tracing::debug!(?entity, key, "spawned entity");
+        entity
+    }
+
+    /// Spawns an empty entity placed directly above or below `anchor`
+    pub fn spawn_at(&mut self, anchor: Entity, placement: RowPlacement) -> Entity {
+        let entities = self.ordered_entities();
+        let Some(row) = entities.iter().position(|&entity| entity == anchor) else {
+            return self.spawn();
+        };
+        let anchor_key = self.order_key(anchor).unwrap_or(row as f64);
+
+        let neighbour_key = match placement {
+            RowPlacement::Above => row
+                .checked_sub(1)
+                .and_then(|row| self.order_key(entities[row]))
+                .unwrap_or(anchor_key - 1.0),
+            RowPlacement::Below => entities
+                .get(row + 1)
+                .and_then(|&entity| self.order_key(entity))
+                .unwrap_or(anchor_key + 1.0),
+        };
+
+        let entity = self.spawn_timestamped();
+        self.set_order_key(entity, (anchor_key + neighbour_key) / 2.0);
+        tracing::debug!(?entity, ?placement, row, "spawned entity");
+        entity
+    }
+
+    /// Names of labels starting with `prefix`, ignoring case, in schema order
+    pub fn names_starting_with(&self, prefix: &str) -> Vec<String> {
+        let prefix = prefix.to_lowercase();
+        self.labels
+            .iter()
+            .filter(|label| label.name.to_lowercase().starts_with(&prefix))
+            .map(|label| label.name.clone())
+            .collect()
+    }
+
+    /// How many entities have a component for `label`
+    pub fn label_count(&self, label: &Label) -> usize {
+        let table = &self.column(label).table;
+        self.world
+            .join(WithEntities)
+            .filter(|&entity| table.contains(&self.world, entity))
+            .count()
+    }
+
+    /// Reads the component `label` holds for `entity` back into a typed [`Value`]
+    pub fn get_value(&self, label: &Label, entity: Entity) -> Option<Value> {
+        self.value_at(self.handle(label), entity)
+    }
+
+    /// [`ReplayDB::get_value`] for a label that's already been looked up
+    pub fn value_at(&self, ColumnHandle(idx): ColumnHandle, entity: Entity) -> Option<Value> {
+        let label = &self.labels[idx];
+        tracing::trace!(label = %label.name, ?entity, "get_component");
+        let col = &self.columns[idx].table;
+        let world = &self.world;
+
+        // SAFETY: a column's components are always the type its kind's layout is made from
+        Some(unsafe {
+            match &label.data {
+                LabelDataKind::Number => Value::Number(*col.get::<i16>(world, entity)?),
+                LabelDataKind::Decimal => Value::Decimal(*col.get::<f64>(world, entity)?),
+                LabelDataKind::Text => Value::Text(col.get::<String>(world, entity)?.clone()),
+                LabelDataKind::Unit => {
+                    col.get::<()>(world, entity)?;
+                    Value::Unit
+                }
+                LabelDataKind::FilePath => {
+                    Value::FilePath(col.get::<String>(world, entity)?.clone())
+                }
+                LabelDataKind::Bool => Value::Bool(*col.get::<bool>(world, entity)?),
+                LabelDataKind::Enum { variants } => {
+                    let index = *col.get::<u16>(world, entity)?;
+                    Value::Enum(variants[index as usize].clone())
+                }
+            }
+        })
+    }
+
+    /// Counts of `label`'s values across every row in up to [`HISTOGRAM_BUCKETS`] equal width
+    /// buckets, each named by where it starts. Rows without a value aren't counted and
+    /// anything but Number and Decimal columns has no buckets.
+    pub fn histogram(&self, label: &Label) -> Vec<(String, u64)> {
+        let handle = self.handle(label);
+        let values = self
+            .ordered_entities()
+            .into_iter()
+            .filter_map(|entity| match self.value_at(handle, entity)? {
+                Value::Number(n) => Some(n as f64),
+                Value::Decimal(d) => Some(d),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let Some(min) = values.iter().copied().reduce(f64::min) else {
+            return Vec::new();
+        };
+        let max = values.iter().copied().fold(min, f64::max);
+
+        // Whole numbers can't be split any finer than one bucket per number
+        let buckets = match label.data {
+            LabelDataKind::Number => HISTOGRAM_BUCKETS.min((max - min) as usize + 1),
+            _ if min == max => 1,
+            _ => HISTOGRAM_BUCKETS,
+        };
+        let width = match label.data {
+            LabelDataKind::Number => ((max - min + 1.0) / buckets as f64).ceil(),
+            _ => (max - min) / buckets as f64,
+        };
+        let mut counts = vec![0; buckets];
+        for value in values {
+            let bucket = if width == 0.0 {
+                0
+            } else {
+                ((value - min) / width) as usize
+            };
+            counts[bucket.min(buckets - 1)] += 1;
+        }
+
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(n, count)| {
+                let start = min + width * n as f64;
+                let name = match label.data {
+                    LabelDataKind::Number => format!("{start}"),
+                    _ => format!("{start:.DECIMAL_PLACES$}"),
+                };
+                (name, count)
+            })
+            .collect()
+    }
+
+    /// Formats a cell the same way the viewer displays it, `hex_numbers` displays Number
+    /// cells in hexadecimal rather than decimal
+    pub fn cell_text(&self, label: &Label, entity: Entity, hex_numbers: bool) -> String {
+        self.cell_text_at(self.handle(label), entity, hex_numbers)
+    }
+
+    /// [`ReplayDB::cell_text`] for a label that's already been looked up
+    pub fn cell_text_at(&self, handle: ColumnHandle, entity: Entity, hex_numbers: bool) -> String {
+        let (label, config) = (&self.labels[handle.0], &self.columns[handle.0].config);
+
+        match self.value_at(handle, entity) {
+            Some(Value::Number(n)) if hex_numbers => format!("{n:#x}"),
+            Some(Value::Number(n)) => format!("{n}"),
+            Some(Value::Decimal(d)) => format!("{d:.DECIMAL_PLACES$}"),
+            Some(Value::Text(text) | Value::Enum(text)) => text,
+            Some(Value::Bool(b)) => b.to_string(),
+            Some(Value::Unit) => config.unit_present_glyph.clone(),
+            Some(Value::FilePath(path)) => {
+                let warning = if Path::new(&path).exists() {
+                    ""
+                } else {
+                    MISSING_FILE_MARKER
+                };
+                format!("{FILE_ICON} {path}{warning}")
+            }
+            None => match label.data {
+                LabelDataKind::Unit => config.unit_absent_glyph.clone(),
+                LabelDataKind::Number
+                | LabelDataKind::Decimal
+                | LabelDataKind::Text
+                | LabelDataKind::FilePath
+                | LabelDataKind::Bool
+                | LabelDataKind::Enum { .. } => "".to_string(),
+            },
+        }
+    }
+
+    /// Writes `value` into `label`'s column for `entity`.
+    ///
+    /// Panics if `value` doesn't match the kind of `label`.
+    pub fn insert_value(&mut self, label: &Label, entity: Entity, value: Value) {
+        self.dirty = true;
+        let col = &mut self.column_mut(label).table;
+        let world = &mut self.world;
+        match (&label.data, value) {
+            (LabelDataKind::Number, Value::Number(n)) => col.insert(world, entity, n),
+            (LabelDataKind::Decimal, Value::Decimal(d)) => col.insert(world, entity, d),
+            (LabelDataKind::Text, Value::Text(s))
+            | (LabelDataKind::FilePath, Value::FilePath(s)) => col.insert(world, entity, s),
+            (LabelDataKind::Unit, Value::Unit) => col.insert(world, entity, ()),
+            (LabelDataKind::Bool, Value::Bool(b)) => col.insert(world, entity, b),
+            (LabelDataKind::Enum { variants }, Value::Enum(name)) if variants.contains(&name) => {
+                let index = variants
+                    .iter()
+                    .position(|variant| *variant == name)
+                    .unwrap();
+                col.insert(world, entity, index as u16)
+            }
+            (kind, value) => panic!("cannot store {value:?} in a {kind:?} column"),
+        }
+        tracing::debug!(label = %label.name, ?entity, "insert_component");
+    }
+
+    /// Writes each of `values` to `entity`, removing the value where it's `None`, and leaves
+    /// every other column alone. Only columns whose value actually changed are written to,
+    /// returning how many were.
+    ///
+    /// Nothing is written unless every value fits its column, and if a write panics the columns
+    /// already written are put back before unwinding, so `entity` is never left half saved.
+    pub fn set_values(
+        &mut self,
+        entity: Entity,
+        values: &[(Label, Option<Value>)],
+    ) -> Result<usize, String> {
+        for (label, value) in values {
+            if !self.labels.contains(label) {
+                return Err(format!("{} isn't a column", label.name));
+            }
+            if let Some(value) = value
+                && !value.fits(&label.data)
+            {
+                return Err(format!("{} can't hold {value:?}", label.name));
+            }
+        }
+
+        // Each changed column with its old and new value
+        let changes = values
+            .iter()
+            .filter_map(|(label, new)| {
+                let old = self.get_value(label, entity);
+                (old != *new).then(|| (label.clone(), old, new.clone()))
+            })
+            .collect::<Vec<_>>();
+
+        let mut started = 0;
+        let written = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            for (label, _, new) in &changes {
+                started += 1;
+                self.remove_value(label, entity);
+                if let Some(new) = new {
+                    self.insert_value(label, entity, new.clone());
+                }
+            }
+        }));
+        if let Err(panic) = written {
+            tracing::error!(?entity, "saving panicked, putting back the old values");
+            for (label, old, _) in &changes[..started] {
+                self.remove_value(label, entity);
+                if let Some(old) = old {
+                    self.insert_value(label, entity, old.clone());
+                }
+            }
+            std::panic::resume_unwind(panic);
+        }
+        Ok(changes.len())
+    }
+
+    /// Removes `label`'s component from `entity`, if it has one
+    pub fn remove_value(&mut self, label: &Label, entity: Entity) {
+        tracing::debug!(label = %label.name, ?entity, "remove_component");
+        self.dirty = true;
+        let col = &mut self.column_mut(label).table;
+
+        // The table doesn't know how to drop components so `String`s have to be taken back out
+        // to be dropped, everything else is `Copy`
+        match label.data {
+            LabelDataKind::Text | LabelDataKind::FilePath => {
+                // SAFETY: Text/FilePath components are always `String`s
+                drop(unsafe { col.remove::<String>(&mut self.world, entity) });
+            }
+            _ => col.forget(&mut self.world, entity),
+        }
+    }
+
+    /// Despawns `entity`, dropping all of its values and notes
+    pub fn despawn(&mut self, entity: Entity) {
+        self.dirty = true;
+        for label in self.labels.clone() {
+            self.remove_value(&label, entity);
+        }
+        self.notes.retain(|(e, _), _| *e != entity);
+        self.world.despawn(entity);
+        tracing::debug!(?entity, "despawned entity");
+    }
+
+    pub fn is_locked(&self, label: &Label) -> bool {
+        self.column(label).config.locked
+    }
+
+    /// Required labels are always in the editor and can't be removed from an entity
+    pub fn is_required(&self, label: &Label) -> bool {
+        self.column(label).config.required
+    }
+
+    /// Checks `value` follows `label`'s column rules when stored on `entity`, `None` for a row
+    /// that's yet to be spawned. See [`LabelConfig::check_text`] and
+    /// [`LabelConfig::check_range`], and no other row can already have it in a unique column.
+    pub fn check_value(
+        &self,
+        label: &Label,
+        entity: Option<Entity>,
+        value: &Value,
+    ) -> Result<(), String> {
+        let config = &self.column(label).config;
+        match value {
+            Value::Text(text) | Value::FilePath(text) => config.check_text(text)?,
+            Value::Number(n) => config.check_range(f64::from(*n))?,
+            Value::Decimal(d) => config.check_range(*d)?,
+            _ => (),
+        }
+        let taken = config.unique
+            && label.data != LabelDataKind::Unit
+            && self
+                .find(label, |other| other == value)
+                .into_iter()
+                .any(|other| Some(other) != entity);
+        if taken {
+            return Err("is already used by another row".to_string());
+        }
+        Ok(())
+    }
+
+    /// Every value breaking its column's rules and every required column a row has no value
+    /// for, in row then column order, each with why
+    pub fn problems(&self) -> Vec<(Entity, Label, String)> {
+        let mut problems = vec![];
+        for entity in self.ordered_entities() {
+            for label in &self.labels {
+                let checked = match self.get_value(label, entity) {
+                    Some(value) => self.check_value(label, Some(entity), &value),
+                    None if self.is_required(label) => Err("can't be left blank".to_string()),
+                    None => Ok(()),
+                };
+                if let Err(err) = checked {
+                    problems.push((entity, label.clone(), err));
+                }
+            }
+        }
+        problems
+    }
+
+    /// The first required column missing from `labels`, the columns a new row would have
+    /// values for
+    fn missing_required<'a>(
+        &self,
+        labels: impl Iterator<Item = &'a Label> + Clone,
+    ) -> Option<&Label> {
+        self.labels
+            .iter()
+            .find(|label| self.is_required(label) && !labels.clone().any(|other| other == *label))
+    }
+
+    pub fn is_visible(&self, label: &Label) -> bool {
+        self.column(label).config.visible
+    }
+
+    /// Multi-line Text columns are edited in a popup rather than a single line field
+    pub fn is_multiline(&self, label: &Label) -> bool {
+        label.data == LabelDataKind::Text && self.column(label).config.multiline
+    }
+
+    /// Shows or hides `label` in the viewer, returning whether it's now visible. The last
+    /// visible column can't be hidden.
+    pub fn toggle_visible(&mut self, label: &Label) -> Result<bool, String> {
+        let visible = self.labels.iter().filter(|l| self.is_visible(l)).count();
+        if self.is_visible(label) && visible == 1 {
+            return Err(format!("{} is the only visible column", label.name));
+        }
+
+        self.dirty = true;
+        let config = &mut self.column_mut(label).config;
+        config.visible = !config.visible;
+        tracing::debug!(label = %label.name, visible = config.visible, "toggled visibility");
+        Ok(config.visible)
+    }
+
+    pub fn is_pinned(&self, label: &Label) -> bool {
+        self.column(label).config.pinned
+    }
+
+    /// Pins or unpins `label` to the left of the viewer, returning whether it's now pinned
+    pub fn toggle_pinned(&mut self, label: &Label) -> bool {
+        self.dirty = true;
+        let config = &mut self.column_mut(label).config;
+        config.pinned = !config.pinned;
+        tracing::debug!(label = %label.name, pinned = config.pinned, "toggled pinned");
+        config.pinned
+    }
+
+    /// Locks or unlocks `label` against editing, returning whether it's now locked
+    pub fn toggle_lock(&mut self, label: &Label) -> bool {
+        self.dirty = true;
+        let config = &mut self.column_mut(label).config;
+        config.locked = !config.locked;
+        tracing::debug!(label = %label.name, locked = config.locked, "toggled lock");
+        config.locked
+    }
+
+    /// Adds or removes `entity`'s component for the Unit column `label`
+    pub fn toggle_unit(&mut self, label: &Label, entity: Entity) -> Result<(), String> {
+        if label.data != LabelDataKind::Unit {
+            return Err(format!(
+                "{} isn't a Unit column, open the editor with e to change it",
+                label.name
+            ));
+        }
+        if self.is_locked(label) {
+            return Err(format!("{} is locked", label.name));
+        }
+
+        if self.get_value(label, entity).is_some() {
+            self.remove_value(label, entity);
+        } else {
+            self.insert_value(label, entity, Value::Unit);
+        }
+        self.touch(entity);
+        Ok(())
+    }
+
+    /// Removes `label`'s column along with every value and note in it. The last column and
+    /// locked columns can't be deleted.
+    pub fn delete_column(&mut self, label: &Label) -> Result<(), String> {
+        if self.labels.len() == 1 {
+            return Err("can't delete the last column".to_string());
+        }
+        if self.is_locked(label) {
+            return Err(format!("{} is locked", label.name));
+        }
+        let visible = self.labels.iter().filter(|l| self.is_visible(l)).count();
+        if self.is_visible(label) && visible == 1 {
+            return Err(format!("{} is the only visible column", label.name));
+        }
+
+        self.dirty = true;
+        for entity in self.ordered_entities() {
+            self.remove_value(label, entity);
+        }
+        self.notes.retain(|(_, note_label), _| note_label != label);
+        let ColumnHandle(col) = self.handle(label);
+        self.labels.remove(col);
+        self.columns.remove(col);
+
+        tracing::debug!(label = %label.name, "deleted column");
+        Ok(())
+    }
+
+    /// Renames `old`'s column keeping all of its values, notes and settings. Nothing changes
+    /// if `new_name` is empty or already used by another column.
+    pub fn rename_label(&mut self, old: &Label, new_name: &str) -> Result<Label, String> {
+        let new_name = new_name.trim();
+        if new_name.is_empty() {
+            return Err("a name is required".to_string());
+        }
+        if self
+            .labels
+            .iter()
+            .any(|label| label != old && label.name == new_name)
+        {
+            return Err(format!("{new_name} already exists"));
+        }
+
+        let new = Label {
+            name: new_name.to_string(),
+            data: old.data.clone(),
+        };
+        self.dirty = true;
+        let ColumnHandle(col) = self.handle(old);
+        self.labels[col] = new.clone();
+        self.notes = std::mem::take(&mut self.notes)
+            .into_iter()
+            .map(|((entity, label), note)| {
+                let label = if label == *old { new.clone() } else { label };
+                ((entity, label), note)
+            })
+            .collect();
+
+        tracing::debug!(old = %old.name, new = %new.name, "renamed column");
+        Ok(new)
+    }
+
+    /// Registers an empty column named `name`, names must be unique
+    pub fn add_column(&mut self, name: &str, kind: LabelDataKind) -> Result<Label, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("a name is required".to_string());
+        }
+        if self.labels.iter().any(|label| label.name == name) {
+            return Err(format!("{name} already exists"));
+        }
+
+        let label = Label {
+            name: name.to_string(),
+            data: kind,
+        };
+        let column = Column::new(&mut self.world, &label.data)?;
+        self.dirty = true;
+        self.columns.push(column);
+        self.labels.push(label.clone());
+
+        tracing::debug!(label = %label.name, kind = ?label.data, "added column");
+        Ok(label)
+    }
+
+    /// Swaps the `col`th label with its neighbour, which changes where it's displayed and
+    /// saved. Returns the label's new index, `None` if it's already at that end.
+    pub fn move_column(&mut self, col: usize, later: bool) -> Option<usize> {
+        let to = if later { col + 1 } else { col.checked_sub(1)? };
+        if to >= self.labels.len() || col >= self.labels.len() {
+            return None;
+        }
+        self.dirty = true;
+        self.labels.swap(col, to);
+        self.columns.swap(col, to);
+        tracing::debug!(label = %self.labels[to].name, from = col, to, "moved column");
+        Some(to)
+    }
+
+    /// Orders the labels alphabetically by name, ignoring case, which changes where they're
+    /// displayed and saved. Returns whether anything moved.
+    pub fn sort_columns(&mut self) -> bool {
+        let before = self.labels.clone();
+        let mut pairs = self
+            .labels
+            .drain(..)
+            .zip(self.columns.drain(..))
+            .collect::<Vec<_>>();
+        // Stable, so names only differing by case keep their order
+        pairs.sort_by_key(|(label, _)| label.name.to_lowercase());
+        (self.labels, self.columns) = pairs.into_iter().unzip();
+        let moved = self.labels != before;
+        if moved {
+            self.dirty = true;
+        }
+        tracing::debug!(moved, "sorted columns");
+        moved
+    }
+
+    /// Spawns a row directly below `entity` holding a copy of each of its values. Values are
+    /// read out as owned [`Value`]s so Text/FilePath cells get their own `String`s rather than
+    /// sharing the original's bytes. Unique columns are left empty as the copy would clash.
+    pub fn duplicate_row(&mut self, entity: Entity) -> Entity {
+        let copy = self.spawn_at(entity, RowPlacement::Below);
+        for label in self.labels.clone() {
+            if self.column(&label).config.unique && label.data != LabelDataKind::Unit {
+                continue;
+            }
+            if let Some(value) = self.get_value(&label, entity) {
+                self.insert_value(&label, copy, value);
+            }
+        }
+        tracing::debug!(?entity, ?copy, "duplicated row");
+        copy
+    }
+
+    /// Rows that repeat another row's value for `label`, i.e. every row but one of each group
+    /// sharing a value. The most recently modified row of each group is kept, the first one
+    /// when that's a tie. Rows without a value for `label` are never duplicates.
+    pub fn duplicates(&self, label: &Label) -> Vec<Entity> {
+        let mut keyed = self
+            .ordered_entities()
+            .into_iter()
+            .map(|entity| (self.get_value(label, entity), entity))
+            .filter(|(value, _)| value.is_some())
+            .collect::<Vec<_>>();
+        // Stable, so each group stays in natural order
+        keyed.sort_by(|(a, _), (b, _)| compare_values(&label.data, a, b, SortDirection::Ascending));
+
+        let mut extras = vec![];
+        for group in keyed.chunk_by(|(a, _), (b, _)| a == b) {
+            // `max_by_key` picks the last of equal keys, so search from the back
+            let keep = group
+                .iter()
+                .rev()
+                .max_by_key(|(_, entity)| self.modified_at(*entity))
+                .map(|&(_, entity)| entity);
+            extras.extend(
+                group
+                    .iter()
+                    .map(|&(_, entity)| entity)
+                    .filter(|&entity| Some(entity) != keep),
+            );
+        }
+        extras
+    }
+
+    /// Creates a new column holding a copy of every value in `src`, placed right after it.
+    /// Returns the label of the new column.
+    pub fn duplicate_column(&mut self, src: &Label) -> Result<Label, String> {
+        let mut name = format!("{} (copy)", src.name);
+        while self.labels.iter().any(|label| label.name == name) {
+            name.push_str(" (copy)");
+        }
+        let label = Label {
+            name,
+            data: src.data.clone(),
+        };
+
+        let mut column = Column::new(&mut self.world, &label.data)?;
+        self.dirty = true;
+        column.config = self.column(src).config.clone();
+
+        let ColumnHandle(idx) = self.handle(src);
+        self.labels.insert(idx + 1, label.clone());
+        self.columns.insert(idx + 1, column);
+
+        for entity in self.find(src, |_| true) {
+            let value = self.get_value(src, entity).unwrap();
+            self.insert_value(&label, entity, value);
+        }
+
+        tracing::debug!(src = %src.name, dst = %label.name, "duplicated column");
+        Ok(label)
+    }
+
+    /// The `(entity, old, new)` values in the Text column `label` that replacing `find` with
+    /// `with` would change, in row order. `whole_value` only matches values equal to `find`.
+    pub fn replace_preview(
+        &self,
+        label: &Label,
+        find: &str,
+        with: &str,
+        whole_value: bool,
+    ) -> Vec<(Entity, String, String)> {
+        if find.is_empty() {
+            return vec![];
+        }
+
+        self.ordered_entities()
+            .into_iter()
+            .filter_map(|entity| {
+                let Some(Value::Text(old)) = self.get_value(label, entity) else {
+                    return None;
+                };
+                let new = match whole_value {
+                    true if old == find => with.to_string(),
+                    true => return None,
+                    false => old.replace(find, with),
+                };
+                (new != old).then_some((entity, old, new))
+            })
+            .collect()
+    }
+
+    /// Applies [`ReplayDB::replace_preview`], returning each changed row along with its
+    /// [`ReplayDB::row_file`] from before the change. Nothing is replaced if any new value
+    /// breaks the column's rules.
+    pub fn replace_text(
+        &mut self,
+        label: &Label,
+        find: &str,
+        with: &str,
+        whole_value: bool,
+    ) -> Result<Vec<(Entity, RowFile)>, String> {
+        let changes = self.replace_preview(label, find, with, whole_value);
+        let unique = self.column(label).config.unique;
+        for (n, (entity, _, new)) in changes.iter().enumerate() {
+            let value = Value::Text(new.clone());
+            let repeated = unique && changes[..n].iter().any(|(_, _, other)| other == new);
+            self.check_value(label, Some(*entity), &value)
+                .and_then(|()| match repeated {
+                    true => Err("is already used by another row".to_string()),
+                    false => Ok(()),
+                })
+                .map_err(|err| format!("`{new}` {err}"))?;
+        }
+
+        let mut changed = Vec::new();
+        for (entity, _, new) in changes {
+            changed.push((entity, self.row_file(entity)));
+            self.remove_value(label, entity);
+            self.insert_value(label, entity, Value::Text(new));
+            self.touch(entity);
+        }
+
+        tracing::debug!(label = %label.name, find, with, changed = changed.len(), "replaced text");
+        Ok(changed)
+    }
+
+    /// Reads the CSV file at `path`, whose header names the column each field belongs to, ready
+    /// for [`ReplayDB::import_csv_batch`] to spawn its records as rows. Only a bad header
+    /// stops the import, bad records are skipped as they're reached. Headers that don't name a
+    /// column yet are listed in [`CsvImport::new_columns`] with a kind suggested from their
+    /// values, [`ReplayDB::create_import_columns`] has to create them before importing.
+    pub fn start_csv_import(&self, path: &Path) -> Result<CsvImport, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("failed to read `{}`: {err}", path.display()))?;
+        let mut records = csv::parse(&text)
+            .map_err(|err| format!("failed to parse `{}`: {err}", path.display()))?
+            .into_iter()
+            // Blank lines don't hold any rows
+            .filter(|record| record.iter().any(|field| !field.is_empty()));
+        let header = records
+            .next()
+            .ok_or_else(|| format!("`{}` has no header", path.display()))?;
+
+        let mut columns = Vec::<Option<Label>>::new();
+        let mut unknown = Vec::<(usize, &str)>::new();
+        for (field, name) in header.iter().enumerate() {
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(format!("column {} has no name", field + 1));
+            }
+            if columns.iter().flatten().any(|label| label.name == name)
+                || unknown.iter().any(|(_, unknown)| *unknown == name)
+            {
+                return Err(format!("column `{name}` appears more than once"));
+            }
+            match self.labels.iter().find(|label| label.name == name) {
+                Some(label) if self.is_locked(label) => {
+                    return Err(format!("column `{name}` is locked"));
+                }
+                Some(label) => columns.push(Some(label.clone())),
+                None => {
+                    unknown.push((field, name));
+                    columns.push(None);
+                }
+            }
+        }
+        if let Some(label) = self.missing_required(columns.iter().flatten()) {
+            return Err(format!("required column `{}` is missing", label.name));
+        }
+
+        // The header is row 1
+        let records = records
+            .enumerate()
+            .map(|(n, record)| (n + 2, record))
+            .collect::<Vec<_>>();
+        let new_columns = unknown
+            .into_iter()
+            .map(|(field, name)| {
+                let samples = records
+                    .iter()
+                    .filter_map(|(_, record)| Some(record.get(field)?.trim()))
+                    .filter(|value| !value.is_empty())
+                    .take(IMPORT_SAMPLE)
+                    .map(str::to_string)
+                    .collect::<Vec<_>>();
+                let kind = suggest_kind(&samples);
+                NewCsvColumn {
+                    name: name.to_string(),
+                    field,
+                    kind: NEW_LABEL_KINDS
+                        .iter()
+                        .position(|new| *new == kind)
+                        .unwrap_or(0),
+                    samples,
+                }
+            })
+            .collect();
+        Ok(CsvImport {
+            path: path.to_path_buf(),
+            columns,
+            total: records.len(),
+            records: records.into_iter(),
+            imported: 0,
+            errors: vec![],
+            new_columns,
+            selected: 0,
+        })
+    }
+
+    /// Creates the columns `import` found in its header but not in the schema, as the kind each
+    /// was left on, so the rest of the import can fill them in
+    pub fn create_import_columns(&mut self, import: &mut CsvImport) -> Result<(), String> {
+        for column in import.new_columns.drain(..) {
+            let label = self.add_column(&column.name, NEW_LABEL_KINDS[column.kind].clone())?;
+            import.columns[column.field] = Some(label);
+        }
+        Ok(())
+    }
+
+    /// Spawns a row for each of the next `batch` records of `import`, blank fields leave the
+    /// row without a value for that column. Records that don't fit the columns or break their
+    /// rules are skipped and their error kept in [`CsvImport::errors`]. Returns whether every
+    /// record is done.
+    pub fn import_csv_batch(&mut self, import: &mut CsvImport, batch: usize) -> bool {
+        // The viewer stays usable between batches so the schema may have changed since the last
+        for column in &mut import.columns {
+            if let Some(label) = column
+                && (!self.labels.contains(label) || self.is_locked(label))
+            {
+                import.errors.push(format!(
+                    "column `{}` changed partway through, the rest of its values were skipped",
+                    label.name
+                ));
+                *column = None;
+            }
+        }
+
+        for (row, record) in import.records.by_ref().take(batch) {
+            if record.len() != import.columns.len() {
+                import.errors.push(format!(
+                    "row {row} has {} fields but the header has {}",
+                    record.len(),
+                    import.columns.len()
+                ));
+                continue;
+            }
+
+            let values = import
+                .columns
+                .iter()
+                .zip(&record)
+                .filter_map(|(label, field)| Some((label.as_ref()?, field)))
+                .filter(|(_, field)| !field.is_empty())
+                .map(|(label, field)| {
+                    Value::parse(&label.data, field)
+                        .and_then(|value| {
+                            self.check_value(label, None, &value)?;
+                            Ok((label, value))
+                        })
+                        .map_err(|err| format!("row {row}, column `{}`: {err}", label.name))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .and_then(|values| {
+                    match self.missing_required(values.iter().map(|(label, _)| *label)) {
+                        Some(label) => Err(format!(
+                            "row {row}, column `{}`: can't be left blank",
+                            label.name
+                        )),
+                        None => Ok(values),
+                    }
+                });
+            let values = match values {
+                Ok(values) => values,
+                Err(err) => {
+                    import.errors.push(err);
+                    continue;
+                }
+            };
+
+            let entity = self.spawn();
+            for (label, value) in values {
+                self.insert_value(label, entity, value);
+            }
+            import.imported += 1;
+        }
+
+        let done = import.records.len() == 0;
+        if done {
+            tracing::debug!(
+                path = %import.path.display(),
+                imported = import.imported,
+                skipped = import.errors.len(),
+                "imported csv"
+            );
+        }
+        done
+    }
+
+    /// Writes everything stored for `entity` to `path` as a standalone RON [`RowFile`], so a
+    /// single replay can be shared and read back with [`ReplayDB::import_row`]
+    pub fn export_row(&self, entity: Entity, path: &Path) -> Result<(), String> {
+        let text =
+            ron::ser::to_string_pretty(&self.row_file(entity), ron::ser::PrettyConfig::default())
+                .map_err(|err| format!("failed to serialize row: {err}"))?;
+        std::fs::write(path, text)
+            .map_err(|err| format!("failed to write `{}`: {err}", path.display()))?;
+        tracing::debug!(?entity, path = %path.display(), "exported row");
+        Ok(())
+    }
+
+    /// Spawns a row from a [`RowFile`] written by [`ReplayDB::export_row`]. Every value and note
+    /// has to belong to an unlocked column of the current schema with a matching kind and follow
+    /// its rules, otherwise nothing is spawned.
+    pub fn import_row(&mut self, path: &Path) -> Result<Entity, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("failed to read `{}`: {err}", path.display()))?;
+        let row: RowFile = ron::from_str(&text)
+            .map_err(|err| format!("failed to parse `{}`: {err}", path.display()))?;
+
+        let find_label = |name: &str| {
+            let label = self
+                .labels
+                .iter()
+                .find(|label| label.name == name)
+                .ok_or_else(|| format!("unknown column `{name}`"))?;
+            if self.is_locked(label) {
+                return Err(format!("column `{name}` is locked"));
+            }
+            Ok(label.clone())
+        };
+        let values = row
+            .values
+            .iter()
+            .map(|(name, value)| {
+                let label = find_label(name)?;
+                if !value.fits(&label.data) {
+                    return Err(format!(
+                        "a {value:?} value doesn't fit the {:?} column `{name}`",
+                        label.data
+                    ));
+                }
+                self.check_value(&label, None, value)
+                    .map_err(|err| format!("column `{name}`: {err}"))?;
+                Ok((label, value.clone()))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        if let Some(label) = self.missing_required(values.iter().map(|(label, _)| label)) {
+            return Err(format!("column `{}`: can't be left blank", label.name));
+        }
+        let notes = row
+            .notes
+            .iter()
+            .map(|(name, note)| Ok((find_label(name)?, note.clone())))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let entity = self.spawn();
+        for (label, value) in values {
+            self.insert_value(&label, entity, value);
+        }
+        for (label, note) in notes {
+            self.notes.insert((entity, label), note);
+        }
+        tracing::debug!(?entity, path = %path.display(), "imported row");
+        Ok(entity)
+    }
+
+    /// Writes just the column definitions to `path` as a [`SchemaFile`], without any rows
+    pub fn export_schema(&self, path: &Path) -> Result<(), String> {
+        let schema = SchemaFile {
+            columns: self.column_files(),
+        };
+        let text = ron::ser::to_string_pretty(&schema, ron::ser::PrettyConfig::default())
+            .map_err(|err| format!("failed to serialize schema: {err}"))?;
+        std::fs::write(path, text)
+            .map_err(|err| format!("failed to write `{}`: {err}", path.display()))?;
+        tracing::debug!(path = %path.display(), "exported schema");
+        Ok(())
+    }
+
+    /// Reads the columns of a [`SchemaFile`] written by [`ReplayDB::export_schema`], checked
+    /// the same way a database's columns are when it's loaded
+    pub fn read_schema(path: &Path) -> Result<Vec<ColumnFile>, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("failed to read `{}`: {err}", path.display()))?;
+        let schema: SchemaFile = ron::from_str(&text)
+            .map_err(|err| format!("failed to parse `{}`: {err}", path.display()))?;
+        let file = ReplayDBFile {
+            version: FORMAT_VERSION,
+            columns: schema.columns,
+            rows: Vec::new(),
+        };
+        Self::from_file(file)
+            .map(|db| db.column_files())
+            .map_err(|err| format!("invalid schema `{}`: {err}", path.display()))
+    }
+
+    /// Adds the schema `columns` this database doesn't have yet and overwrites the config of
+    /// the ones it does, matched by name. Columns missing from the schema and every value are
+    /// left alone. Nothing changes if a column's kind differs from the schema's. Returns what
+    /// was done to each column that changed.
+    pub fn import_schema(&mut self, columns: Vec<ColumnFile>) -> Result<Vec<String>, String> {
+        let existing =
+            |db: &Self, name: &str| db.labels.iter().find(|label| label.name == name).cloned();
+        for column in &columns {
+            if let Some(label) = existing(self, &column.name)
+                && label.data != column.kind
+            {
+                return Err(format!(
+                    "column `{}` is {:?} but {:?} in the schema",
+                    column.name, label.data, column.kind
+                ));
+            }
+        }
+
+        let mut changes = Vec::new();
+        for column in columns {
+            let label = match existing(self, &column.name) {
+                Some(label) if self.column(&label).config == column.config => continue,
+                Some(label) => {
+                    changes.push(format!("updated column `{}`", label.name));
+                    label
+                }
+                None => {
+                    let label = self.add_column(&column.name, column.kind)?;
+                    changes.push(format!("added column `{}`", label.name));
+                    label
+                }
+            };
+            self.dirty = true;
+            self.column_mut(&label).config = column.config;
+        }
+        Ok(changes)
+    }
+
+    /// Returns every entity with a component for `label` whose value satisfies `pred`.
+    /// Entities lacking the component are never passed to `pred`.
+    pub fn find<F: Fn(&Value) -> bool>(&self, label: &Label, pred: F) -> Vec<Entity> {
+        self.world
+            .join(WithEntities)
+            .filter(|&entity| {
+                self.get_value(label, entity)
+                    .is_some_and(|value| pred(&value))
+            })
+            .collect()
+    }
+}
+
+#[derive(Eq, PartialEq, Hash, Debug, Clone)]
+pub struct Label {
+    pub name: String,
+    pub data: LabelDataKind,
+}
+
+#[derive(Eq, PartialEq, Hash, Debug, Clone, Serialize, Deserialize)]
+pub enum LabelDataKind {
+    Number,
+    /// A fractional number, e.g. a split time in seconds, stored as an `f64`
+    Decimal,
+    Text,
+    Unit,
+    /// A path to a replay file, stored as a `String`
+    FilePath,
+    /// True or false, stored as a `bool`. Unlike Unit a false value is still a value.
+    Bool,
+    /// One of a fixed list of names, e.g. a category or platform, stored as the `u16` index of
+    /// the chosen variant. Only defined in the database file's column list.
+    Enum {
+        variants: Vec<String>,
+    },
+}
+
+impl LabelDataKind {
+    /// The layout of the components stored in a column of this kind
+    fn layout(&self) -> alloc::Layout {
+        match self {
+            LabelDataKind::Number => alloc::Layout::new::<i16>(),
+            LabelDataKind::Decimal => alloc::Layout::new::<f64>(),
+            LabelDataKind::Text | LabelDataKind::FilePath => alloc::Layout::new::<String>(),
+            LabelDataKind::Unit => alloc::Layout::new::<()>(),
+            LabelDataKind::Bool => alloc::Layout::new::<bool>(),
+            LabelDataKind::Enum { .. } => alloc::Layout::new::<u16>(),
+        }
+    }
+}
+
+/// A typed copy of a single component read out of a [`TypedTable`]
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub enum Value {
+    Number(i16),
+    Decimal(f64),
+    Text(String),
+    Unit,
+    FilePath(String),
+    Bool(bool),
+    /// The name of the chosen variant
+    Enum(String),
+}
+
+impl Value {
+    /// How the value is written in an editor field, the reverse of [`Value::parse`]
+    pub fn input_text(self) -> String {
+        match self {
+            Value::Number(n) => format!("{n}"),
+            Value::Decimal(d) => format!("{d}"),
+            Value::Text(s) | Value::FilePath(s) | Value::Enum(s) => s,
+            Value::Bool(b) => b.to_string(),
+            Value::Unit => UNIT_SET.to_string(),
+        }
+    }
+
+    /// Whether this value can be stored in a `kind` column
+    fn fits(&self, kind: &LabelDataKind) -> bool {
+        match (self, kind) {
+            (Value::Number(_), LabelDataKind::Number)
+            | (Value::Decimal(_), LabelDataKind::Decimal)
+            | (Value::Text(_), LabelDataKind::Text)
+            | (Value::Unit, LabelDataKind::Unit)
+            | (Value::FilePath(_), LabelDataKind::FilePath)
+            | (Value::Bool(_), LabelDataKind::Bool) => true,
+            (Value::Enum(name), LabelDataKind::Enum { variants }) => variants.contains(name),
+            _ => false,
+        }
+    }
+
+    /// Parses text typed by the user or read from a file into a value for a `kind` column.
+    /// Unit columns don't store anything so any text is accepted.
+    pub fn parse(kind: &LabelDataKind, text: &str) -> Result<Value, String> {
+        Ok(match kind {
+            LabelDataKind::Number => Value::Number(parse_number(text)?),
+            LabelDataKind::Decimal => Value::Decimal(parse_decimal(text)?),
+            LabelDataKind::Text => Value::Text(text.to_string()),
+            LabelDataKind::Unit => Value::Unit,
+            LabelDataKind::FilePath => Value::FilePath(text.to_string()),
+            LabelDataKind::Bool => match text.trim() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                _ => return Err("must be true or false".to_string()),
+            },
+            LabelDataKind::Enum { variants } => {
+                let name = text.trim();
+                if !variants.iter().any(|variant| variant == name) {
+                    return Err(format!("must be one of {}", variants.join(", ")));
+                }
+                Value::Enum(name.to_string())
+            }
+        })
+    }
+}
+
+/// Orders two values from a `kind` column for sorting rows. Missing values always sort last,
+/// except in Unit columns where absence is the value.
+pub fn compare_values(
+    kind: &LabelDataKind,
+    a: &Option<Value>,
+    b: &Option<Value>,
+    direction: SortDirection,
+) -> Ordering {
+    let ordering = match (a, b) {
+        _ if *kind == LabelDataKind::Unit => a.is_some().cmp(&b.is_some()),
+        (None, None) => return Ordering::Equal,
+        (None, Some(_)) => return Ordering::Greater,
+        (Some(_), None) => return Ordering::Less,
+        (Some(a), Some(b)) => match (a, b) {
+            (Value::Number(a), Value::Number(b)) => a.cmp(b),
+            (Value::Decimal(a), Value::Decimal(b)) => a.total_cmp(b),
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (Value::Text(a), Value::Text(b)) | (Value::FilePath(a), Value::FilePath(b)) => a.cmp(b),
+            // Variants sort in the order they're declared in rather than alphabetically
+            (Value::Enum(a), Value::Enum(b)) => match kind {
+                LabelDataKind::Enum { variants } => {
+                    let idx = |name: &String| variants.iter().position(|variant| variant == name);
+                    idx(a).cmp(&idx(b))
+                }
+                _ => Ordering::Equal,
+            },
+            _ => Ordering::Equal,
+        },
+    };
+
+    match direction {
+        SortDirection::Ascending => ordering,
+        SortDirection::Descending => ordering.reverse(),
+    }
+}
+
+/// A CSV import in progress, see [`ReplayDB::start_csv_import`]
+pub struct CsvImport {
+    pub path: PathBuf,
+    /// The column each field belongs to, from the header. `None` until a new column has been
+    /// created, or once the column has been deleted, renamed or locked partway through the
+    /// import, its fields are then skipped.
+    columns: Vec<Option<Label>>,
+    /// Records yet to be imported along with their row in the file
+    records: std::vec::IntoIter<(usize, Vec<String>)>,
+    pub total: usize,
+    /// How many rows have been spawned so far
+    pub imported: usize,
+    /// Why each skipped record couldn't be imported, in file order
+    pub errors: Vec<String>,
+    /// Header columns that aren't in the schema yet, nothing is imported until they're created
+    pub new_columns: Vec<NewCsvColumn>,
+    /// Index into `new_columns` of the one whose kind Left/Right changes
+    pub selected: usize,
+}
+
+/// A column a [`CsvImport`] will create, see [`ReplayDB::create_import_columns`]
+pub struct NewCsvColumn {
+    pub name: String,
+    /// Index of the column's field in each record
+    field: usize,
+    /// Index into [`NEW_LABEL_KINDS`], starting out as [`suggest_kind`]'s pick
+    pub kind: usize,
+    /// The first few non-empty values of the column, for showing how well a kind fits them
+    pub samples: Vec<String>,
+}
+
+impl NewCsvColumn {
+    /// How many of the sampled values parse as the column's current kind
+    pub fn fitting_samples(&self) -> usize {
+        let kind = &NEW_LABEL_KINDS[self.kind];
+        self.samples
+            .iter()
+            .filter(|value| Value::parse(kind, value).is_ok())
+            .count()
+    }
+}
+
+/// Column kinds a new label can be created with, in the order the popup cycles through them.
+/// Enums need a variant list so they can only be defined in the database file.
+pub const NEW_LABEL_KINDS: [LabelDataKind; 6] = [
+    LabelDataKind::Text,
+    LabelDataKind::Number,
+    LabelDataKind::Decimal,
+    LabelDataKind::Unit,
+    LabelDataKind::Bool,
+    LabelDataKind::FilePath,
+];
+
+#[test]
+fn find_number_predicate() {
+    let mut db = ReplayDB::new();
+    db.seed_demo_data(10);
+    let split = db.labels[1].clone();
+
+    let entity = db.spawn();
+    db.insert_value(&split, entity, Value::Number(1000));
+
+    let found = db.find(&split, |value| *value == Value::Number(1000));
+    assert_eq!(found, vec![entity]);
+
+    let found = db.find(
+        &split,
+        |value| matches!(value, Value::Number(n) if *n > 182),
+    );
+    assert_eq!(found, vec![entity]);
+}
+
+#[test]
+fn find_text_predicate() {
+    let mut db = ReplayDB::new();
+    db.seed_demo_data(10);
+    let name = db.labels[0].clone();
+
+    let entity = db.spawn();
+    db.insert_value(&name, entity, Value::Text("Tower of Trials".to_string()));
+
+    let found = db.find(&name, |value| match value {
+        Value::Text(text) => text.contains("Trials"),
+        _ => false,
+    });
+    assert_eq!(found, vec![entity]);
+}
+
+#[test]
+fn find_skips_absent_components() {
+    let mut db = ReplayDB::new();
+    db.seed_demo_data(10);
+    let name = db.labels[0].clone();
+    let split = db.labels[1].clone();
+
+    let entity = db.spawn();
+    db.insert_value(&name, entity, Value::Text("no split".to_string()));
+
+    assert!(!db.find(&split, |_| true).contains(&entity));
+    assert!(db.find(&name, |_| true).contains(&entity));
+}
+
+#[test]
+fn duplicates_keep_the_latest_of_each_value() {
+    let mut db = ReplayDB::new();
+    let [name, split, ..] = db.labels.clone().try_into().unwrap();
+    let mut spawn = |value: Option<&str>, modified| {
+        let entity = db.spawn();
+        if let Some(value) = value {
+            db.insert_value(&name, entity, Value::Text(value.to_string()));
+        }
+        set_timestamp(&mut db.modified, &mut db.world, entity, modified);
+        entity
+    };
+    let old_tower = spawn(Some("Tower"), 100);
+    let pit = spawn(Some("Pit"), 100);
+    let new_tower = spawn(Some("Tower"), 200);
+    let unnamed = spawn(None, 100);
+    let pit_again = spawn(Some("Pit"), 100);
+    let also_unnamed = spawn(None, 100);
+
+    // Ties keep the first row, rows without a name are left alone
+    assert_eq!(db.duplicates(&name), [pit_again, old_tower]);
+    assert!(db.duplicates(&split).is_empty());
+
+    for entity in db.duplicates(&name) {
+        db.despawn(entity);
+    }
+    assert_eq!(
+        db.ordered_entities(),
+        [pit, new_tower, unnamed, also_unnamed]
+    );
+    assert!(db.duplicates(&name).is_empty());
+}
+
+#[test]
+fn duplicate_column_copies_values() {
+    let mut db = ReplayDB::new();
+    db.seed_demo_data(10);
+
+    for src in db.labels.clone() {
+        let copy = db.duplicate_column(&src).unwrap();
+        assert_eq!(copy.name, format!("{} (copy)", src.name));
+        assert_eq!(copy.data, src.data);
+
+        for entity in db.ordered_entities() {
+            assert_eq!(db.get_value(&src, entity), db.get_value(&copy, entity));
+        }
+    }
+
+    // Copying a copy shouldn't reuse an existing name
+    let name = db.labels[0].clone();
+    let copy = db.duplicate_column(&name).unwrap();
+    assert_eq!(copy.name, "Name (copy) (copy)");
+}
+
+#[test]
+fn every_column_kind_fits_in_a_table() {
+    let kinds = [
+        LabelDataKind::Number,
+        LabelDataKind::Decimal,
+        LabelDataKind::Text,
+        LabelDataKind::Unit,
+        LabelDataKind::FilePath,
+        LabelDataKind::Bool,
+        LabelDataKind::Enum {
+            variants: Vec::new(),
+        },
+    ];
+    let sizes = kinds.map(|kind| kind.layout().size());
+    assert!(
+        sizes
+            .iter()
+            .all(|&size| size <= typed_table::MAX_COMPONENT_SIZE)
+    );
+    assert_eq!(sizes.iter().max(), Some(&typed_table::MAX_COMPONENT_SIZE));
+}
+
+#[test]
+fn timestamps_format_as_utc_dates() {
+    assert_eq!(format_timestamp(0), "1970-01-01 00:00");
+    assert_eq!(format_timestamp(951782400), "2000-02-29 00:00");
+    assert_eq!(format_timestamp(1700000000), "2023-11-14 22:13");
+    assert_eq!(format_timestamp(-60), "1969-12-31 23:59");
+}
+
+#[test]
+fn spawn_records_timestamps() {
+    let mut db = ReplayDB::new();
+    let before = unix_now();
+    let entity = db.spawn();
+
+    let created = db.created_at(entity).unwrap();
+    assert!(created >= before);
+    assert_eq!(db.modified_at(entity), Some(created));
+
+    db.touch(entity);
+    assert!(db.modified_at(entity).unwrap() >= created);
+    assert_eq!(db.created_at(entity), Some(created));
+}
+
+#[test]
+fn file_path_cells_flag_missing_files() {
+    let mut db = ReplayDB::new();
+    db.seed_demo_data(1);
+    let label = db.labels[3].clone();
+    assert_eq!(label.data, LabelDataKind::FilePath);
+    let entity = db.ordered_entities()[0];
+
+    let existing = env!("CARGO_MANIFEST_DIR").to_string();
+    db.insert_value(&label, entity, Value::FilePath(existing.clone()));
+    assert_eq!(
+        db.get_value(&label, entity),
+        Some(Value::FilePath(existing.clone()))
+    );
+    assert_eq!(
+        db.cell_text(&label, entity, false),
+        format!("{} {existing}", FILE_ICON)
+    );
+
+    let missing = "/does/not/exist.ddr".to_string();
+    db.remove_value(&label, entity);
+    db.insert_value(&label, entity, Value::FilePath(missing.clone()));
+    assert!(
+        db.cell_text(&label, entity, false)
+            .ends_with(MISSING_FILE_MARKER)
+    );
+}
+
+#[test]
+fn saving_keeps_the_previous_save() {
+    let path = std::env::temp_dir().join(format!("ddreplayer-backup-{}.ron", std::process::id()));
+    let mut db = ReplayDB::new();
+    db.save_to_path(&path).unwrap();
+    assert!(!ReplayDB::backup_path(&path).exists());
+
+    db.spawn();
+    db.save_to_path(&path).unwrap();
+    let backup = ReplayDB::load_from_path(&ReplayDB::backup_path(&path)).unwrap();
+    let saved = ReplayDB::load_from_path(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(ReplayDB::backup_path(&path)).unwrap();
+
+    assert_eq!(backup.ordered_entities().len(), 0);
+    assert_eq!(saved.ordered_entities().len(), 1);
+}
+
+#[test]
+fn save_and_load_round_trip() {
+    let labels = [
+        ("Name", LabelDataKind::Text),
+        ("800 Split", LabelDataKind::Number),
+        ("PB", LabelDataKind::Unit),
+        ("Replay", LabelDataKind::FilePath),
+    ]
+    .map(|(name, data)| Label {
+        name: name.to_string(),
+        data,
+    });
+    let mut db =
+        ReplayDB::with_columns(labels.clone().map(|label| (label, LabelConfig::default())))
+            .unwrap();
+    db.toggle_lock(&labels[1]);
+
+    let values = [
+        Value::Text("Tower of Trials".to_string()),
+        Value::Number(-12),
+        Value::Unit,
+        Value::FilePath("replays/tot.ddr".to_string()),
+    ];
+    // Every combination of present/missing columns, including an entity with none at all
+    for mask in 0..(1 << labels.len()) {
+        let entity = db.spawn();
+        for (n, (label, value)) in labels.iter().zip(&values).enumerate() {
+            if mask & (1 << n) != 0 {
+                db.insert_value(label, entity, value.clone());
+            }
+        }
+    }
+    let entity = db.ordered_entities()[3];
+    db.notes
+        .insert((entity, labels[0].clone()), "fell off".to_string());
+
+    let path = std::env::temp_dir().join(format!("ddreplayer-{}.ron", std::process::id()));
+    db.save_to_path(&path).unwrap();
+    let loaded = ReplayDB::load_from_path(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.to_file(), db.to_file());
+    assert!(loaded.is_locked(&labels[1]));
+    for (a, b) in db
+        .ordered_entities()
+        .into_iter()
+        .zip(loaded.ordered_entities())
+    {
+        for label in &labels {
+            assert_eq!(db.get_value(label, a), loaded.get_value(label, b));
+        }
+    }
+}
+
+#[test]
+fn load_rejects_mismatched_values() {
+    let file = ron::from_str(
+        r#"(
+            columns: [(name: "Split", kind: Number)],
+            rows: [(values: {"Split": Text("fast")})],
+        )"#,
+    )
+    .unwrap();
+    assert!(ReplayDB::from_file(file).is_err());
+}
+
+#[test]
+fn files_from_newer_versions_are_refused() {
+    let path = std::env::temp_dir().join(format!("ddreplayer-format-{}.ron", std::process::id()));
+    let columns =
+        r#"columns: [(name: "Split", kind: Number)], rows: [(values: {"Split": Number(1)})]"#;
+
+    // Saved before the format was versioned
+    std::fs::write(&path, format!("({columns})")).unwrap();
+    let db = ReplayDB::load_from_path(&path).unwrap();
+    assert_eq!(db.to_file().version, FORMAT_VERSION);
+    assert_eq!(db.ordered_entities().len(), 1);
+
+    // Even where a newer layout parses, saving it again would drop whatever this can't read
+    let newer = FORMAT_VERSION + 1;
+    std::fs::write(&path, format!("(version: {newer}, {columns}, tags: [])")).unwrap();
+    let err = ReplayDB::load_from_path(&path).err().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(err.contains(&format!("is format version {newer}")), "{err}");
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Bytes allocated minus bytes freed by the current thread, see [`CountingAlloc`]
+    static LIVE_BYTES: std::cell::Cell<isize> = const { std::cell::Cell::new(0) };
+}
+
+/// Tracks [`LIVE_BYTES`] so tests can check they don't leak. Tests run on their own threads
+/// so this isn't thrown off by other tests running in parallel.
+#[cfg(test)]
+struct CountingAlloc;
+
+#[cfg(test)]
+unsafe impl alloc::GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: alloc::Layout) -> *mut u8 {
+        _ = LIVE_BYTES.try_with(|live| live.set(live.get() + layout.size() as isize));
+        unsafe { std::alloc::System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: alloc::Layout) {
+        _ = LIVE_BYTES.try_with(|live| live.set(live.get() - layout.size() as isize));
+        unsafe { std::alloc::System.dealloc(ptr, layout) }
+    }
+}
+
+#[cfg(test)]
+#[global_allocator]
+static ALLOCATOR: CountingAlloc = CountingAlloc;
+
+#[test]
+fn removing_text_values_frees_strings() {
+    let mut db = ReplayDB::new();
+    let name = db.labels[0].clone();
+    let entities = (0..1000).map(|_| db.spawn()).collect::<Vec<_>>();
+
+    let round = |db: &mut ReplayDB, n: usize| {
+        for &entity in &entities {
+            db.insert_value(&name, entity, Value::Text(format!("replay {n}")));
+        }
+        for &entity in &entities {
+            db.remove_value(&name, entity);
+        }
+    };
+
+    // The first round grows the column's storage, which is kept around afterwards
+    round(&mut db, 0);
+    let before = LIVE_BYTES.with(|live| live.get());
+    for n in 1..5 {
+        round(&mut db, n);
+    }
+    assert_eq!(LIVE_BYTES.with(|live| live.get()), before);
+}
+
+#[test]
+fn decimal_columns_store_f64() {
+    let label = Label {
+        name: "Time".to_string(),
+        data: LabelDataKind::Decimal,
+    };
+    let mut db = ReplayDB::with_columns([(label.clone(), LabelConfig::default())]).unwrap();
+    let entity = db.spawn();
+
+    db.insert_value(&label, entity, Value::Decimal(83.4567));
+    assert_eq!(db.get_value(&label, entity), Some(Value::Decimal(83.4567)));
+    assert_eq!(db.cell_text(&label, entity, false), "83.457");
+
+    assert_eq!(parse_decimal(" 12.5 "), Ok(12.5));
+    for invalid in ["", "abc", "NaN", "inf", "1.2.3"] {
+        assert!(
+            parse_decimal(invalid).is_err(),
+            "{invalid:?} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn move_column_reorders_labels() {
+    let mut db = ReplayDB::new();
+    db.seed_demo_data(2);
+    let names = |db: &ReplayDB| {
+        db.labels
+            .iter()
+            .map(|label| label.name.clone())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(db.move_column(0, true), Some(1));
+    assert_eq!(names(&db), ["800 Split", "Name", "PB", "Replay"]);
+    assert_eq!(db.move_column(0, false), None);
+    assert_eq!(db.move_column(3, true), None);
+
+    // The order is part of the saved schema
+    let file = db.to_file();
+    assert_eq!(file.columns[0].name, "800 Split");
+    let loaded = ReplayDB::from_file(file).unwrap();
+    assert_eq!(names(&loaded), names(&db));
+}
+
+#[test]
+fn columns_stay_with_their_labels() {
+    let mut db = ReplayDB::new();
+    db.seed_demo_data(5);
+    let by_name = |db: &ReplayDB| {
+        db.labels
+            .iter()
+            .map(|label| {
+                let values = db
+                    .ordered_entities()
+                    .into_iter()
+                    .map(|entity| db.get_value(label, entity))
+                    .collect::<Vec<_>>();
+                (label.name.clone(), values)
+            })
+            .collect::<HashMap<_, _>>()
+    };
+    let before = by_name(&db);
+
+    db.move_column(0, true);
+    db.sort_columns();
+    let copy = db.duplicate_column(&db.labels[0].clone()).unwrap();
+    db.delete_column(&copy).unwrap();
+    assert_eq!(db.columns.len(), db.labels.len());
+    assert_eq!(by_name(&db), before);
+
+    // Looping over handles reads the same values as looking each label up
+    let entity = db.ordered_entities()[0];
+    for (handle, label) in db.handles() {
+        assert_eq!(db.value_at(handle, entity), db.get_value(label, entity));
+    }
+}
+
+#[test]
+fn sort_columns_orders_labels_by_name() {
+    let mut db = ReplayDB::new();
+    db.seed_demo_data(2);
+    let names = |db: &ReplayDB| {
+        db.labels
+            .iter()
+            .map(|label| label.name.clone())
+            .collect::<Vec<_>>()
+    };
+
+    db.dirty = false;
+    assert!(db.sort_columns());
+    assert_eq!(names(&db), ["800 Split", "Name", "PB", "Replay"]);
+    assert!(db.dirty);
+
+    // Already sorted, so nothing needs saving
+    db.dirty = false;
+    assert!(!db.sort_columns());
+    assert!(!db.dirty);
+}
+
+#[test]
+fn add_column_requires_unique_names() {
+    let mut db = ReplayDB::new();
+    db.seed_demo_data(1);
+    let before = db.labels.clone();
+
+    assert!(db.add_column("Name", LabelDataKind::Number).is_err());
+    assert!(db.add_column("   ", LabelDataKind::Text).is_err());
+    assert_eq!(db.labels, before);
+    assert_eq!(db.columns.len(), before.len());
+
+    let label = db.add_column(" Time ", LabelDataKind::Decimal).unwrap();
+    assert_eq!(label.name, "Time");
+    assert_eq!(db.labels.last(), Some(&label));
+
+    let entity = db.ordered_entities()[0];
+    db.insert_value(&label, entity, Value::Decimal(1.5));
+    assert_eq!(db.get_value(&label, entity), Some(Value::Decimal(1.5)));
+}
+
+#[test]
+fn delete_column_keeps_the_last_one() {
+    let mut db = ReplayDB::new();
+    db.seed_demo_data(5);
+    let notes_label = db.labels[1].clone();
+    let entity = db.ordered_entities()[0];
+    db.notes
+        .insert((entity, notes_label.clone()), "note".to_string());
+
+    db.delete_column(&notes_label).unwrap();
+    assert!(!db.labels.contains(&notes_label));
+    assert_eq!(db.columns.len(), db.labels.len());
+    assert!(db.notes.is_empty());
+
+    let locked = db.labels[0].clone();
+    db.toggle_lock(&locked);
+    assert!(db.delete_column(&locked).is_err());
+    db.toggle_lock(&locked);
+
+    while db.labels.len() > 1 {
+        let label = db.labels[0].clone();
+        db.delete_column(&label).unwrap();
+    }
+    let last = db.labels[0].clone();
+    assert!(db.delete_column(&last).is_err());
+    assert_eq!(db.labels, vec![last]);
+}
+
+#[test]
+fn names_starting_with_ignores_case() {
+    let mut db = ReplayDB::new();
+    db.add_column("Notes", LabelDataKind::Text).unwrap();
+    assert_eq!(db.names_starting_with("n"), ["Name", "Notes"]);
+    assert_eq!(db.names_starting_with("NO"), ["Notes"]);
+    assert_eq!(db.names_starting_with("naem"), Vec::<String>::new());
+    assert_eq!(db.names_starting_with("").len(), db.labels.len());
+}
+
+#[test]
+fn label_count_follows_entities() {
+    let mut db = ReplayDB::new();
+    let [name, split, ..] = db.labels.clone().try_into().unwrap();
+    assert_eq!(db.label_count(&name), 0);
+
+    let [first, second] = [db.spawn(), db.spawn()];
+    db.insert_value(&name, first, Value::Text("Tower".to_string()));
+    db.insert_value(&name, second, Value::Text("Pit".to_string()));
+    db.insert_value(&split, second, Value::Number(30));
+    assert_eq!((db.label_count(&name), db.label_count(&split)), (2, 1));
+
+    db.remove_value(&name, first);
+    db.despawn(second);
+    assert_eq!((db.label_count(&name), db.label_count(&split)), (0, 0));
+}
+
+#[test]
+fn rename_label_keeps_column_data() {
+    let mut db = ReplayDB::new();
+    db.seed_demo_data(5);
+    let old = db.labels[1].clone();
+    let entities = db.ordered_entities();
+    let values = entities
+        .iter()
+        .map(|&entity| db.get_value(&old, entity))
+        .collect::<Vec<_>>();
+    db.notes
+        .insert((entities[0], old.clone()), "slow".to_string());
+
+    // Colliding or empty names are rejected without changing anything
+    assert!(db.rename_label(&old, "Name").is_err());
+    assert!(db.rename_label(&old, " ").is_err());
+    assert_eq!(db.labels[1], old);
+
+    let new = db.rename_label(&old, "Tower Split").unwrap();
+    assert_eq!(db.labels[1], new);
+    assert_eq!(new.data, old.data);
+    assert_eq!(db.columns.len(), db.labels.len());
+    for (&entity, value) in entities.iter().zip(values) {
+        assert_eq!(db.get_value(&new, entity), value);
+    }
+    assert_eq!(
+        db.notes.get(&(entities[0], new)).map(String::as_str),
+        Some("slow")
+    );
+}
+
+#[test]
+fn import_csv_spawns_typed_rows() {
+    let mut db = ReplayDB::new();
+    let [name, split, pb, _] = db.labels.clone().try_into().unwrap();
+    let path = std::env::temp_dir().join(format!("ddreplayer-{}.csv", std::process::id()));
+    // One record per batch, like a file much bigger than a batch
+    let import_csv = |db: &mut ReplayDB| -> Result<(usize, Vec<String>), String> {
+        let mut import = db.start_csv_import(&path)?;
+        while !db.import_csv_batch(&mut import, 1) {}
+        Ok((import.imported, import.errors))
+    };
+
+    std::fs::write(&path, "PB,Name,800 Split\nyes,\"Smith, J\",42\n,Doe,\n").unwrap();
+    assert_eq!(import_csv(&mut db), Ok((2, vec![])));
+    let [first, second] = db.ordered_entities().try_into().unwrap();
+    assert_eq!(
+        db.get_value(&name, first),
+        Some(Value::Text("Smith, J".to_string()))
+    );
+    assert_eq!(db.get_value(&split, first), Some(Value::Number(42)));
+    assert_eq!(db.get_value(&pb, first), Some(Value::Unit));
+    assert_eq!(db.get_value(&split, second), None);
+    assert_eq!(db.get_value(&pb, second), None);
+
+    // Bad records are skipped without stopping the rest
+    std::fs::write(&path, "Name,800 Split\nbad,1.5\nok,1\nshort\n").unwrap();
+    assert_eq!(
+        import_csv(&mut db),
+        Ok((
+            1,
+            vec![
+                "row 2, column `800 Split`: not a whole number".to_string(),
+                "row 4 has 1 fields but the header has 2".to_string()
+            ]
+        ))
+    );
+    assert_eq!(db.ordered_entities().len(), 3);
+
+    // A bad header means nothing gets imported
+    std::fs::write(&path, "Name,Name\nok,ok\n").unwrap();
+    assert_eq!(
+        import_csv(&mut db).map(|_| ()),
+        Err("column `Name` appears more than once".to_string())
+    );
+    assert_eq!(db.ordered_entities().len(), 3);
+
+    // Deleting a column between batches leaves its values out of the rest
+    std::fs::write(&path, "Name,800 Split\nfirst,1\nsecond,2\n").unwrap();
+    let mut import = db.start_csv_import(&path).unwrap();
+    assert!(!db.import_csv_batch(&mut import, 1));
+    db.delete_column(&split).unwrap();
+    assert!(db.import_csv_batch(&mut import, 1));
+    assert_eq!(import.imported, 2);
+    assert_eq!(
+        import.errors,
+        ["column `800 Split` changed partway through, the rest of its values were skipped"]
+    );
+    let last = *db.ordered_entities().last().unwrap();
+    assert_eq!(
+        db.get_value(&name, last),
+        Some(Value::Text("second".to_string()))
+    );
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn suggested_kinds_fit_every_sample() {
+    let samples = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+    assert_eq!(
+        suggest_kind(&samples(&["true", "false"])),
+        LabelDataKind::Bool
+    );
+    assert_eq!(suggest_kind(&samples(&["1", "-20"])), LabelDataKind::Number);
+    assert_eq!(
+        suggest_kind(&samples(&["1", "2.5"])),
+        LabelDataKind::Decimal
+    );
+    assert_eq!(
+        suggest_kind(&samples(&["1", "2024-05-01"])),
+        LabelDataKind::Text
+    );
+    assert_eq!(suggest_kind(&[]), LabelDataKind::Text);
+}
+
+#[test]
+fn importing_a_schema_keeps_existing_data() {
+    let mut db = ReplayDB::new();
+    db.seed_demo_data(2);
+    let rows = db.to_file().rows;
+    let [name, split, ..] = db.labels.clone().try_into().unwrap();
+
+    // Columns only in the database are left alone
+    let mut columns = db.column_files();
+    columns[0].config.required = false;
+    columns.remove(1);
+    columns.push(ColumnFile {
+        name: "Time".to_string(),
+        kind: LabelDataKind::Decimal,
+        config: LabelConfig::default(),
+    });
+    assert_eq!(
+        db.import_schema(columns),
+        Ok(vec![
+            "updated column `Name`".to_string(),
+            "added column `Time`".to_string()
+        ])
+    );
+    assert!(!db.is_required(&name));
+    assert!(db.labels.contains(&split));
+    assert_eq!(db.labels.last().unwrap().name, "Time");
+    assert_eq!(db.to_file().rows, rows);
+    assert_eq!(db.import_schema(db.column_files()), Ok(Vec::new()));
+
+    // A column can't change kind, and nothing else is applied when one would
+    let labels = db.labels.clone();
+    let mut columns = db.column_files();
+    columns[0].config.required = true;
+    columns[1].kind = LabelDataKind::Text;
+    assert_eq!(
+        db.import_schema(columns),
+        Err("column `800 Split` is Number but Text in the schema".to_string())
+    );
+    assert_eq!(db.labels, labels);
+    assert!(!db.is_required(&name));
+}
+
+#[test]
+fn exported_rows_import_into_another_db() {
+    let mut db = ReplayDB::new();
+    let [name, split, pb, _] = db.labels.clone().try_into().unwrap();
+    let entity = db.spawn();
+    db.insert_value(&name, entity, Value::Text("Any%".to_string()));
+    db.insert_value(&split, entity, Value::Number(42));
+    db.notes
+        .insert((entity, name.clone()), "first try".to_string());
+    let path = std::env::temp_dir().join(format!("ddreplayer-row-{}.ron", std::process::id()));
+    db.export_row(entity, &path).unwrap();
+
+    let mut other = ReplayDB::new();
+    let imported = other.import_row(&path).unwrap();
+    assert_eq!(other.ordered_entities(), [imported]);
+    assert_eq!(
+        other.get_value(&name, imported),
+        Some(Value::Text("Any%".to_string()))
+    );
+    assert_eq!(other.get_value(&split, imported), Some(Value::Number(42)));
+    assert_eq!(other.get_value(&pb, imported), None);
+    assert_eq!(
+        other
+            .notes
+            .get(&(imported, name.clone()))
+            .map(String::as_str),
+        Some("first try")
+    );
+
+    // Labels have to exist in the importing schema
+    other.rename_label(&split, "Split").unwrap();
+    assert_eq!(
+        other.import_row(&path),
+        Err("unknown column `800 Split`".to_string())
+    );
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(other.ordered_entities().len(), 1);
+}
+
+#[test]
+fn duplicate_row_copies_values() {
+    let mut db = ReplayDB::new();
+    let [name, split, _, _] = db.labels.clone().try_into().unwrap();
+    let first = db.spawn();
+    db.insert_value(&name, first, Value::Text("original".to_string()));
+    db.insert_value(&split, first, Value::Number(7));
+    let last = db.spawn();
+
+    let copy = db.duplicate_row(first);
+    assert_eq!(db.ordered_entities(), [first, copy, last]);
+    assert_eq!(db.row_file(copy).values, db.row_file(first).values);
+
+    // The copy owns its own `String` so dropping the original leaves it intact
+    db.despawn(first);
+    assert_eq!(
+        db.get_value(&name, copy),
+        Some(Value::Text("original".to_string()))
+    );
+}
+
+#[test]
+fn problems_list_every_broken_rule() {
+    let mut db = ReplayDB::new();
+    let [name, split, _, _] = db.labels.clone().try_into().unwrap();
+    let [first, second] = [db.spawn(), db.spawn()];
+    for entity in [first, second] {
+        db.insert_value(&name, entity, Value::Text("Any%".to_string()));
+    }
+    db.insert_value(&split, first, Value::Number(500));
+    assert!(db.problems().is_empty());
+
+    // Rules added after the values were written
+    db.column_mut(&name).config.unique = true;
+    db.column_mut(&split).config.max = Some(100.0);
+    db.column_mut(&split).config.required = true;
+    let used = "is already used by another row".to_string();
+    assert_eq!(
+        db.problems(),
+        [
+            (first, name.clone(), used.clone()),
+            (first, split.clone(), "must be at most 100".to_string()),
+            (second, name.clone(), used),
+            (second, split.clone(), "can't be left blank".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn histograms_bucket_values_that_are_there() {
+    let mut db = ReplayDB::new();
+    let [name, split, _, _] = db.labels.clone().try_into().unwrap();
+    assert!(db.histogram(&split).is_empty());
+
+    for split_time in [3, 4, 4, 6] {
+        let entity = db.spawn();
+        db.insert_value(&split, entity, Value::Number(split_time));
+    }
+    // Rows without a split aren't counted
+    db.spawn();
+    let buckets = db.histogram(&split);
+    assert_eq!(
+        buckets,
+        ["3", "4", "5", "6"]
+            .map(|start| start.to_string())
+            .into_iter()
+            .zip([1, 2, 0, 1])
+            .collect::<Vec<_>>()
+    );
+    assert!(db.histogram(&name).is_empty());
+}
+
+#[test]
+fn check_schema_rejects_mismatched_columns() {
+    let file = ron::from_str(
+        r#"(
+            columns: [
+                (name: "Category", kind: Enum(variants: ["Any%", "100%"])),
+                (name: "Split", kind: Number),
+            ],
+            rows: [(values: {"Category": Enum("100%"), "Split": Number(30)})],
+        )"#,
+    )
+    .unwrap();
+    let mut db = ReplayDB::from_file(file).unwrap();
+    assert_eq!(db.check_schema(), Ok(()));
+    let entity = db.ordered_entities()[0];
+
+    // An index past the last variant would panic when read back
+    db.columns[0].table.insert(&mut db.world, entity, 7_u16);
+    assert_eq!(
+        db.check_schema(),
+        Err(format!(
+            "entity {} has variant 7 in column `Category`, which only has 2",
+            entity_id(entity)
+        ))
+    );
+    db.columns[0].table.insert(&mut db.world, entity, 1_u16);
+
+    db.columns[1].table = TypedTable::new(&mut db.world, alloc::Layout::new::<i64>()).unwrap();
+    assert_eq!(
+        db.check_schema(),
+        Err(format!(
+            "column `Split` is stored as {:?} but Number values need {:?}",
+            alloc::Layout::new::<i64>(),
+            alloc::Layout::new::<i16>()
+        ))
+    );
+}
+
+#[test]
+fn load_rejects_bad_enum_columns() {
+    for kind in ["Enum(variants: [])", r#"Enum(variants: ["PC", "PC"])"#] {
+        let text = format!("(columns: [(name: \"Platform\", kind: {kind})], rows: [])");
+        assert!(ReplayDB::from_file(ron::from_str(&text).unwrap()).is_err());
+    }
+}
+
+#[test]
+fn set_values_only_writes_changes() {
+    let mut db = ReplayDB::new();
+    let [name, split, pb, _] = db.labels.clone().try_into().unwrap();
+    let entity = db.spawn();
+    let values = [
+        (
+            name.clone(),
+            Some(Value::Text("Tower of Trials".to_string())),
+        ),
+        (split.clone(), Some(Value::Number(42))),
+    ];
+    assert_eq!(db.set_values(entity, &values), Ok(2));
+
+    // Saving with no edits doesn't touch any column
+    assert_eq!(db.set_values(entity, &values), Ok(0));
+
+    let values = [(split.clone(), None), (pb.clone(), Some(Value::Unit))];
+    assert_eq!(db.set_values(entity, &values), Ok(2));
+    assert_eq!(db.get_value(&split, entity), None);
+    assert_eq!(db.get_value(&pb, entity), Some(Value::Unit));
+    // Columns that aren't given are left alone
+    assert_eq!(
+        db.get_value(&name, entity),
+        Some(Value::Text("Tower of Trials".to_string()))
+    );
+
+    // One bad value and nothing is written, even to the columns before it
+    let values = [
+        (name.clone(), Some(Value::Text("Forest".to_string()))),
+        (split.clone(), Some(Value::Text("42".to_string()))),
+    ];
+    assert_eq!(
+        db.set_values(entity, &values),
+        Err("800 Split can't hold Text(\"42\")".to_string())
+    );
+    assert_eq!(
+        db.get_value(&name, entity),
+        Some(Value::Text("Tower of Trials".to_string()))
+    );
+    assert_eq!(db.get_value(&pb, entity), Some(Value::Unit));
+}
@@ -1,155 +1,293 @@
-use core::alloc;
 use std::{
-    collections::HashMap,
-    hash::Hash,
-    mem::{ManuallyDrop, MaybeUninit},
-    ptr::slice_from_raw_parts,
+    collections::{HashMap, HashSet, VecDeque},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::{Duration, Instant},
 };
 
-use crossterm::event::{self, Event, KeyCode};
-use decentralecs::{ColumnsApi, Entity, WithEntities, World};
-use decentralecs_dynamic::DynamicTable;
-use rand::Rng;
+use crossterm::event::{
+    self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ddreplayer::{
+    CsvImport, Label, LabelDataKind, NEW_LABEL_KINDS, ReplayDB, RowPlacement, SortDirection,
+    UNIT_SET, Value, apply_mask, entity_id, format_timestamp, is_mask_separator,
+    replay_format::{FORMAT_VERSION, ReplayDBFile},
+};
+use decentralecs::Entity;
+use history::History;
+use keys::{EditorAction, KeyBindings, ViewerAction};
 use ratatui::{
     DefaultTerminal, Frame,
-    layout::{self, Constraint, Layout},
-    style::{Color, Modifier, Style, Stylize, palette::tailwind},
-    text::{Line, Text},
-    widgets::{self, HighlightSpacing, Row, ScrollbarState, Table, TableState},
+    layout::{self, Constraint, Flex, Layout, Position, Rect},
+    style::{Modifier, Style, Stylize, palette::tailwind},
+    text::{Line, Span, Text},
+    widgets::{
+        self, Bar, BarChart, BarGroup, Block, Clear, HighlightSpacing, Paragraph, Row, Scrollbar,
+        ScrollbarOrientation, ScrollbarState, Table, TableState,
+    },
 };
+use regex::Regex;
+use text_field::{TextArea, TextField};
+use theme::Theme;
 use tui_input::{Input, backend::crossterm::EventHandler};
+use unicode_width::UnicodeWidthStr;
+use view::{MissingFilter, ReplayDBView, ViewOptions};
+use view_state::{Recents, ViewState};
 
-mod replay_format;
+mod cli;
+mod history;
+mod keys;
+mod snapshot;
+mod text_field;
+mod theme;
+mod view;
+mod view_state;
 
 // TODO:
-// * Support more complex datatypes than i16/String/bool (ADTs defined on disk as a config)
-// * Validate the data written by the user
-// * Persist data to disk and load it on startup
+// * Enum variants that carry data of their own (full ADTs defined on disk as a config)
+// * Optional (always ask / never ask) confirmation before a paste overwrites non-empty data.
+//   Pastes only insert at the focused field's cursor so nothing can be overwritten yet, this
+//   needs a paste that replaces a whole cell, e.g. straight into the selected viewer cell
+// * `_:__.___` masked input for Duration formatted columns storing total milliseconds
+//   (needs a Duration display format first)
+// * Stream CSV exports row by row through a `BufWriter`, cleaning up partial files on error
+//   (needs CSV export first)
+// * `ExportOptions { columns: Option<Vec<Label>> }` to export a reordered subset of columns
+//   (needs CSV/JSON export first)
 
-/// SAFETY: `T` must not contain `UnsafeCell` without going through indirection
-unsafe fn uninit_slice_from_borrow<T: ?Sized>(data: &T) -> &[MaybeUninit<u8>] {
-    let size = size_of_val(data);
-    let ptr = slice_from_raw_parts(data as *const T as *const MaybeUninit<u8>, size);
-    unsafe { &*ptr }
+/// Whether `text` is a Number, or a Decimal if `decimal`, that's been partly typed: an optional
+/// leading minus sign then digits, with at most one decimal point for Decimals
+fn could_be_number(text: &str, decimal: bool) -> bool {
+    let unsigned = text.strip_prefix('-').unwrap_or(text);
+    let parts = match unsigned.split_once('.') {
+        Some((whole, fraction)) if decimal => [whole, fraction],
+        _ => [unsigned, ""],
+    };
+    parts
+        .iter()
+        .all(|part| part.chars().all(|c| c.is_ascii_digit()))
 }
 
-struct ReplayDB {
-    world: World<'static>,
-    labels: Vec<Label>,
-    columns: HashMap<Label, DynamicTable>,
+struct App {
+    replay_db: ReplayDB,
+    state: AppState,
+    view: ViewOptions,
+    /// Feedback from the last viewer action, cleared on the next key press
+    status: Option<String>,
+    /// Draw the help/about overlay over the current view, dismissed by any key
+    show_help: bool,
+    /// Yes/no prompt drawn over the current view, which is left untouched until it's answered
+    confirm: Option<Confirm>,
+    /// Row edits that can be undone with `u` and redone with Ctrl+R
+    history: History,
+    /// Where clickable things were last drawn
+    click_areas: ClickAreas,
+    keys: KeyBindings,
+    theme: Theme,
+    /// What `T` cycles through, a custom [`theme::THEME_FILE`] first if there is one
+    themes: Vec<Theme>,
+    /// Program FilePath cells are opened with, `None` uses the platform default
+    open_with: Option<String>,
+    /// Where the database is autosaved to
+    db_path: PathBuf,
+    /// When the database was last autosaved, or the app started
+    last_save: Instant,
+    /// The `--import` CSV file, while it still has records left to spawn
+    import: Option<CsvImport>,
+    /// Messages about saves, exports and imports drawn in the top right corner over every mode,
+    /// oldest first
+    toasts: VecDeque<Toast>,
+    /// The viewer as it was when the editor was opened, to go back to once it's closed
+    suspended_viewer: Option<AppState>,
+    /// Refuse every key that would change the database, toggled with `W`
+    read_only: bool,
+    /// The view settings as they were last saved to [`view_state::VIEW_STATE_FILE`]
+    saved_view: ViewState,
+    /// Quitting with "discard", changes since the last autosave are thrown away
+    discard_on_exit: bool,
+    /// The command line the app was launched with, databases switched to with Ctrl+Tab are
+    /// opened the same way
+    args: cli::Args,
+    /// What Ctrl+Tab switches between, kept in [`view_state::RECENTS_FILE`] next to the
+    /// database the app was launched with
+    recents: Recents,
 }
 
-impl ReplayDB {
-    fn new() -> Self {
-        let labels = [
-            Label {
-                name: "Name".to_string(),
-                data: LabelDataKind::Text,
-            },
-            Label {
-                name: "800 Split".to_string(),
-                data: LabelDataKind::Number,
-            },
-            Label {
-                name: "PB".to_string(),
-                data: LabelDataKind::Unit,
-            },
-        ];
-
-        let mut world = World::new();
-
-        let mut columns = HashMap::from([
-            (
-                labels[0].clone(),
-                DynamicTable::new(&mut world, alloc::Layout::new::<String>()),
-            ),
-            (
-                labels[1].clone(),
-                DynamicTable::new(&mut world, alloc::Layout::new::<i16>()),
-            ),
-            (
-                labels[2].clone(),
-                DynamicTable::new(&mut world, alloc::Layout::new::<()>()),
-            ),
-        ]);
-
-        let mut rng = rand::rng();
-        for _ in 0..10 {
-            let name: ManuallyDrop<String> = ManuallyDrop::new(
-                (0..(rng.random_range(1..8)))
-                    .map(|_| 'a')
-                    .collect::<String>(),
-            );
-            let split: &i16 = &rng.random_range(-100..=182);
-            let pb = rng.random();
-
-            let mut builder = world.spawn();
-            // FIXME: `insert` should probably not be a reference for `DynamicTable`. It doesn't imply
-            // ownership semantics.
-            builder
-                .insert(columns.get_mut(&labels[0].clone()).unwrap(), unsafe {
-                    uninit_slice_from_borrow::<ManuallyDrop<String>>(&name)
-                })
-                .insert(columns.get_mut(&labels[1].clone()).unwrap(), unsafe {
-                    uninit_slice_from_borrow::<i16>(split)
-                });
-
-            if pb {
-                builder.insert(columns.get_mut(&labels[2].clone()).unwrap(), unsafe {
-                    uninit_slice_from_borrow(&())
-                });
-            }
-        }
+/// A message shown for [`TOAST_DURATION`], see [`App::toasts`]
+struct Toast {
+    message: String,
+    shown_at: Instant,
+}
 
+impl Toast {
+    fn new(message: impl Into<String>) -> Self {
         Self {
-            world,
-            labels: labels.into(),
-            columns,
+            message: message.into(),
+            shown_at: Instant::now(),
         }
     }
 }
 
-#[derive(Eq, PartialEq, Hash, Debug, Clone)]
-struct Label {
-    name: String,
-    data: LabelDataKind,
-}
-
-#[derive(Eq, PartialEq, Hash, Debug, Clone)]
-enum LabelDataKind {
-    Number,
-    Text,
-    Unit,
-}
-
-struct App {
-    replay_db: ReplayDB,
-    state: AppState,
-}
-
 enum AppState {
     ReplayDBViewer {
         table_state: TableState,
         scroll_state: ScrollbarState,
+        note_editor: Option<NoteEditor>,
+        cell_editor: Option<CellEditor>,
+        replace_editor: Option<ReplaceEditor>,
+        schema_editor: Option<SchemaEditor>,
+        /// The search box, open while typing a query for [`ViewOptions::search`]
+        search_input: Option<Input>,
+        /// The `:` prompt, open while typing the number of a row to jump to
+        goto_input: Option<Input>,
+        /// The value being set on every marked row, see [`App::set_cells`]
+        batch_editor: Option<LabelInput>,
+        /// The Number or Decimal column whose [`ReplayDB::histogram`] is open
+        histogram: Option<Label>,
+        /// What `V` found breaking column rules
+        problems: Option<ProblemList>,
+        /// Rows marked with `v` for batch deletion and editing
+        marked: HashSet<Entity>,
     },
     ReplayInfoEditor(ReplayInfoEditor),
 }
 
+/// Screen areas recorded while drawing so mouse clicks can be mapped back to what's under them
+#[derive(Default)]
+struct ClickAreas {
+    /// The viewer's rows, not including the header
+    table_rows: Rect,
+    /// Each editor field and button
+    editor: Vec<(Rect, ReplayInfoEditorFocus)>,
+}
+
+/// A destructive action waiting on the user to answer "Are you sure?"
+struct Confirm {
+    message: String,
+    action: PendingAction,
+}
+
+/// Actions that need confirming, applied to [`App::state`] on `y`
+#[derive(Debug)]
+enum PendingAction {
+    /// Remove `labels[n]` from the entity being edited
+    RemoveLabel(usize),
+    /// Save an entity that has no labels left, despawning it
+    DespawnEntity,
+    /// Save the editor's fields to a new entity since the one being edited was despawned,
+    /// answering no throws them away instead
+    RespawnEntity,
+    /// Go back to the viewer, throwing away every edit
+    DiscardChanges,
+    /// Despawn every row marked in the viewer
+    DespawnMarked,
+    /// Despawn the rows found by [`ReplayDB::duplicates`]
+    DespawnDuplicates(Vec<Entity>),
+    /// Leave the viewer before the last changes have been autosaved. Answered with save,
+    /// discard or cancel rather than yes or no, see [`App::answer_confirm`].
+    Quit,
+}
+
+/// Popup over the viewer for editing the note attached to a single cell
+struct NoteEditor {
+    entity: Entity,
+    label: Label,
+    input: Input,
+}
+
+/// Input drawn over the selected viewer cell so its value can be changed without opening the
+/// editor
+struct CellEditor {
+    entity: Entity,
+    input: LabelInput,
+}
+
+/// Popup over the viewer listing [`ReplayDB::problems`], Enter jumps to the selected one's cell
+struct ProblemList {
+    problems: Vec<(Entity, Label, String)>,
+    selected: usize,
+}
+
+/// Popup over the viewer listing every column so they can be deleted
+struct SchemaEditor {
+    /// Index into `ReplayDB::labels`
+    selected: usize,
+    /// Waiting for the user to confirm deleting the selected column
+    confirm_delete: bool,
+    /// The new name being typed for the selected column
+    rename: Option<TextField>,
+}
+
+/// Popup over the viewer for find/replace across every value in a Text column
+struct ReplaceEditor {
+    label: Label,
+    find: TextField,
+    replace: TextField,
+    /// Whether typing goes to `replace` rather than `find`
+    replace_focused: bool,
+    /// Only replace values equal to `find` instead of every occurrence within them
+    whole_value: bool,
+}
+
 struct ReplayInfoEditor {
     entity: Entity,
     focus: ReplayInfoEditorFocus,
     labels: Vec<LabelInput>,
+    /// Schema labels that can still be added, in schema order. Kept in sync with `labels`
+    /// by `App::add_label`/`App::remove_label` rather than recomputed on every key press.
+    addable: Vec<Label>,
+    /// Narrows the addable list down to labels whose names fuzzy match it
+    addable_filter: TextField,
+    suggestions: Suggestions,
+    /// Fields that failed [`ReplayInfoEditor::validate`] on the last save, by `labels` index
+    errors: Vec<(usize, String)>,
+    new_label: NewLabel,
+    /// The field being edited while the focus is [`ReplayInfoEditorFocus::TextArea`]
+    text_area: TextArea,
+    /// The labels `labels` started with, any missing on save were removed by the user
+    opened_with: Vec<Label>,
+}
+
+/// Selection state for the dropdown of values other entities already use for the focused
+/// Text field
+#[derive(Default)]
+struct Suggestions {
+    /// The `LabelData` index `selected`/`dismissed` apply to
+    field: usize,
+    selected: usize,
+    dismissed: bool,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 enum ReplayInfoEditorFocus {
     LabelData(usize),
     LabelRemove(usize),
     LabelAdd,
+    /// One past the last addable label is the "New Label" entry
     AddableLabel(usize),
+    /// Naming a brand new label in the [`NewLabel`] popup
+    NewLabel,
+    /// Editing a multi-line field in the [`TextArea`] popup
+    TextArea(usize),
     SaveChanges,
 }
 
+/// State of the popup for defining a new label, nothing is registered until it's confirmed
+#[derive(Default)]
+struct NewLabel {
+    name: TextField,
+    /// Index into [`NEW_LABEL_KINDS`]
+    kind: usize,
+    /// Why the last attempt to create the label was rejected
+    error: Option<String>,
+    /// Existing names starting with what was typed when Tab was first pressed, repeated Tabs
+    /// cycle through them until the name is edited again
+    completions: Vec<String>,
+    /// Index into `completions` of the name that was filled in
+    completion: usize,
+}
+
 impl ReplayInfoEditorFocus {
     #[must_use]
     fn next_focus(self, max_labels: usize, max_addable_labels: usize, for_deletion: bool) -> Self {
@@ -174,7 +312,15 @@ impl ReplayInfoEditorFocus {
                 ReplayInfoEditorFocus::AddableLabel(new_idx)
             }
             ReplayInfoEditorFocus::LabelAdd => ReplayInfoEditorFocus::SaveChanges,
-            ReplayInfoEditorFocus::SaveChanges => ReplayInfoEditorFocus::SaveChanges,
+            ReplayInfoEditorFocus::NewLabel => ReplayInfoEditorFocus::NewLabel,
+            ReplayInfoEditorFocus::TextArea(n) => ReplayInfoEditorFocus::TextArea(n),
+            ReplayInfoEditorFocus::SaveChanges => {
+                if max_labels >= 1 {
+                    ReplayInfoEditorFocus::LabelData(0)
+                } else {
+                    ReplayInfoEditorFocus::LabelAdd
+                }
+            }
         }
     }
 
@@ -184,7 +330,7 @@ impl ReplayInfoEditorFocus {
             ReplayInfoEditorFocus::LabelRemove(n) => ReplayInfoEditorFocus::LabelData(n),
             ReplayInfoEditorFocus::LabelData(n) => {
                 if n == 0 {
-                    ReplayInfoEditorFocus::LabelData(n)
+                    ReplayInfoEditorFocus::SaveChanges
                 } else {
                     ReplayInfoEditorFocus::LabelRemove(n - 1)
                 }
@@ -202,539 +348,6178 @@ impl ReplayInfoEditorFocus {
                 if max_labels >= 1 {
                     ReplayInfoEditorFocus::LabelRemove(max_labels - 1)
                 } else {
-                    ReplayInfoEditorFocus::LabelAdd
+                    ReplayInfoEditorFocus::SaveChanges
                 }
             }
+            ReplayInfoEditorFocus::NewLabel => ReplayInfoEditorFocus::NewLabel,
+            ReplayInfoEditorFocus::TextArea(n) => ReplayInfoEditorFocus::TextArea(n),
             ReplayInfoEditorFocus::SaveChanges => ReplayInfoEditorFocus::LabelAdd,
         }
     }
+
+    /// [`Self::next_focus`] or [`Self::prev_focus`], passing over the remove buttons of required
+    /// labels since there's nothing to press
+    #[must_use]
+    fn step(
+        self,
+        db: &ReplayDB,
+        labels: &[LabelInput],
+        max_addable_labels: usize,
+        forward: bool,
+    ) -> Self {
+        let mut focus = self;
+        loop {
+            focus = if forward {
+                focus.next_focus(labels.len(), max_addable_labels, false)
+            } else {
+                focus.prev_focus(labels.len(), max_addable_labels)
+            };
+            match focus {
+                ReplayInfoEditorFocus::LabelRemove(n) if db.is_required(&labels[n].label) => (),
+                _ => return focus,
+            }
+        }
+    }
 }
 
 struct LabelInput {
     label: Label,
-    data: Input,
+    data: TextField,
 }
 
 impl ReplayInfoEditor {
+    /// Checks every field can be stored in its column and follows the column's rules when saved
+    /// to `entity`, returning the `labels` index and problem of each one that doesn't
+    fn validate(
+        db: &ReplayDB,
+        entity: Entity,
+        labels: &[LabelInput],
+    ) -> Result<(), Vec<(usize, String)>> {
+        let errors = labels
+            .iter()
+            .enumerate()
+            .filter_map(|(n, input)| {
+                if Self::clears_value(input) {
+                    let required = db.is_required(&input.label);
+                    return required.then(|| (n, "can't be left blank".to_string()));
+                }
+                let checked = Value::parse(&input.label.data, input.data.value())
+                    .and_then(|value| db.check_value(&input.label, Some(entity), &value));
+                checked.err().map(|err| (n, err))
+            })
+            .collect::<Vec<_>>();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Whether saving `input` removes its column's component rather than storing a value, which
+    /// is how unticked Unit fields and blank Number and Decimal fields are told apart from zero
+    fn clears_value(input: &LabelInput) -> bool {
+        let text = input.data.value();
+        match input.label.data {
+            LabelDataKind::Unit => text.is_empty(),
+            LabelDataKind::Number | LabelDataKind::Decimal => text.trim().is_empty(),
+            _ => false,
+        }
+    }
+
+    /// What saving writes to each column, `None` removing its value. Fields are stored (or
+    /// cleared, see [`Self::clears_value`]), labels removed since the editor opened are
+    /// deleted, and columns the editor never had a field for are left out so they're untouched.
+    ///
+    /// Panics if a field hasn't been validated.
+    fn staged_values(labels: &[LabelInput], opened_with: &[Label]) -> Vec<(Label, Option<Value>)> {
+        let removed = opened_with
+            .iter()
+            .filter(|label| !labels.iter().any(|input| input.label == **label))
+            .map(|label| (label.clone(), None));
+        labels
+            .iter()
+            .map(|input| {
+                let value = (!Self::clears_value(input))
+                    .then(|| Value::parse(&input.label.data, input.data.value()).unwrap());
+                (input.label.clone(), value)
+            })
+            .chain(removed)
+            .collect()
+    }
+
+    /// Rechecks field `n` after it's been edited so its error goes away as soon as it's valid.
+    /// Fields without an error aren't checked until the next save.
+    fn revalidate(
+        db: &ReplayDB,
+        entity: Entity,
+        labels: &[LabelInput],
+        errors: &mut Vec<(usize, String)>,
+        n: usize,
+    ) {
+        let Some(idx) = errors.iter().position(|(field, _)| *field == n) else {
+            return;
+        };
+        let error = Self::validate(db, entity, labels)
+            .err()
+            .and_then(|invalid| invalid.into_iter().find(|(field, _)| *field == n));
+        match error {
+            Some(error) => errors[idx] = error,
+            None => {
+                errors.remove(idx);
+            }
+        }
+    }
+
+    /// Whether `labels` differ from what's currently stored for `entity`
+    fn has_changes(db: &ReplayDB, entity: Entity, labels: &[LabelInput]) -> bool {
+        let stored = Self::new(db, entity).labels;
+        stored.len() != labels.len()
+            || stored
+                .iter()
+                .zip(labels)
+                .any(|(a, b)| a.label != b.label || a.data.value() != b.data.value())
+    }
+
     fn new(db: &ReplayDB, entity: Entity) -> Self {
         let labels = db
-            .labels
+            .labels()
             .iter()
             .flat_map(|label| {
-                let data = db.columns[label].get_component(&db.world, entity)?;
-
-                let existing_input = match label.data {
-                    LabelDataKind::Number => {
-                        let typed_data =
-                            unsafe { *(data as *const [MaybeUninit<u8>] as *const i16) };
-                        format!("{typed_data}")
+                // Columns with a default start with it, Unit fields are always shown so they can
+                // be ticked without adding them first, and required fields so they can't be left
+                // off
+                let default = db.config(label).default.as_deref();
+                let existing_input = match (db.get_value(label, entity), default) {
+                    (Some(value), _) => value.input_text(),
+                    // Checked when the database is loaded
+                    (None, Some(default)) => {
+                        Value::parse(&label.data, default).map_or(String::new(), Value::input_text)
                     }
-                    LabelDataKind::Text => {
-                        let typed_data =
-                            unsafe { &*(data as *const [MaybeUninit<u8>] as *const String) };
-                        typed_data.clone()
+                    (None, None) if label.data == LabelDataKind::Unit || db.is_required(label) => {
+                        "".to_string()
                     }
-                    LabelDataKind::Unit => "".to_string(),
+                    (None, None) => return None,
                 };
 
                 Some(LabelInput {
                     label: label.clone(),
-                    data: Input::new(existing_input),
+                    data: TextField::new(existing_input),
                 })
             })
             .collect::<Vec<_>>();
 
         Self {
             entity,
+            addable: App::addable_labels(db, &labels).collect(),
             focus: if labels.len() > 0 {
                 ReplayInfoEditorFocus::LabelData(0)
             } else {
                 ReplayInfoEditorFocus::LabelAdd
             },
+            opened_with: labels.iter().map(|input| input.label.clone()).collect(),
             labels,
+            addable_filter: TextField::default(),
+            suggestions: Suggestions::default(),
+            errors: vec![],
+            new_label: NewLabel::default(),
+            text_area: TextArea::default(),
+        }
+    }
+}
+
+/// Keybindings listed in the help overlay, keep these in sync with `App::run`. Some of these
+/// are only the defaults and can be changed in [`keys::KEYS_FILE`].
+const VIEWER_KEYS: &[(&str, &str)] = &[
+    ("↑/↓, j/k", "Select row"),
+    ("Home/g, End/G", "Select the first/last row"),
+    ("PgUp/PgDn", "Move the selection a page up/down"),
+    ("Click/Wheel", "Select row with the mouse"),
+    ("←/→, h/l", "Select column"),
+    ("Ctrl+←/→", "Move the selected column left/right"),
+    ("e", "Edit the selected replay"),
+    ("n", "Add a new replay"),
+    ("o/O", "Add a new replay below/above the selected one"),
+    ("a", "Annotate the selected cell"),
+    ("Space", "Toggle the selected Unit cell"),
+    (
+        "Enter",
+        "Edit the selected cell in place, Enter again saves it",
+    ),
+    ("p", "Open the selected replay file"),
+    ("y", "Copy the selected cell"),
+    ("Y", "Copy the selected row as tab separated values"),
+    ("v", "Mark/unmark the selected row"),
+    ("Delete", "Delete every marked row"),
+    ("E", "Set the selected column on every marked row"),
+    ("H", "Chart the selected Number/Decimal column's values"),
+    ("V", "List values breaking their column's rules"),
+    ("d", "Duplicate the selected row"),
+    ("D", "Duplicate the selected column"),
+    ("K", "Delete rows repeating the selected column's value"),
+    ("S", "List, rename and delete columns"),
+    ("R", "Find/replace in the selected column"),
+    ("L", "Lock/unlock the selected column"),
+    ("c", "Toggle compact rows"),
+    ("t", "Toggle created/modified timestamps"),
+    ("i", "Toggle the entity id column"),
+    ("T", "Switch to the next colour theme"),
+    ("W", "Toggle read-only mode, refusing every change"),
+    ("s", "Sort by the selected column, again to reverse/clear"),
+    (
+        "Ctrl+S",
+        "Also sort by the selected column, again to reverse/remove it",
+    ),
+    ("/", "Search, Enter keeps the filter and Esc clears it"),
+    (":", "Jump to a row by its number"),
+    ("Tab", "While searching, toggle regex mode"),
+    ("m", "Only show rows missing a value"),
+    ("M", "Only show rows missing the selected column"),
+    ("Ctrl+B", "Toggle hexadecimal numbers"),
+    ("u/Ctrl+R", "Undo/redo the last row edit"),
+    ("x", "Export a snapshot of the table"),
+    ("X/I", "Export the selected row/import a row"),
+    (
+        "Ctrl+E",
+        "Edit the database file in $EDITOR, reloading it after",
+    ),
+    ("Ctrl+Tab", "Switch to the next recently opened database"),
+    ("?/F1", "Show this help"),
+    ("Esc", "Clear marked rows, otherwise quit"),
+];
+const EDITOR_KEYS: &[(&str, &str)] = &[
+    ("↑/↓", "Move focus"),
+    ("Tab/S-Tab", "Move focus, wrapping around"),
+    ("Enter", "Next field or press the focused button"),
+    ("Click", "Focus a field or press a button"),
+    ("Tab", "Accept the highlighted suggestion"),
+    ("S-←/→/Ctrl+A", "Select text, typing replaces it"),
+    ("Ctrl+←/→", "Move by a word, Ctrl+W deletes the one before"),
+    (
+        "Ctrl+U",
+        "Clear the focused field, saving it empty removes the value",
+    ),
+    ("Space", "Toggle the focused Unit or Bool field"),
+    ("Ctrl+V", "Paste into the focused field as a single line"),
+    (
+        "Enter",
+        "Open a multi-line field's popup, Esc closes it keeping the text",
+    ),
+    ("F1", "Show this help"),
+    (
+        "Esc",
+        "Back to the viewer without saving, asks first if anything changed",
+    ),
+];
+
+/// Most problems listed at once by `V` before the list scrolls, see [`ProblemList`]
+const PROBLEM_LIST_HEIGHT: usize = 15;
+/// How often the UI redraws without any input, long enough that idling costs next to nothing
+const TICK_RATE: Duration = Duration::from_millis(250);
+/// How often unsaved changes are written to disk while the app is open
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(5);
+/// How long a [`Toast`] stays up, it's drawn dim for the last [`TOAST_FADE`] of that
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+const TOAST_FADE: Duration = Duration::from_secs(1);
+/// Older toasts are dropped early when there are more than this many
+const MAX_TOASTS: usize = 3;
+/// How many CSV records are imported between redraws
+const IMPORT_BATCH: usize = 200;
+/// The last entry in the editor's addable list, opens the [`NewLabel`] popup
+const NEW_LABEL_ITEM: &str = "New Label…";
+/// Lines in the viewer's footer, see [`ReplayDBView::footer_cells`]
+const FOOTER_HEIGHT: u16 = 2;
+
+/// Widest a viewer column grows to fit its content, so one long value can't squeeze the rest
+const MAX_COLUMN_WIDTH: usize = 40;
+
+/// How many previously used values to offer when editing a Text field
+const MAX_SUGGESTIONS: usize = 5;
+/// How many affected rows the find/replace popup lists before applying
+const MAX_REPLACE_PREVIEW: usize = 8;
+
+/// Where `x` in the viewer writes a plain text copy of the table
+const SNAPSHOT_PATH: &str = "ddreplayer-snapshot.txt";
+/// Where `X` in the viewer exports the selected row to and `I` imports a row from
+const ROW_EXPORT_PATH: &str = "ddreplayer-row.ron";
+
+/// Header of the viewer's read-only column of entity ids, toggled with `i`
+const ID_HEADER: &str = "ID";
+/// Drawn in the corner of viewer cells that have a note attached
+const NOTE_MARKER: &str = "•";
+
+/// Launches `path` with `open_with`, or the platform's default handler for the file
+fn open_path(path: &str, open_with: Option<&str>) -> std::io::Result<()> {
+    let mut command = match open_with {
+        Some(program) => Command::new(program),
+        None if cfg!(target_os = "macos") => Command::new("open"),
+        None if cfg!(windows) => {
+            let mut command = Command::new("cmd");
+            command.args(["/C", "start", ""]);
+            command
+        }
+        None => Command::new("xdg-open"),
+    };
+
+    // Anything the launched program prints would draw over the TUI
+    command
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(drop)
+}
+
+/// Puts `text` on the system clipboard by asking the terminal to, which also works over ssh
+fn copy_to_clipboard(text: &str) -> std::io::Result<()> {
+    crossterm::execute!(
+        std::io::stdout(),
+        crossterm::clipboard::CopyToClipboard::to_clipboard_from(text)
+    )
+}
+
+/// Word wraps `text` into at most `max_lines` lines of at most `width` columns, ending the
+/// last line with `…` if it doesn't all fit. Words wider than a whole line are split and
+/// line breaks in `text` are kept.
+fn wrap_text(text: &str, width: usize, max_lines: usize) -> String {
+    let width = width.max(1);
+    let mut lines = vec![];
+    for paragraph in text.split('\n') {
+        let start = lines.len();
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            wrap_word(&mut lines, &mut line, word, width);
+        }
+        if !line.is_empty() || lines.len() == start {
+            lines.push(line);
+        }
+    }
+
+    let max_lines = max_lines.max(1);
+    if lines.len() > max_lines {
+        lines.truncate(max_lines);
+        let last = lines.last_mut().unwrap();
+        while !last.is_empty() && last.width() + 1 > width {
+            last.pop();
+        }
+        last.push('…');
+    }
+    lines.join("\n")
+}
+
+/// Adds `word` to the end of `line`, moving full lines to `lines` as it goes
+fn wrap_word(lines: &mut Vec<String>, line: &mut String, mut word: &str, width: usize) {
+    loop {
+        let space = !line.is_empty() as usize;
+        if line.width() + space + word.width() <= width {
+            if space == 1 {
+                line.push(' ');
+            }
+            line.push_str(word);
+            break;
+        }
+        if !line.is_empty() {
+            lines.push(std::mem::take(line));
+            continue;
+        }
+
+        // The word doesn't fit on a line of its own so it goes across several, always
+        // taking at least one char so this can't loop forever
+        let mut split = word.chars().next().map_or(0, char::len_utf8);
+        for (idx, c) in word.char_indices().skip(1) {
+            if word[..idx + c.len_utf8()].width() > width {
+                break;
+            }
+            split = idx + c.len_utf8();
+        }
+        lines.push(word[..split].to_string());
+        word = &word[split..];
+        if word.is_empty() {
+            break;
+        }
+    }
+}
+
+/// `line` with every match of `query` highlighted, ignoring case like
+/// [`ViewOptions::search`]
+fn highlight_matches(line: &str, query: &str) -> Line<'static> {
+    let lowercase = |text: &str| {
+        text.chars()
+            .flat_map(char::to_lowercase)
+            .collect::<String>()
+    };
+    let query = lowercase(query);
+    if query.is_empty() {
+        return Line::raw(line.to_string());
+    }
+
+    let mut spans = vec![];
+    let (mut plain, mut start) = (0, 0);
+    while let Some(c) = line[start..].chars().next() {
+        // The shortest text starting here that lowercases to at least as long as the query
+        let end = line[start..]
+            .char_indices()
+            .map(|(idx, c)| start + idx + c.len_utf8())
+            .find(|&end| lowercase(&line[start..end]).len() >= query.len())
+            .filter(|&end| lowercase(&line[start..end]) == query);
+        match end {
+            Some(end) => {
+                spans.push(Span::raw(line[plain..start].to_string()));
+                spans.push(Span::raw(line[start..end].to_string()).bg(tailwind::AMBER.c700));
+                (plain, start) = (end, end);
+            }
+            None => start += c.len_utf8(),
+        }
+    }
+    spans.push(Span::raw(line[plain..].to_string()));
+    Line::from(spans)
+}
+
+/// `line` with every match of `regex` highlighted, like [`highlight_matches`]
+fn highlight_regex(line: &str, regex: &Regex) -> Line<'static> {
+    let mut spans = vec![];
+    let mut plain = 0;
+    for found in regex.find_iter(line).filter(|found| !found.is_empty()) {
+        spans.push(Span::raw(line[plain..found.start()].to_string()));
+        spans.push(Span::raw(found.as_str().to_string()).bg(tailwind::AMBER.c700));
+        plain = found.end();
+    }
+    spans.push(Span::raw(line[plain..].to_string()));
+    Line::from(spans)
+}
+
+/// A `width` x `height` rect in the middle of `area`, shrunk to fit if `area` is too small
+/// Whether every char of `query` appears in `text` in order, ignoring case
+fn fuzzy_matches(text: &str, query: &str) -> bool {
+    let mut text = text.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|wanted| text.any(|c| c == wanted))
+}
+
+/// Runs the whole `--import` of `path` into `db` for `--dry-run`, returning a report of how
+/// many rows it would add, which columns it would create and why any would be skipped. `db` is thrown away afterwards so
+/// nothing is saved.
+fn dry_run_import(db: &mut ReplayDB, path: &Path) -> Result<String, String> {
+    let mut import = db.start_csv_import(path)?;
+    let new_columns = import
+        .new_columns
+        .iter()
+        .map(|column| {
+            format!(
+                "\nwould create column `{}` as {:?} ({}/{} sampled values fit)",
+                column.name,
+                NEW_LABEL_KINDS[column.kind],
+                column.fitting_samples(),
+                column.samples.len()
+            )
+        })
+        .collect::<String>();
+    db.create_import_columns(&mut import)?;
+    while !db.import_csv_batch(&mut import, IMPORT_BATCH) {}
+    let mut report = format!(
+        "would import {} of {} rows from {}{new_columns}",
+        import.imported,
+        import.total,
+        path.display()
+    );
+    for err in &import.errors {
+        report.push_str(&format!("\nskipped {err}"));
+    }
+    Ok(report)
+}
+
+/// Runs `--import-schema` and then `--export-schema` on `db`, returning a report of what
+/// changed. An imported schema is saved straight away, and a database that didn't exist yet
+/// starts with just the schema's columns rather than the default ones.
+fn run_schema_args(db: &mut ReplayDB, args: &cli::Args) -> Result<String, String> {
+    let mut report = Vec::new();
+    if let Some(path) = &args.import_schema {
+        let columns = ReplayDB::read_schema(path)?;
+        let changes = if args.db_path.exists() {
+            db.import_schema(columns)?
+        } else {
+            let changes = columns
+                .iter()
+                .map(|column| format!("added column `{}`", column.name))
+                .collect();
+            *db = ReplayDB::from_file(ReplayDBFile {
+                version: FORMAT_VERSION,
+                columns,
+                rows: Vec::new(),
+            })?;
+            changes
+        };
+
+        if changes.is_empty() {
+            report.push(format!(
+                "`{}` already matches `{}`",
+                args.db_path.display(),
+                path.display()
+            ));
+        } else {
+            db.save_to_path(&args.db_path)?;
+            report.extend(changes);
         }
     }
+    if let Some(path) = &args.export_schema {
+        db.export_schema(path)?;
+        report.push(format!(
+            "wrote {} columns to `{}`",
+            db.labels().len(),
+            path.display()
+        ));
+    }
+    Ok(report.join("\n"))
+}
+
+/// Offers to load the backup [`ReplayDB::save_to_path`] keeps of `path` after `path` itself
+/// failed to load. The broken file is moved aside so the next save can't back it up over the
+/// good copy.
+fn recover_from_backup(path: &Path) -> Option<ReplayDB> {
+    let backup = ReplayDB::backup_path(path);
+    if !backup.exists() {
+        return None;
+    }
+    eprint!(
+        "Load the backup from the save before, `{}`, instead? [y/N] ",
+        backup.display()
+    );
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).ok()?;
+    if !answer.trim().eq_ignore_ascii_case("y") {
+        return None;
+    }
+
+    let replay_db = ReplayDB::load_from_path(&backup)
+        .inspect_err(|err| eprintln!("error: {err}"))
+        .ok()?;
+    let broken = path.with_extension("ron.broken");
+    if let Err(err) = std::fs::rename(path, &broken) {
+        eprintln!("error: failed to move `{}` aside: {err}", path.display());
+        return None;
+    }
+    eprintln!("The broken database was moved to `{}`", broken.display());
+    Some(replay_db)
+}
+
+fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
 }
 
 fn main() {
+    let args = match cli::Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(2);
+        }
+    };
+
+    if args.verbose {
+        let log_file = match std::fs::File::create(cli::LOG_PATH) {
+            Ok(log_file) => log_file,
+            Err(err) => {
+                eprintln!("error: failed to create `{}`: {err}", cli::LOG_PATH);
+                std::process::exit(1);
+            }
+        };
+
+        // Logs must go to a file as anything written to stdout/stderr would corrupt the TUI
+        tracing_subscriber::fmt()
+            .with_writer(std::sync::Mutex::new(log_file))
+            .with_ansi(false)
+            .with_max_level(tracing::Level::TRACE)
+            .init();
+    }
+
+    let mut replay_db = match ReplayDB::open(&args.db_path, args.sample_rows) {
+        Ok(replay_db) => replay_db,
+        Err(err) => {
+            eprintln!("error: {err}");
+            match recover_from_backup(&args.db_path) {
+                Some(replay_db) => replay_db,
+                None => std::process::exit(1),
+            }
+        }
+    };
+    if let Some(path) = args.import.as_deref().filter(|_| args.dry_run) {
+        match dry_run_import(&mut replay_db, path) {
+            Ok(report) => println!("{report}"),
+            Err(err) => {
+                eprintln!("error: {err}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if args.import_schema.is_some() || args.export_schema.is_some() {
+        match run_schema_args(&mut replay_db, &args) {
+            Ok(report) => println!("{report}"),
+            Err(err) => {
+                eprintln!("error: {err}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    let mut app = match App::with_db(&args, replay_db) {
+        Ok(app) => app,
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+    };
+    app.load_recents();
+
+    // `ratatui::init` installs a panic hook that calls `ratatui::restore` before handing off to
+    // the previous hook, so a panic anywhere below still leaves the user's terminal usable.
+    // Don't swap this for a manual setup without keeping that hook around.
     let mut terminal = ratatui::init();
-    let app = App::new();
-    app.run(&mut terminal);
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        _ = crossterm::execute!(
+            std::io::stdout(),
+            DisableMouseCapture,
+            DisableBracketedPaste
+        );
+        hook(info);
+    }));
+    if let Err(err) = crossterm::execute!(std::io::stdout(), EnableMouseCapture) {
+        tracing::warn!(%err, "failed to enable mouse capture");
+    }
+    // Pastes arrive as one `Event::Paste` rather than a key press per character
+    if let Err(err) = crossterm::execute!(std::io::stdout(), EnableBracketedPaste) {
+        tracing::warn!(%err, "failed to enable bracketed paste");
+    }
+
+    // The terminal is restored before any error is printed so it's readable, and edits made
+    // before the error are still saved
+    let result = app.run(&mut terminal);
+    _ = crossterm::execute!(
+        std::io::stdout(),
+        DisableMouseCapture,
+        DisableBracketedPaste
+    );
     ratatui::restore();
+
+    let saved = app.save_on_exit();
+    // Not worth failing over, the data is what matters
+    if let Err(err) = app.save_view().and_then(|()| app.save_recents()) {
+        eprintln!("warning: {err}");
+    }
+    if let Err(err) = &result {
+        eprintln!("error: terminal failed: {err}");
+    }
+    if let Err(err) = &saved {
+        eprintln!("error: {err}");
+    }
+    if result.is_err() || saved.is_err() {
+        std::process::exit(1);
+    }
 }
 
 impl App {
-    fn new() -> Self {
-        App {
-            replay_db: ReplayDB::new(),
+    /// Starts the app on an already opened database, e.g. one recovered from a backup
+    fn with_db(args: &cli::Args, mut replay_db: ReplayDB) -> Result<Self, String> {
+        // Only changes made from here on need saving
+        replay_db.mark_saved();
+
+        let keys_path = args.db_path.with_file_name(keys::KEYS_FILE);
+        let keys = if keys_path.exists() {
+            KeyBindings::load_from_path(&keys_path)?
+        } else {
+            KeyBindings::default()
+        };
+
+        let theme_path = args.db_path.with_file_name(theme::THEME_FILE);
+        let mut themes = Theme::built_in();
+        if theme_path.exists() {
+            themes.insert(0, Theme::load_from_path(&theme_path)?);
+        }
+
+        // Only preferences, so a bad file is better off ignored than refusing to start
+        let view_path = args.db_path.with_file_name(view_state::VIEW_STATE_FILE);
+        let saved_view = if view_path.exists() {
+            ViewState::load_from_path(&view_path).unwrap_or_else(|err| {
+                tracing::warn!(%err, "ignoring saved view settings");
+                ViewState::default()
+            })
+        } else {
+            ViewState::default()
+        };
+        let mut view = ViewOptions {
+            row_height: args.row_height,
+            cell_padding: args.cell_padding,
+            ..ViewOptions::default()
+        };
+        saved_view.apply(&replay_db, &mut view);
+
+        // Spawned a batch at a time from `run` so a large file doesn't freeze the UI
+        let import = args
+            .import
+            .as_deref()
+            .map(|path| replay_db.start_csv_import(path))
+            .transpose()?;
+
+        Ok(App {
+            replay_db,
             state: AppState::ReplayDBViewer {
                 table_state: TableState::default().with_selected(0),
                 scroll_state: ScrollbarState::new(0),
+                note_editor: None,
+                cell_editor: None,
+                replace_editor: None,
+                schema_editor: None,
+                search_input: None,
+                goto_input: None,
+                batch_editor: None,
+                histogram: None,
+                problems: None,
+                marked: HashSet::new(),
             },
-        }
+            view,
+            status: None,
+            import,
+            show_help: false,
+            confirm: None,
+            history: History::default(),
+            click_areas: ClickAreas::default(),
+            keys,
+            theme: themes[0].clone(),
+            themes,
+            open_with: args.open_with.clone(),
+            db_path: args.db_path.clone(),
+            last_save: Instant::now(),
+            toasts: VecDeque::new(),
+            suspended_viewer: None,
+            read_only: args.read_only,
+            saved_view,
+            discard_on_exit: false,
+            args: args.clone(),
+            recents: Recents::default(),
+        })
     }
 
     fn addable_labels(
         db: &ReplayDB,
         existing_labels: &[LabelInput],
     ) -> impl Iterator<Item = Label> {
-        db.labels
+        db.labels()
             .iter()
             .filter(|new_label| {
-                !existing_labels
-                    .iter()
-                    .any(|existing_label| existing_label.label == **new_label)
+                !db.is_locked(new_label)
+                    && !existing_labels
+                        .iter()
+                        .any(|existing_label| existing_label.label == **new_label)
             })
             .cloned()
     }
 
-    fn number_addable_labels(db: &ReplayDB, existing_labels: &[LabelInput]) -> usize {
-        Self::addable_labels(db, existing_labels).count()
+    /// Indices of the `addable` labels shown while `filter` is typed over the list
+    fn filter_addable(addable: &[Label], filter: &str) -> Vec<usize> {
+        (0..addable.len())
+            .filter(|&n| fuzzy_matches(&addable[n].name, filter))
+            .collect()
     }
 
-    fn run(mut self, terminal: &mut DefaultTerminal) {
-        loop {
-            terminal.draw(|frame| self.draw(frame)).unwrap();
+    /// Stores `input` as `entity`'s value for its label without touching any other column,
+    /// checked the same way as saving from the editor. Returns why it was rejected.
+    fn set_cell(
+        db: &mut ReplayDB,
+        history: &mut History,
+        entity: Entity,
+        input: &LabelInput,
+    ) -> Result<(), String> {
+        if db.is_locked(&input.label) {
+            return Err(format!("{} is locked", input.label.name));
+        }
+        ReplayInfoEditor::validate(db, entity, std::slice::from_ref(input))
+            .map_err(|invalid| format!("{} {}", input.label.name, invalid[0].1))?;
 
-            match &mut self.state {
-                AppState::ReplayDBViewer {
-                    table_state,
-                    scroll_state: _,
-                } => {
-                    let event = event::read().unwrap();
-                    if let Event::Key(key) = event {
-                        match key.code {
-                            KeyCode::Esc => return,
-                            KeyCode::Up => self.prev_row(),
-                            KeyCode::Down => self.next_row(),
-                            KeyCode::Right => table_state.select_next_column(),
-                            KeyCode::Left => table_state.select_previous_column(),
-                            KeyCode::Char('e') => {
-                                let selected_row = table_state.selected().unwrap();
-
-                                let (_, selected_entity) = self
-                                    .replay_db
-                                    .world
-                                    .join(WithEntities)
-                                    .enumerate()
-                                    .find(|(n, _)| n == &selected_row)
-                                    .unwrap();
+        let value = if ReplayInfoEditor::clears_value(input) {
+            None
+        } else {
+            Some(Value::parse(&input.label.data, input.data.value())?)
+        };
+        if db.get_value(&input.label, entity) == value {
+            return Ok(());
+        }
+        let before = db.row_file(entity);
+        db.remove_value(&input.label, entity);
+        if let Some(value) = value {
+            db.insert_value(&input.label, entity, value);
+        }
+        db.touch(entity);
+        history.record(db, entity, Some(before));
+        Ok(())
+    }
 
-                                self.state = AppState::ReplayInfoEditor(ReplayInfoEditor::new(
-                                    &self.replay_db,
-                                    selected_entity,
-                                ));
-                            }
-                            KeyCode::Char('n') => {
-                                let selected_entity = self.replay_db.world.spawn().id();
+    /// Sets `input`'s column to its value on every one of `entities`, or clears it for unticked
+    /// Unit and blank Number and Decimal inputs. The value is checked once before anything is
+    /// written, then each changed row is recorded separately. Returns how many rows changed.
+    fn set_cells(
+        db: &mut ReplayDB,
+        history: &mut History,
+        entities: &[Entity],
+        input: &LabelInput,
+    ) -> Result<usize, String> {
+        let Some(&first) = entities.first() else {
+            return Ok(0);
+        };
+        if db.is_locked(&input.label) {
+            return Err(format!("{} is locked", input.label.name));
+        }
+        let clears = ReplayInfoEditor::clears_value(input);
+        if !clears && entities.len() > 1 && db.config(&input.label).unique {
+            return Err(format!(
+                "{} is unique so it can't be set on {} rows",
+                input.label.name,
+                entities.len()
+            ));
+        }
+        ReplayInfoEditor::validate(db, first, std::slice::from_ref(input))
+            .map_err(|invalid| format!("{} {}", input.label.name, invalid[0].1))?;
 
-                                self.state = AppState::ReplayInfoEditor(ReplayInfoEditor::new(
-                                    &self.replay_db,
-                                    selected_entity,
-                                ));
-                            }
-                            _ => (),
-                        }
-                    }
+        let value = if clears {
+            None
+        } else {
+            Some(Value::parse(&input.label.data, input.data.value())?)
+        };
+        let mut changed = 0;
+        for &entity in entities {
+            if db.get_value(&input.label, entity) == value {
+                continue;
+            }
+            let before = db.row_file(entity);
+            db.remove_value(&input.label, entity);
+            if let Some(value) = &value {
+                db.insert_value(&input.label, entity, value.clone());
+            }
+            db.touch(entity);
+            history.record(db, entity, Some(before));
+            changed += 1;
+        }
+        Ok(changed)
+    }
+
+    /// Forwards `event` to `input` unless its column is locked, returning whether the value changed
+    fn edit_label_input(db: &ReplayDB, input: &mut LabelInput, event: &Event) -> bool {
+        if db.is_locked(&input.label) {
+            return false;
+        }
+
+        // Unit and Bool fields are toggled rather than typed into
+        if let LabelDataKind::Unit | LabelDataKind::Bool = input.label.data {
+            let Event::Key(key) = event else {
+                return false;
+            };
+            if key.code != KeyCode::Char(' ') {
+                return false;
+            }
+            let toggled = match (&input.label.data, input.data.value()) {
+                (LabelDataKind::Unit, "") => UNIT_SET,
+                (LabelDataKind::Unit, _) => "",
+                (_, "true") => "false",
+                _ => "true",
+            };
+            input.data = TextField::new(toggled.to_string());
+            return true;
+        }
+
+        // Keys that couldn't be part of a number are ignored, what's left is still checked on save
+        if let LabelDataKind::Number | LabelDataKind::Decimal = input.label.data {
+            let mut edited = input.data.clone();
+            if edited.handle_event(event).is_none() {
+                return false;
+            }
+            let decimal = input.label.data == LabelDataKind::Decimal;
+            if !could_be_number(edited.value(), decimal) {
+                return false;
+            }
+            input.data = edited;
+            return true;
+        }
+
+        // Masked fields refuse characters that don't fit and fill in separators themselves
+        if let Some(mask) = &db.config(&input.label).mask
+            && let LabelDataKind::Text | LabelDataKind::FilePath = input.label.data
+        {
+            let mut edited = input.data.clone();
+            match edited.handle_event(event) {
+                None => return false,
+                // Only the cursor or selection moved
+                Some(changed) if !changed.value => {
+                    input.data = edited;
+                    return true;
                 }
-                AppState::ReplayInfoEditor(ReplayInfoEditor {
-                    entity,
-                    focus,
-                    labels,
-                }) => {
-                    let event = event::read().unwrap();
-                    if let Event::Key(key) = event {
-                        match key.code {
-                            KeyCode::Esc => {
-                                if let ReplayInfoEditorFocus::AddableLabel(_) = focus {
-                                    *focus = ReplayInfoEditorFocus::LabelAdd;
-                                } else {
-                                    self.state = AppState::ReplayDBViewer {
-                                        table_state: TableState::default().with_selected(0),
-                                        scroll_state: ScrollbarState::new(0),
-                                    }
-                                }
-                            }
-                            KeyCode::Up => {
-                                *focus = focus.prev_focus(
-                                    labels.len(),
-                                    Self::number_addable_labels(&self.replay_db, labels),
-                                )
-                            }
-                            KeyCode::Down | KeyCode::Tab => {
-                                *focus = focus.next_focus(
-                                    labels.len(),
-                                    Self::number_addable_labels(&self.replay_db, labels),
-                                    false,
-                                )
-                            }
-                            KeyCode::Enter => match *focus {
-                                ReplayInfoEditorFocus::LabelData(n) => {
-                                    *focus = focus.next_focus(
-                                        labels.len(),
-                                        Self::number_addable_labels(&self.replay_db, labels),
-                                        false,
-                                    );
-                                }
-                                ReplayInfoEditorFocus::LabelRemove(n) => {
-                                    *focus = focus.next_focus(
-                                        labels.len(),
-                                        Self::number_addable_labels(&self.replay_db, labels),
-                                        true,
-                                    );
-                                    labels.remove(n);
-                                }
-                                ReplayInfoEditorFocus::AddableLabel(n) => {
-                                    let label = Self::addable_labels(&self.replay_db, labels)
-                                        .nth(n)
-                                        .unwrap();
+                Some(_) => (),
+            }
+            let Some(masked) = apply_mask(mask, edited.value()) else {
+                return false;
+            };
+            // The cursor stays after the same typed character, wherever separators moved it
+            let typed = edited
+                .value()
+                .chars()
+                .take(edited.cursor())
+                .filter(|&c| !is_mask_separator(mask, c))
+                .count();
+            let (mut cursor, mut seen) = (0, 0);
+            for (n, slot) in mask.chars().take(masked.chars().count()).enumerate() {
+                if seen == typed {
+                    break;
+                }
+                if let '#' | 'A' = slot {
+                    seen += 1;
+                }
+                cursor = n + 1;
+            }
+            input.data = TextField::new(masked).with_cursor(cursor);
+            return true;
+        }
 
-                                    labels.push(LabelInput {
-                                        label,
-                                        data: Input::new("".to_string()),
-                                    });
+        // Enum fields can only hold one of their variants so they're cycled through rather than
+        // typed into
+        let LabelDataKind::Enum { variants } = &input.label.data else {
+            return input.data.handle_event(event).is_some();
+        };
+        let step = match event {
+            Event::Key(key) if key.code == KeyCode::Right => 1,
+            Event::Key(key) if key.code == KeyCode::Left => variants.len() - 1,
+            _ => return false,
+        };
+        let current = variants
+            .iter()
+            .position(|variant| variant == input.data.value());
+        let next = current.map_or(0, |current| (current + step) % variants.len());
+        input.data = TextField::new(variants[next].clone());
+        true
+    }
 
-                                    *focus = ReplayInfoEditorFocus::LabelData(labels.len() - 1);
-                                }
-                                ReplayInfoEditorFocus::LabelAdd => {
-                                    if Self::number_addable_labels(&self.replay_db, labels) > 0 {
-                                        *focus = ReplayInfoEditorFocus::AddableLabel(0);
-                                    }
-                                }
-                                ReplayInfoEditorFocus::SaveChanges => {
-                                    if labels.is_empty() {
-                                        self.replay_db.world.despawn(*entity);
-                                    } else {
-                                        // FIXME: this is really slow lol. (but maybe doesn't matter?)
-                                        for label in &self.replay_db.labels {
-                                            let col =
-                                                self.replay_db.columns.get_mut(label).unwrap();
-                                            col.remove_component(
-                                                &mut self.replay_db.world,
-                                                *entity,
-                                            );
-                                        }
+    /// Moves the `n`th addable label into the editor's labels
+    fn add_label(labels: &mut Vec<LabelInput>, addable: &mut Vec<Label>, n: usize) {
+        let label = addable.remove(n);
+        let initial = match &label.data {
+            LabelDataKind::Enum { variants } => variants[0].clone(),
+            LabelDataKind::Unit => UNIT_SET.to_string(),
+            LabelDataKind::Bool => "false".to_string(),
+            _ => "".to_string(),
+        };
+        labels.push(LabelInput {
+            label,
+            data: TextField::new(initial),
+        });
+    }
 
-                                        for label in labels {
-                                            let (n, s);
-
-                                            let col = self
-                                                .replay_db
-                                                .columns
-                                                .get_mut(&label.label)
-                                                .unwrap();
-
-                                            // FIXME: actually require the user written data is validated
-                                            let typed_data = match label.label.data {
-                                                LabelDataKind::Number => unsafe {
-                                                    n = str::parse::<i16>(label.data.value())
-                                                        .unwrap();
-                                                    uninit_slice_from_borrow::<i16>(&n)
-                                                },
-                                                LabelDataKind::Text => unsafe {
-                                                    s = ManuallyDrop::new(
-                                                        label.data.value().to_string(),
-                                                    );
-                                                    uninit_slice_from_borrow::<ManuallyDrop<String>>(
-                                                        &s,
-                                                    )
-                                                },
-                                                LabelDataKind::Unit => unsafe {
-                                                    uninit_slice_from_borrow(&())
-                                                },
-                                            };
-
-                                            col.insert_component(
-                                                &mut self.replay_db.world,
-                                                *entity,
-                                                typed_data,
-                                            );
-                                        }
-                                    }
+    /// Removes the `n`th label from the editor, making it addable again
+    fn remove_label(
+        db: &ReplayDB,
+        labels: &mut Vec<LabelInput>,
+        addable: &mut Vec<Label>,
+        n: usize,
+    ) {
+        let label = labels.remove(n).label;
+        let schema_idx = |label: &Label| db.labels().iter().position(|l| l == label);
+        let idx = addable
+            .iter()
+            .position(|other| schema_idx(other) > schema_idx(&label))
+            .unwrap_or(addable.len());
+        addable.insert(idx, label);
+    }
 
-                                    self.state = AppState::ReplayDBViewer {
-                                        table_state: TableState::default().with_selected(0),
-                                        scroll_state: ScrollbarState::new(0),
-                                    };
-                                }
-                            },
-                            _ => match focus {
-                                ReplayInfoEditorFocus::LabelData(n) => {
-                                    _ = labels[*n].data.handle_event(&event);
-                                }
-                                ReplayInfoEditorFocus::SaveChanges
-                                | ReplayInfoEditorFocus::AddableLabel(_)
-                                | ReplayInfoEditorFocus::LabelRemove(_)
-                                | ReplayInfoEditorFocus::LabelAdd => (),
-                            },
-                        }
-                    }
+    /// Values other entities have for `input`'s Text column that start with what's been typed
+    /// so far, most used first
+    fn text_suggestions(db: &ReplayDB, entity: Entity, input: &LabelInput) -> Vec<String> {
+        let typed = input.data.value().to_lowercase();
+        if input.label.data != LabelDataKind::Text
+            || typed.is_empty()
+            || db.is_locked(&input.label)
+            || db.is_multiline(&input.label)
+        {
+            return vec![];
+        }
+
+        let mut counts = HashMap::<String, usize>::new();
+        for other in db.find(&input.label, |_| true) {
+            if other == entity {
+                continue;
+            }
+
+            if let Some(Value::Text(text)) = db.get_value(&input.label, other) {
+                let lowercase = text.to_lowercase();
+                if lowercase.starts_with(&typed) && lowercase != typed {
+                    *counts.entry(text).or_default() += 1;
                 }
             }
         }
+
+        let mut suggestions = counts.into_iter().collect::<Vec<_>>();
+        suggestions.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+        suggestions
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(text, _)| text)
+            .collect()
     }
 
-    fn next_row(&mut self) {
-        let AppState::ReplayDBViewer { table_state, .. } = &mut self.state else {
+    /// Spawns the next [`IMPORT_BATCH`] rows of the CSV import, reporting progress in the
+    /// status line and the outcome once it's done
+    fn step_import(&mut self) {
+        let Some(import) = &mut self.import else {
             return;
         };
+        // Waiting on `answer_new_columns`
+        if !import.new_columns.is_empty() {
+            return;
+        }
+        let done = self.replay_db.import_csv_batch(import, IMPORT_BATCH);
+        let path = import.path.display();
+        let imported = import.imported;
+        if !done {
+            self.status = Some(format!(
+                "Imported {imported} / {} rows from {path}",
+                import.total
+            ));
+            return;
+        }
 
-        let i = match table_state.selected() {
-            Some(i) => {
-                if i >= /* self.items.len() */ 10 - 1 {
-                    0
-                } else {
-                    i + 1
-                }
-            }
-            None => 0,
-        };
-        table_state.select(Some(i));
+        self.toasts.push_back(Toast::new(match &*import.errors {
+            [] => format!("Imported {imported} rows from {path}"),
+            errors => format!(
+                "Imported {imported} rows from {path}, skipped {}: {}",
+                errors.len(),
+                errors.join("; ")
+            ),
+        }));
+        self.status = None;
+        self.import = None;
     }
 
-    fn prev_row(&mut self) {
-        let AppState::ReplayDBViewer { table_state, .. } = &mut self.state else {
+    /// Handles a key pressed while choosing the kinds of the columns an import will create:
+    /// Up/Down pick a column, Left/Right change its kind, Enter creates them all and starts the
+    /// import and Esc cancels it
+    fn answer_new_columns(&mut self, key: KeyCode) {
+        let Some(import) = &mut self.import else {
             return;
         };
-
-        let i = match table_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    /* self.items.len() */
-                    10 - 1
-                } else {
-                    i - 1
+        let count = import.new_columns.len();
+        let column = &mut import.new_columns[import.selected];
+        match key {
+            KeyCode::Up | KeyCode::Char('k') => {
+                import.selected = (import.selected + count - 1) % count
+            }
+            KeyCode::Down | KeyCode::Char('j') => import.selected = (import.selected + 1) % count,
+            KeyCode::Left | KeyCode::Char('h') => {
+                column.kind = (column.kind + NEW_LABEL_KINDS.len() - 1) % NEW_LABEL_KINDS.len();
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                column.kind = (column.kind + 1) % NEW_LABEL_KINDS.len();
+            }
+            KeyCode::Enter => {
+                if let Err(err) = self.replay_db.create_import_columns(import) {
+                    self.toasts.push_back(Toast::new(format!(
+                        "Importing {} failed: {err}",
+                        import.path.display()
+                    )));
+                    self.import = None;
                 }
             }
-            None => 0,
-        };
-        table_state.select(Some(i));
+            KeyCode::Esc => {
+                self.toasts.push_back(Toast::new(format!(
+                    "Cancelled importing {}",
+                    import.path.display()
+                )));
+                self.import = None;
+            }
+            _ => {}
+        }
     }
 
-    fn draw(&mut self, frame: &mut Frame) {
-        match &mut self.state {
-            AppState::ReplayDBViewer {
-                table_state,
-                scroll_state,
-            } => {
-                let header_style = Style::default()
-                    .fg(tailwind::SLATE.c200)
-                    .bg(tailwind::BLUE.c900);
-                let selected_row_style = Style::default()
-                    .add_modifier(Modifier::REVERSED)
-                    .fg(tailwind::BLUE.c400);
-                let selected_col_style = Style::default().fg(tailwind::BLUE.c400);
-                let selected_cell_style = Style::default()
-                    .add_modifier(Modifier::REVERSED)
-                    .fg(tailwind::BLUE.c600);
+    /// Saves the database if anything has changed since the last save and it's been at least
+    /// [`AUTOSAVE_INTERVAL`]. Edits still in the editor aren't in the database until they're
+    /// saved there, so a half typed value is never written to disk.
+    fn autosave(&mut self) {
+        if self.read_only || self.last_save.elapsed() < AUTOSAVE_INTERVAL {
+            return;
+        }
+        // Restarted whether or not anything needs writing so a failure is only retried (and
+        // reported) once an interval
+        self.last_save = Instant::now();
+        if let Err(err) = self.save_view() {
+            self.toasts
+                .push_back(Toast::new(format!("Saving view settings failed: {err}")));
+        }
+        if !self.replay_db.is_dirty() {
+            return;
+        }
+        self.toasts.push_back(Toast::new(
+            match self.replay_db.save_to_path(&self.db_path) {
+                Ok(()) => {
+                    self.replay_db.mark_saved();
+                    "Saved".to_string()
+                }
+                // Left dirty so it's tried again next interval
+                Err(err) => format!("Autosave failed: {err}"),
+            },
+        ));
+    }
 
-                let header = self
-                    .replay_db
-                    .labels
-                    .iter()
-                    .map(|label| &*label.name)
-                    .into_iter()
-                    .map(widgets::Cell::from)
-                    .collect::<Row>()
-                    .style(header_style)
-                    .height(1);
+    /// Saves the database on the way out if anything changed, or if it isn't on disk yet so
+    /// it's there next time. Nothing is written after quitting with "discard", or while read
+    /// only, not even the backup, which is an error if there are changes it would lose.
+    fn save_on_exit(&mut self) -> Result<(), String> {
+        if self.discard_on_exit {
+            return Ok(());
+        }
+        if self.read_only && self.replay_db.is_dirty() {
+            return Err("the database is read only so unsaved changes weren't written".to_string());
+        }
+        if self.read_only || (!self.replay_db.is_dirty() && self.db_path.exists()) {
+            return Ok(());
+        }
+        self.replay_db.save_to_path(&self.db_path)?;
+        self.replay_db.mark_saved();
+        Ok(())
+    }
 
-                let rows = self
-                    .replay_db
-                    .world
-                    .join(WithEntities)
-                    .enumerate()
-                    .map(|(i, e)| {
-                        let color = match i % 2 {
-                            0 => tailwind::SLATE.c950,
-                            _ => tailwind::SLATE.c900,
-                        };
+    /// Suspends the TUI to open the database file in `$VISUAL` or `$EDITOR`, reloading it once
+    /// the editor exits. The database is saved first so the editor sees every change. Only
+    /// terminal errors are returned, anything else is shown as a toast.
+    fn edit_db_file(&mut self, terminal: &mut DefaultTerminal) -> std::io::Result<()> {
+        // Its rows would be spawned into the database that's about to be replaced
+        if self.import.is_some() {
+            self.status = Some("Wait for the import to finish first".to_string());
+            return Ok(());
+        }
+        if let Err(err) = self.replay_db.save_to_path(&self.db_path) {
+            self.toasts
+                .push_back(Toast::new(format!("Saving failed: {err}")));
+            return Ok(());
+        }
+        self.replay_db.mark_saved();
 
-                        let row_data = self.replay_db.labels.iter().map(|label| {
-                            let raw_data = self
-                                .replay_db
-                                .columns
-                                .get(label)
-                                .unwrap()
-                                .get_component(&self.replay_db.world, e);
+        let default = if cfg!(windows) { "notepad" } else { "vi" };
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .unwrap_or_else(|_| default.to_string());
+        // Often set with arguments of its own, e.g. `code --wait`
+        let mut words = editor.split_whitespace();
+        let program = words.next().unwrap_or(default);
 
-                            let Some(raw_data) = raw_data else {
-                                return "".to_string();
-                            };
+        crossterm::execute!(
+            std::io::stdout(),
+            DisableMouseCapture,
+            DisableBracketedPaste
+        )?;
+        ratatui::restore();
+        let status = Command::new(program)
+            .args(words)
+            .arg(&self.db_path)
+            .status();
+        crossterm::terminal::enable_raw_mode()?;
+        crossterm::execute!(
+            std::io::stdout(),
+            crossterm::terminal::EnterAlternateScreen,
+            EnableMouseCapture,
+            EnableBracketedPaste
+        )?;
+        terminal.clear()?;
 
-                            match label.data {
-                                LabelDataKind::Number => {
-                                    let typed_data = unsafe {
-                                        *(raw_data as *const [MaybeUninit<u8>] as *const i16)
-                                    };
+        let reloaded = match status {
+            Ok(status) if status.success() => self.reload(),
+            Ok(status) => Err(format!("`{program}` exited with {status}")),
+            Err(err) => Err(format!("failed to run `{program}`: {err}")),
+        };
+        if let Err(err) = reloaded {
+            self.toasts.push_back(Toast::new(format!(
+                "Kept the database as it was, Ctrl+E to try again: {err}"
+            )));
+        }
+        Ok(())
+    }
 
-                                    format!("{typed_data}")
-                                }
-                                LabelDataKind::Text => {
-                                    let typed_data = unsafe {
-                                        &*(raw_data as *const [MaybeUninit<u8>] as *const String)
-                                    };
+    /// Replaces the database with the one at [`App::db_path`], e.g. after it's been edited
+    /// outside the app. Every row is a new entity so the undo history, marks and popups are
+    /// dropped, as are view settings for columns that are gone.
+    fn reload(&mut self) -> Result<(), String> {
+        let db = ReplayDB::load_from_path(&self.db_path)?;
+        ViewState::from_view(&self.view).apply(&db, &mut self.view);
+        self.replay_db = db;
+        self.history = History::default();
 
-                                    typed_data.clone()
-                                }
-                                LabelDataKind::Unit => "X".to_string(),
-                            }
-                        });
+        if let AppState::ReplayDBViewer { table_state, .. } = &self.state {
+            self.state = self.viewer_state(table_state.selected().unwrap_or(0));
+        }
+        Ok(())
+    }
 
-                        row_data
-                            .map(|content| {
-                                widgets::Cell::from(Text::from(format!("\n{content}\n")))
-                            })
-                            .collect::<Row>()
-                            .style(Style::new().fg(tailwind::SLATE.c200).bg(color))
-                            .height(4)
-                    });
+    /// Reads the databases opened before and adds this one to the front of them. Like the view
+    /// settings a bad file is ignored rather than refusing to start.
+    fn load_recents(&mut self) {
+        let path = self.args.db_path.with_file_name(view_state::RECENTS_FILE);
+        if path.exists() {
+            self.recents = Recents::load_from_path(&path).unwrap_or_else(|err| {
+                tracing::warn!(%err, "ignoring recent databases");
+                Recents::default()
+            });
+        }
+        self.recents.opened(&self.db_path);
+    }
 
-                let bar = " █ ";
-                let table = Table::new(
-                    rows,
-                    // FIXME: Properly track max width of columns
-                    [Constraint::Min(10), Constraint::Min(10), Constraint::Min(9)],
-                )
-                .header(header)
-                .row_highlight_style(selected_row_style)
-                .column_highlight_style(selected_col_style)
-                .cell_highlight_style(selected_cell_style)
-                .highlight_symbol(Text::from(vec![
-                    "".into(),
-                    bar.into(),
-                    bar.into(),
-                    "".into(),
-                ]))
-                .bg(tailwind::SLATE.c950)
-                .highlight_spacing(HighlightSpacing::Always);
-
-                frame.render_stateful_widget(table, frame.area(), table_state);
-            }
+    /// Keeps the current view settings for this database in [`App::recents`] and writes it.
+    /// Writing is skipped while read only like [`App::save_view`].
+    fn save_recents(&mut self) -> Result<(), String> {
+        self.recents
+            .set_view(&self.db_path, ViewState::from_view(&self.view));
+        if self.read_only {
+            return Ok(());
+        }
+        self.recents
+            .save_to_path(&self.args.db_path.with_file_name(view_state::RECENTS_FILE))
+    }
 
-            AppState::ReplayInfoEditor(ReplayInfoEditor {
-                entity: _,
-                focus,
-                labels,
-            }) => {
-                let rects = layout::Layout::horizontal(Constraint::from_percentages([50, 50]))
-                    .split(frame.area());
-                let label_edit_area = rects[0];
+    /// Saves the database and opens the next one in [`App::recents`] in its place, with the
+    /// view settings it was last left with. Anything that goes wrong leaves the current database
+    /// open.
+    fn switch_db(&mut self) -> Result<(), String> {
+        // Its rows would be spawned into the wrong database
+        if self.import.is_some() {
+            return Err("wait for the import to finish first".to_string());
+        }
+        let Some(next) = self
+            .recents
+            .next_after(&self.db_path)
+            .map(Path::to_path_buf)
+        else {
+            return Err("no other database has been opened yet".to_string());
+        };
+        let db = ReplayDB::load_from_path(&next)?;
+        self.save_on_exit()?;
+        self.save_view()?;
+        self.save_recents()?;
 
-                // edit labels
-                let edit_labels_areas = layout::Layout::vertical(Constraint::from_lengths(
-                    (0..(labels.len() * 2 + 2)).map(|_| 1),
-                ))
-                .split(label_edit_area);
+        // Only the database launched with is imported into
+        let args = cli::Args {
+            db_path: next,
+            import: None,
+            read_only: self.read_only,
+            ..self.args.clone()
+        };
+        let mut app = App::with_db(&args, db)?;
+        if let Some(view) = self.recents.view(&args.db_path) {
+            view.apply(&app.replay_db, &mut app.view);
+            app.state = app.viewer_state(0);
+        }
+        app.args = self.args.clone();
+        app.recents = std::mem::take(&mut self.recents);
+        app.toasts = std::mem::take(&mut self.toasts);
+        app.status = Some(format!("Switched to {}", args.db_path.display()));
+        *self = app;
+        Ok(())
+    }
 
-                for (n, label) in labels.iter().enumerate() {
-                    // Draw the label name + user input
-                    let area = edit_labels_areas[n * 2];
+    /// Writes the view settings next to the database if they've changed since they were last
+    /// saved, so the next launch starts the same way. Skipped while read only, which is often
+    /// a shared location that shouldn't be written to.
+    fn save_view(&mut self) -> Result<(), String> {
+        let view = ViewState::from_view(&self.view);
+        if self.read_only || view == self.saved_view {
+            return Ok(());
+        }
+        view.save_to_path(&self.db_path.with_file_name(view_state::VIEW_STATE_FILE))?;
+        self.saved_view = view;
+        Ok(())
+    }
 
-                    let style = if let ReplayInfoEditorFocus::LabelData(n2) = focus
-                        && *n2 == n
-                    {
-                        Color::Yellow.into()
-                    } else {
-                        Style::default()
-                    };
+    /// Drops toasts that have been up for [`TOAST_DURATION`], and the oldest ones past
+    /// [`MAX_TOASTS`]
+    fn expire_toasts(&mut self) {
+        self.toasts
+            .retain(|toast| toast.shown_at.elapsed() < TOAST_DURATION);
+        while self.toasts.len() > MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+    }
 
-                    let constraints = [
-                        Constraint::Length(label.label.name.len() as u16 + 2),
-                        Constraint::Fill(0),
-                    ];
-                    let [label_area, value_area] = Layout::horizontal(constraints).areas(area);
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> std::io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
 
-                    let line = Line::from_iter([&*label.label.name, ": "])
-                        .bold()
-                        .style(style);
-                    frame.render_widget(line, label_area);
-                    frame.render_widget(label.data.value(), value_area);
+            // Redraw every tick even without input so anything time based stays current, the
+            // `event::read` below only runs once an event is ready so it never blocks
+            self.autosave();
+            self.step_import();
+            self.expire_toasts();
+            // Keep importing as fast as possible, input is still handled between batches
+            let timeout = if self
+                .import
+                .as_ref()
+                .is_some_and(|import| import.new_columns.is_empty())
+            {
+                Duration::ZERO
+            } else {
+                TICK_RATE
+            };
+            if !event::poll(timeout)? {
+                continue;
+            }
+            let event = event::read()?;
+            // Drawing again straight away re-flows everything to the new size
+            if let Event::Resize(..) = event {
+                continue;
+            }
 
-                    // Draw the delete label "button"
-                    let area = edit_labels_areas[n * 2 + 1];
-                    let style: Style = if let ReplayInfoEditorFocus::LabelRemove(n2) = focus
-                        && *n2 == n
-                    {
-                        Color::Red.into()
-                    } else {
-                        Color::Black.into()
-                    };
-                    let line = Line::raw("Delete Label").style(style).bold();
-                    frame.render_widget(line, area);
+            if self.show_help {
+                if let Event::Key(_) = event {
+                    self.show_help = false;
                 }
+                continue;
+            }
 
-                // Draw the add label "button"
-                let area = edit_labels_areas[labels.len() * 2];
-                let style: Style = if let ReplayInfoEditorFocus::LabelAdd = focus {
-                    Color::Blue.into()
-                } else {
-                    Color::Black.into()
-                };
-                let line = Line::raw("Add Label").style(style).bold();
-                frame.render_widget(line, area);
+            if self.confirm.is_some() {
+                if let Event::Key(key) = event
+                    && self.answer_confirm(key.code)
+                {
+                    return Ok(());
+                }
+                continue;
+            }
 
-                // Draw the save changes "button"
-                let area = edit_labels_areas[labels.len() * 2 + 1];
-                let style: Style = if let ReplayInfoEditorFocus::SaveChanges = focus {
-                    Color::Green.into()
-                } else {
-                    Color::Black.into()
-                };
-                let line = Line::raw("Save Changes").style(style).bold();
-                frame.render_widget(line, area);
+            if self
+                .import
+                .as_ref()
+                .is_some_and(|import| !import.new_columns.is_empty())
+            {
+                if let Event::Key(key) = event {
+                    self.answer_new_columns(key.code);
+                }
+                continue;
+            }
 
-                // add labels list
-                let add_label_area = rects[1];
+            match &mut self.state {
+                AppState::ReplayDBViewer {
+                    table_state,
+                    scroll_state: _,
+                    note_editor,
+                    cell_editor,
+                    replace_editor,
+                    schema_editor,
+                    search_input,
+                    goto_input,
+                    batch_editor,
+                    histogram,
+                    problems,
+                    marked,
+                } => {
+                    if let Event::Key(_) = event {
+                        self.status = None;
+                    }
 
-                let addable_labels: Vec<_> = self
-                    .replay_db
-                    .labels
-                    .iter()
-                    .filter(|new_label| {
-                        !labels
-                            .iter()
-                            .any(|existing_label| existing_label.label == **new_label)
-                    })
-                    .collect();
+                    // Read only, so there's nothing to do but close it
+                    if histogram.is_some() {
+                        if let Event::Key(key) = event
+                            && key.code == KeyCode::Esc
+                        {
+                            *histogram = None;
+                        }
+                        continue;
+                    }
 
-                let addable_labels_areas = layout::Layout::vertical(Constraint::from_lengths(
-                    addable_labels
-                        .iter()
-                        .map(|label| /* label.name.len() as u16 */ 1),
-                ))
-                .split(add_label_area);
+                    if let Some(list) = problems {
+                        if let Event::Key(key) = event {
+                            match key.code {
+                                KeyCode::Esc => *problems = None,
+                                KeyCode::Up | KeyCode::Char('k') => {
+                                    list.selected = list.selected.saturating_sub(1);
+                                }
+                                KeyCode::Down | KeyCode::Char('j') => {
+                                    list.selected =
+                                        (list.selected + 1).min(list.problems.len() - 1);
+                                }
+                                KeyCode::Enter => {
+                                    let (entity, label, _) = &list.problems[list.selected];
+                                    match self.replay_db.row_of(*entity, &self.view) {
+                                        _ if !self.replay_db.is_visible(label) => {
+                                            self.status = Some(format!("{} is hidden", label.name));
+                                        }
+                                        None => {
+                                            self.status = Some(
+                                                "That row is hidden by the current filter"
+                                                    .to_string(),
+                                            );
+                                        }
+                                        Some(row) => {
+                                            let col = self.replay_db.handle(label).index();
+                                            table_state.select_column(Some(col));
+                                            *problems = None;
+                                            self.select_row(row);
+                                        }
+                                    }
+                                }
+                                _ => (),
+                            }
+                        }
+                        continue;
+                    }
 
-                for (n, label) in addable_labels.iter().enumerate() {
-                    let style: Style = if let ReplayInfoEditorFocus::AddableLabel(selected_n) =
-                        focus
-                        && *selected_n == n
-                    {
-                        Color::Green.into()
-                    } else {
-                        Color::White.into()
-                    };
-                    let line = Line::raw(&label.name).style(style).bold();
-                    frame.render_widget(line, addable_labels_areas[n]);
-                }
+                    if let Some(editor) = note_editor {
+                        if let Event::Key(key) = event {
+                            match key.code {
+                                KeyCode::Esc => *note_editor = None,
+                                KeyCode::Enter => {
+                                    let note = editor.input.value().trim().to_string();
+                                    let before = self.replay_db.row_file(editor.entity);
+                                    self.replay_db.set_note(editor.entity, &editor.label, note);
+                                    self.history.record(
+                                        &self.replay_db,
+                                        editor.entity,
+                                        Some(before),
+                                    );
+                                    *note_editor = None;
+                                }
+                                _ => {
+                                    _ = editor.input.handle_event(&event);
+                                }
+                            }
+                        }
+                        continue;
+                    }
 
-                match focus {
-                    ReplayInfoEditorFocus::LabelData(n) => {
-                        let area = edit_labels_areas[*n * 2];
-                        let label = &labels[*n];
-                        let cursor_offset = label.data.cursor();
-                        frame.set_cursor_position(area.offset(layout::Offset {
-                            x: label.label.name.len() as i32 + 2 + cursor_offset as i32,
-                            y: 0,
-                        }));
+                    if let Some(editor) = cell_editor {
+                        match event {
+                            Event::Key(key) if key.code == KeyCode::Esc => *cell_editor = None,
+                            Event::Key(key) if key.code == KeyCode::Enter => {
+                                // Left open on a bad value so it can be fixed
+                                match Self::set_cell(
+                                    &mut self.replay_db,
+                                    &mut self.history,
+                                    editor.entity,
+                                    &editor.input,
+                                ) {
+                                    Ok(()) => *cell_editor = None,
+                                    Err(err) => self.status = Some(err),
+                                }
+                            }
+                            _ => {
+                                Self::edit_label_input(&self.replay_db, &mut editor.input, &event);
+                            }
+                        }
+                        continue;
                     }
 
-                    ReplayInfoEditorFocus::SaveChanges
-                    | ReplayInfoEditorFocus::AddableLabel(_)
-                    | ReplayInfoEditorFocus::LabelRemove(_)
-                    | ReplayInfoEditorFocus::LabelAdd => (),
-                }
-            }
-        }
+                    if let Some(input) = batch_editor {
+                        match event {
+                            Event::Key(key) if key.code == KeyCode::Esc => *batch_editor = None,
+                            Event::Key(key) if key.code == KeyCode::Enter => {
+                                // Rows in the viewer's order so undo goes through them bottom up
+                                let entities = self
+                                    .replay_db
+                                    .ordered_entities()
+                                    .into_iter()
+                                    .filter(|entity| marked.contains(entity))
+                                    .collect::<Vec<_>>();
+                                // Left open on a bad value so it can be fixed
+                                match Self::set_cells(
+                                    &mut self.replay_db,
+                                    &mut self.history,
+                                    &entities,
+                                    input,
+                                ) {
+                                    Ok(changed) => {
+                                        self.status = Some(format!(
+                                            "Set {} on {changed} rows",
+                                            input.label.name
+                                        ));
+                                        *batch_editor = None;
+                                    }
+                                    Err(err) => self.status = Some(err),
+                                }
+                            }
+                            _ => {
+                                Self::edit_label_input(&self.replay_db, input, &event);
+                            }
+                        }
+                        continue;
+                    }
+
+                    if let Some(editor) = replace_editor {
+                        if let Event::Key(key) = event {
+                            match key.code {
+                                KeyCode::Esc => *replace_editor = None,
+                                KeyCode::Tab | KeyCode::BackTab => {
+                                    editor.replace_focused = !editor.replace_focused;
+                                }
+                                KeyCode::Char('w') if key.modifiers == KeyModifiers::CONTROL => {
+                                    editor.whole_value = !editor.whole_value;
+                                }
+                                KeyCode::Enter => {
+                                    match self.replay_db.replace_text(
+                                        &editor.label,
+                                        editor.find.value(),
+                                        editor.replace.value(),
+                                        editor.whole_value,
+                                    ) {
+                                        Ok(changed) => {
+                                            self.status = Some(format!(
+                                                "Replaced {} values in {}",
+                                                changed.len(),
+                                                editor.label.name
+                                            ));
+                                            // Each changed row is undone on its own
+                                            for (entity, before) in changed {
+                                                self.history.record(
+                                                    &self.replay_db,
+                                                    entity,
+                                                    Some(before),
+                                                );
+                                            }
+                                            *replace_editor = None;
+                                        }
+                                        Err(err) => self.status = Some(err),
+                                    }
+                                }
+                                _ if editor.replace_focused => {
+                                    _ = editor.replace.handle_event(&event);
+                                }
+                                _ => {
+                                    _ = editor.find.handle_event(&event);
+                                }
+                            }
+                        }
+                        continue;
+                    }
+
+                    if let Some(input) = search_input {
+                        if let Event::Key(key) = event {
+                            match key.code {
+                                KeyCode::Esc => {
+                                    self.view.search = None;
+                                    *search_input = None;
+                                }
+                                KeyCode::Enter => *search_input = None,
+                                KeyCode::Tab => self.view.regex_search = !self.view.regex_search,
+                                _ => {
+                                    if input.handle_event(&event).is_some() {
+                                        let query = input.value();
+                                        self.view.search =
+                                            (!query.is_empty()).then(|| query.to_string());
+                                    }
+                                }
+                            }
+                            let rows = self.replay_db.row_count(&self.view);
+                            table_state.select((rows > 0).then_some(0));
+                        }
+                        continue;
+                    }
+
+                    if let Some(input) = goto_input {
+                        if let Event::Key(key) = event {
+                            match key.code {
+                                KeyCode::Esc => *goto_input = None,
+                                KeyCode::Enter => {
+                                    let text = input.value().to_string();
+                                    *goto_input = None;
+                                    if let Err(err) = self.jump_to_row(&text) {
+                                        self.status = Some(err);
+                                    }
+                                }
+                                // Only row numbers can be typed
+                                KeyCode::Char(c) if !c.is_ascii_digit() => (),
+                                _ => {
+                                    _ = input.handle_event(&event);
+                                }
+                            }
+                        }
+                        continue;
+                    }
+
+                    if let Some(editor) = schema_editor {
+                        if let Event::Key(key) = event {
+                            let columns = self.replay_db.labels().len();
+                            if let Some(name) = &mut editor.rename {
+                                match key.code {
+                                    KeyCode::Esc => editor.rename = None,
+                                    KeyCode::Enter => {
+                                        let label =
+                                            self.replay_db.labels()[editor.selected].clone();
+                                        match self.replay_db.rename_label(&label, name.value()) {
+                                            Ok(renamed) => {
+                                                if self.view.missing
+                                                    == Some(MissingFilter::Column(label.clone()))
+                                                {
+                                                    self.view.missing = Some(
+                                                        MissingFilter::Column(renamed.clone()),
+                                                    );
+                                                }
+                                                for (sorted, _) in &mut self.view.sort {
+                                                    if *sorted == label {
+                                                        *sorted = renamed.clone();
+                                                    }
+                                                }
+                                                self.status = Some(format!(
+                                                    "Renamed {} to {}",
+                                                    label.name, renamed.name
+                                                ));
+                                                editor.rename = None;
+                                            }
+                                            Err(err) => self.status = Some(err),
+                                        }
+                                    }
+                                    _ => {
+                                        _ = name.handle_event(&event);
+                                    }
+                                }
+                                continue;
+                            }
+
+                            match key.code {
+                                KeyCode::Char('y') if editor.confirm_delete => {
+                                    let label = self.replay_db.labels()[editor.selected].clone();
+                                    self.status =
+                                        Some(match self.replay_db.delete_column(&label) {
+                                            Ok(()) => format!("Deleted column {}", label.name),
+                                            Err(err) => err,
+                                        });
+
+                                    // Don't leave the viewer pointing at the deleted column
+                                    self.view.sort.retain(|(sorted, _)| *sorted != label);
+                                    if self.view.missing == Some(MissingFilter::Column(label)) {
+                                        self.view.missing = None;
+                                    }
+                                    let columns = self.replay_db.labels().len();
+                                    editor.selected = editor.selected.min(columns - 1);
+                                    editor.confirm_delete = false;
+                                    if let Some(col) = table_state.selected_column() {
+                                        table_state.select_column(Some(col.min(columns - 1)));
+                                    }
+                                }
+                                _ if editor.confirm_delete => editor.confirm_delete = false,
+                                KeyCode::Esc => *schema_editor = None,
+                                KeyCode::Up => {
+                                    editor.selected = (editor.selected + columns - 1) % columns;
+                                }
+                                KeyCode::Down => editor.selected = (editor.selected + 1) % columns,
+                                KeyCode::Char('d') | KeyCode::Delete => {
+                                    editor.confirm_delete = true;
+                                }
+                                KeyCode::Char('r') => {
+                                    let name = &self.replay_db.labels()[editor.selected].name;
+                                    editor.rename = Some(TextField::new(name.clone()));
+                                }
+                                KeyCode::Char('s') => {
+                                    // Both selections stay on the label they were on
+                                    let labels = &self.replay_db.labels();
+                                    let selected = labels[editor.selected].clone();
+                                    let column = table_state
+                                        .selected_column()
+                                        .and_then(|col| labels.get(col).cloned());
+
+                                    self.status = Some(if self.replay_db.sort_columns() {
+                                        "Sorted columns by name".to_string()
+                                    } else {
+                                        "Columns are already sorted".to_string()
+                                    });
+
+                                    let position = |label: &Label| {
+                                        self.replay_db.labels().iter().position(|l| l == label)
+                                    };
+                                    editor.selected = position(&selected).unwrap();
+                                    if let Some(column) = column {
+                                        table_state.select_column(position(&column));
+                                    }
+                                }
+                                KeyCode::Char('v') => {
+                                    let label = self.replay_db.labels()[editor.selected].clone();
+                                    match self.replay_db.toggle_visible(&label) {
+                                        Ok(visible) => {
+                                            let shown = if visible { "Showing" } else { "Hid" };
+                                            self.status = Some(format!("{shown} {}", label.name));
+                                        }
+                                        Err(err) => self.status = Some(err),
+                                    }
+
+                                    // Don't leave the viewer on a column it no longer draws
+                                    let col = table_state.selected_column();
+                                    if col.is_some_and(|col| {
+                                        !self.replay_db.shown_columns(&self.view).contains(&col)
+                                    }) {
+                                        let col = self.replay_db.step_column(col, &self.view, true);
+                                        table_state.select_column(Some(col));
+                                    }
+                                }
+                                KeyCode::Char('p') => {
+                                    let label = self.replay_db.labels()[editor.selected].clone();
+                                    let pinned = self.replay_db.toggle_pinned(&label);
+                                    let pinned = if pinned { "Pinned" } else { "Unpinned" };
+                                    self.status = Some(format!("{pinned} {}", label.name));
+                                }
+                                _ => (),
+                            }
+                        }
+                        continue;
+                    }
+
+                    if let Event::Mouse(mouse) = event {
+                        match mouse.kind {
+                            MouseEventKind::Down(MouseButton::Left) => {
+                                let area = self.click_areas.table_rows;
+                                if area.contains(Position::new(mouse.column, mouse.row)) {
+                                    let row = table_state.offset()
+                                        + ((mouse.row - area.y) / self.view.row_height()) as usize;
+                                    if row < self.replay_db.row_count(&self.view) {
+                                        table_state.select(Some(row));
+                                    }
+                                }
+                            }
+                            MouseEventKind::ScrollUp => self.prev_row(),
+                            MouseEventKind::ScrollDown => self.next_row(),
+                            _ => (),
+                        }
+                        continue;
+                    }
+
+                    if let Event::Key(key) = event {
+                        let action = self.keys.viewer(&key);
+                        if self.read_only && Self::edits_database(&key, action) {
+                            self.status = Some("Read only, press 'W' to allow changes".to_string());
+                            continue;
+                        }
+                        match key.code {
+                            _ if action == Some(ViewerAction::Quit) && !marked.is_empty() => {
+                                marked.clear();
+                            }
+                            _ if action == Some(ViewerAction::Quit)
+                                && self.replay_db.is_dirty() =>
+                            {
+                                self.confirm = Some(Confirm {
+                                    message: "There are unsaved changes".to_string(),
+                                    action: PendingAction::Quit,
+                                });
+                            }
+                            _ if action == Some(ViewerAction::Quit) => return Ok(()),
+                            _ if action == Some(ViewerAction::Help) => self.show_help = true,
+                            _ if action == Some(ViewerAction::PrevRow) => self.prev_row(),
+                            _ if action == Some(ViewerAction::NextRow) => self.next_row(),
+                            _ if action == Some(ViewerAction::FirstRow) => self.select_row(0),
+                            _ if action == Some(ViewerAction::LastRow) => {
+                                self.select_row(usize::MAX)
+                            }
+                            _ if action == Some(ViewerAction::PrevPage) => self.move_page(false),
+                            _ if action == Some(ViewerAction::NextPage) => self.move_page(true),
+                            KeyCode::Left | KeyCode::Right
+                                if key.modifiers.contains(KeyModifiers::CONTROL) =>
+                            {
+                                let later = key.code == KeyCode::Right;
+                                let moved = table_state
+                                    .selected_column()
+                                    .and_then(|col| self.replay_db.move_column(col, later));
+                                if let Some(col) = moved {
+                                    table_state.select_column(Some(col));
+                                }
+                            }
+                            _ if action == Some(ViewerAction::NextColumn) => {
+                                let col = table_state.selected_column();
+                                let col = self.replay_db.step_column(col, &self.view, true);
+                                table_state.select_column(Some(col));
+                            }
+                            _ if action == Some(ViewerAction::PrevColumn) => {
+                                let col = table_state.selected_column();
+                                let col = self.replay_db.step_column(col, &self.view, false);
+                                table_state.select_column(Some(col));
+                            }
+                            _ if action == Some(ViewerAction::EditEntity) => {
+                                let selected_entity = table_state
+                                    .selected()
+                                    .and_then(|row| self.replay_db.nth_entity(row, &self.view));
+
+                                if let Some(selected_entity) = selected_entity {
+                                    self.open_editor(selected_entity);
+                                }
+                            }
+                            _ if action == Some(ViewerAction::NewEntity) => {
+                                let selected_entity = self.replay_db.spawn();
+                                self.history.record(&self.replay_db, selected_entity, None);
+                                self.open_editor(selected_entity);
+                            }
+                            KeyCode::F(1) => self.show_help = true,
+                            // Vim style aliases for the arrow keys
+                            KeyCode::Char('j') => self.next_row(),
+                            KeyCode::Char('k') => self.prev_row(),
+                            KeyCode::Char(c @ ('h' | 'l')) => {
+                                let col = table_state.selected_column();
+                                let col = self.replay_db.step_column(col, &self.view, c == 'l');
+                                table_state.select_column(Some(col));
+                            }
+                            KeyCode::Char('g') => self.select_row(0),
+                            KeyCode::Char('G') => self.select_row(usize::MAX),
+                            KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                                self.view.hex_numbers = !self.view.hex_numbers;
+                            }
+                            KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                                self.edit_db_file(terminal)?;
+                            }
+                            KeyCode::Tab if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                                if let Err(err) = self.switch_db() {
+                                    self.toasts.push_back(Toast::new(format!(
+                                        "Switching databases failed: {err}"
+                                    )));
+                                }
+                            }
+                            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                                let label = table_state
+                                    .selected_column()
+                                    .and_then(|col| self.replay_db.labels().get(col))
+                                    .cloned();
+                                if let Some(label) = label {
+                                    self.view.toggle_sort_key(label);
+                                }
+                            }
+                            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                                if !self.history.redo(&mut self.replay_db) {
+                                    self.status = Some("Nothing to redo".to_string());
+                                }
+                                let rows = self.replay_db.row_count(&self.view);
+                                let row = table_state.selected().unwrap_or(0);
+                                table_state.select((rows > 0).then(|| row.min(rows - 1)));
+                            }
+                            KeyCode::Char('u') => {
+                                if !self.history.undo(&mut self.replay_db) {
+                                    self.status = Some("Nothing to undo".to_string());
+                                }
+                                let rows = self.replay_db.row_count(&self.view);
+                                let row = table_state.selected().unwrap_or(0);
+                                table_state.select((rows > 0).then(|| row.min(rows - 1)));
+                            }
+                            KeyCode::Char('c') => self.view.compact = !self.view.compact,
+                            KeyCode::Char(c @ ('m' | 'M')) => {
+                                let filter = match c {
+                                    'M' => table_state
+                                        .selected_column()
+                                        .and_then(|col| self.replay_db.labels().get(col))
+                                        .cloned()
+                                        .map(MissingFilter::Column),
+                                    _ => Some(MissingFilter::Any),
+                                };
+                                self.view.missing = if self.view.missing == filter {
+                                    None
+                                } else {
+                                    filter
+                                };
+
+                                let rows = self.replay_db.row_count(&self.view);
+                                table_state.select((rows > 0).then_some(0));
+                            }
+                            KeyCode::Char('t') => {
+                                self.view.show_timestamps = !self.view.show_timestamps;
+                            }
+                            KeyCode::Char('i') => self.view.show_ids = !self.view.show_ids,
+                            KeyCode::Char('W') => {
+                                self.read_only = !self.read_only;
+                                self.status = Some(if self.read_only {
+                                    "Read only, nothing can be changed".to_string()
+                                } else {
+                                    "Changes allowed".to_string()
+                                });
+                            }
+                            KeyCode::Char('T') => {
+                                let next = self
+                                    .themes
+                                    .iter()
+                                    .position(|theme| theme.name == self.theme.name)
+                                    .map_or(0, |n| (n + 1) % self.themes.len());
+                                self.theme = self.themes[next].clone();
+                                self.status = Some(format!("Theme: {}", self.theme.name));
+                            }
+                            KeyCode::Char(' ') | KeyCode::Enter => {
+                                let entity = table_state
+                                    .selected()
+                                    .and_then(|row| self.replay_db.nth_entity(row, &self.view));
+                                let label = table_state
+                                    .selected_column()
+                                    .and_then(|col| self.replay_db.labels().get(col))
+                                    .cloned();
+
+                                let Some((entity, label)) = entity.zip(label) else {
+                                    continue;
+                                };
+                                // Enter edits the cell in place, apart from Unit cells which
+                                // can only be toggled
+                                if key.code == KeyCode::Enter && label.data != LabelDataKind::Unit {
+                                    if self.replay_db.is_locked(&label) {
+                                        self.status = Some(format!("{} is locked", label.name));
+                                        continue;
+                                    }
+                                    if self.replay_db.is_multiline(&label) {
+                                        self.status = Some(format!(
+                                            "{} is multi-line, edit it from the row editor",
+                                            label.name
+                                        ));
+                                        continue;
+                                    }
+                                    let text = self
+                                        .replay_db
+                                        .get_value(&label, entity)
+                                        .map_or(String::new(), Value::input_text);
+                                    *cell_editor = Some(CellEditor {
+                                        entity,
+                                        input: LabelInput {
+                                            label,
+                                            data: TextField::new(text),
+                                        },
+                                    });
+                                } else {
+                                    let before = self.replay_db.row_file(entity);
+                                    match self.replay_db.toggle_unit(&label, entity) {
+                                        Ok(()) => self.history.record(
+                                            &self.replay_db,
+                                            entity,
+                                            Some(before),
+                                        ),
+                                        Err(err) => self.status = Some(err),
+                                    }
+                                }
+                            }
+                            KeyCode::Char('v') => {
+                                let entity = table_state
+                                    .selected()
+                                    .and_then(|row| self.replay_db.nth_entity(row, &self.view));
+
+                                if let Some(entity) = entity {
+                                    if !marked.remove(&entity) {
+                                        marked.insert(entity);
+                                    }
+                                    self.status = Some(format!("{} rows marked", marked.len()));
+                                }
+                            }
+                            KeyCode::Delete => {
+                                self.confirm = match marked.len() {
+                                    0 => {
+                                        self.status =
+                                            Some("Mark rows to delete with v first".to_string());
+                                        None
+                                    }
+                                    n => Some(Confirm {
+                                        message: format!("Delete {n} marked rows?"),
+                                        action: PendingAction::DespawnMarked,
+                                    }),
+                                };
+                            }
+                            KeyCode::Char('d') => {
+                                let entity = table_state
+                                    .selected()
+                                    .and_then(|row| self.replay_db.nth_entity(row, &self.view));
+
+                                if let Some(entity) = entity {
+                                    let copy = self.replay_db.duplicate_row(entity);
+                                    self.history.record(&self.replay_db, copy, None);
+                                    if let Some(row) = self.replay_db.row_of(copy, &self.view) {
+                                        table_state.select(Some(row));
+                                    }
+                                }
+                            }
+                            KeyCode::Char('D') => {
+                                let label = table_state
+                                    .selected_column()
+                                    .and_then(|col| self.replay_db.labels().get(col))
+                                    .cloned();
+
+                                match label.map(|label| self.replay_db.duplicate_column(&label)) {
+                                    Some(Ok(copy)) => {
+                                        self.status =
+                                            Some(format!("Duplicated column as {}", copy.name));
+                                    }
+                                    Some(Err(err)) => self.toasts.push_back(Toast::new(format!(
+                                        "Duplicating the column failed: {err}"
+                                    ))),
+                                    None => (),
+                                }
+                            }
+                            KeyCode::Char('K') => {
+                                let label = table_state
+                                    .selected_column()
+                                    .and_then(|col| self.replay_db.labels().get(col))
+                                    .cloned();
+
+                                if let Some(label) = label {
+                                    let duplicates = self.replay_db.duplicates(&label);
+                                    self.confirm = match duplicates.len() {
+                                        0 => {
+                                            self.status = Some(format!(
+                                                "No rows share a {} value",
+                                                label.name
+                                            ));
+                                            None
+                                        }
+                                        n => Some(Confirm {
+                                            message: format!(
+                                                "Delete {n} rows repeating a {} value?",
+                                                label.name
+                                            ),
+                                            action: PendingAction::DespawnDuplicates(duplicates),
+                                        }),
+                                    };
+                                }
+                            }
+                            KeyCode::Char('L') => {
+                                let label = table_state
+                                    .selected_column()
+                                    .and_then(|col| self.replay_db.labels().get(col))
+                                    .cloned();
+
+                                if let Some(label) = label {
+                                    let locked = self.replay_db.toggle_lock(&label);
+                                    self.status = Some(if locked {
+                                        format!("Locked {}", label.name)
+                                    } else {
+                                        format!("Unlocked {}", label.name)
+                                    });
+                                }
+                            }
+                            KeyCode::Char('p') => {
+                                let entity = table_state
+                                    .selected()
+                                    .and_then(|row| self.replay_db.nth_entity(row, &self.view));
+                                let label = table_state
+                                    .selected_column()
+                                    .and_then(|col| self.replay_db.labels().get(col));
+
+                                if let (Some(entity), Some(label)) = (entity, label) {
+                                    let value = self.replay_db.get_value(label, entity);
+                                    self.status = Some(match value {
+                                        _ if label.data != LabelDataKind::FilePath => {
+                                            format!("{} isn't a FilePath column", label.name)
+                                        }
+                                        Some(Value::FilePath(path))
+                                            if !Path::new(&path).exists() =>
+                                        {
+                                            format!("{path} doesn't exist")
+                                        }
+                                        Some(Value::FilePath(path)) => {
+                                            match open_path(&path, self.open_with.as_deref()) {
+                                                Ok(()) => format!("Opened {path}"),
+                                                Err(err) => format!("Failed to open {path}: {err}"),
+                                            }
+                                        }
+                                        _ => format!("No {} set for this replay", label.name),
+                                    });
+                                }
+                            }
+                            KeyCode::Char('y') => {
+                                let entity = table_state
+                                    .selected()
+                                    .and_then(|row| self.replay_db.nth_entity(row, &self.view));
+
+                                if let (Some(entity), Some(col)) =
+                                    (entity, table_state.selected_column())
+                                {
+                                    let text = self.replay_db.copy_text(entity, col, &self.view);
+                                    self.status = Some(match copy_to_clipboard(&text) {
+                                        Ok(()) if text.is_empty() => {
+                                            "Copied an empty cell".to_string()
+                                        }
+                                        Ok(()) => format!("Copied {text}"),
+                                        Err(err) => format!("Failed to copy: {err}"),
+                                    });
+                                }
+                            }
+                            KeyCode::Char('Y') => {
+                                let row = table_state.selected();
+                                let entity =
+                                    row.and_then(|row| self.replay_db.nth_entity(row, &self.view));
+
+                                if let (Some(row), Some(entity)) = (row, entity) {
+                                    let text = self.replay_db.row_tsv(entity, &self.view);
+                                    self.status = Some(match copy_to_clipboard(&text) {
+                                        Ok(()) => format!("Copied row {} as TSV", row + 1),
+                                        Err(err) => format!("Failed to copy: {err}"),
+                                    });
+                                }
+                            }
+                            KeyCode::Char('R') => {
+                                let label = table_state
+                                    .selected_column()
+                                    .and_then(|col| self.replay_db.labels().get(col));
+
+                                match label {
+                                    Some(label) if label.data != LabelDataKind::Text => {
+                                        self.status =
+                                            Some(format!("{} isn't a Text column", label.name));
+                                    }
+                                    Some(label) if self.replay_db.is_locked(label) => {
+                                        self.status = Some(format!("{} is locked", label.name));
+                                    }
+                                    Some(label) => {
+                                        *replace_editor = Some(ReplaceEditor {
+                                            label: label.clone(),
+                                            find: TextField::default(),
+                                            replace: TextField::default(),
+                                            replace_focused: false,
+                                            whole_value: false,
+                                        });
+                                    }
+                                    None => (),
+                                }
+                            }
+                            KeyCode::Char('s') => {
+                                let label = table_state
+                                    .selected_column()
+                                    .and_then(|col| self.replay_db.labels().get(col))
+                                    .cloned();
+
+                                if let Some(label) = label {
+                                    // Ascending, then descending, then back to the natural order
+                                    self.view.sort = match &self.view.sort[..] {
+                                        [(sorted, SortDirection::Ascending)]
+                                            if *sorted == label =>
+                                        {
+                                            vec![(label, SortDirection::Descending)]
+                                        }
+                                        [(sorted, SortDirection::Descending)]
+                                            if *sorted == label =>
+                                        {
+                                            Vec::new()
+                                        }
+                                        _ => vec![(label, SortDirection::Ascending)],
+                                    };
+                                }
+                            }
+                            KeyCode::Char('/') => {
+                                let query = self.view.search.clone().unwrap_or_default();
+                                *search_input = Some(Input::new(query));
+                            }
+                            KeyCode::Char(':') => *goto_input = Some(Input::default()),
+                            KeyCode::Char('E') => {
+                                let label = table_state
+                                    .selected_column()
+                                    .and_then(|col| self.replay_db.labels().get(col));
+
+                                match label {
+                                    _ if marked.is_empty() => {
+                                        self.status = Some("No rows are marked".to_string());
+                                    }
+                                    Some(label) if self.replay_db.is_locked(label) => {
+                                        self.status = Some(format!("{} is locked", label.name));
+                                    }
+                                    // Ticked to start with since setting a flag is more common
+                                    Some(label) => {
+                                        let text = match label.data {
+                                            LabelDataKind::Unit => UNIT_SET,
+                                            _ => "",
+                                        };
+                                        *batch_editor = Some(LabelInput {
+                                            label: label.clone(),
+                                            data: TextField::new(text.to_string()),
+                                        });
+                                    }
+                                    None => (),
+                                }
+                            }
+                            KeyCode::Char('H') => {
+                                let label = table_state
+                                    .selected_column()
+                                    .and_then(|col| self.replay_db.labels().get(col));
+
+                                match label {
+                                    Some(label)
+                                        if !matches!(
+                                            label.data,
+                                            LabelDataKind::Number | LabelDataKind::Decimal
+                                        ) =>
+                                    {
+                                        self.status = Some(format!(
+                                            "{} isn't a Number or Decimal column",
+                                            label.name
+                                        ));
+                                    }
+                                    Some(label) if self.replay_db.histogram(label).is_empty() => {
+                                        self.status = Some(format!("{} has no values", label.name));
+                                    }
+                                    Some(label) => *histogram = Some(label.clone()),
+                                    None => (),
+                                }
+                            }
+                            KeyCode::Char('V') => {
+                                let found = self.replay_db.problems();
+                                if found.is_empty() {
+                                    self.status =
+                                        Some("Every value follows its column's rules".to_string());
+                                } else {
+                                    *problems = Some(ProblemList {
+                                        problems: found,
+                                        selected: 0,
+                                    });
+                                }
+                            }
+                            KeyCode::Char('S') => {
+                                *schema_editor = Some(SchemaEditor {
+                                    selected: table_state
+                                        .selected_column()
+                                        .unwrap_or(0)
+                                        .min(self.replay_db.labels().len() - 1),
+                                    confirm_delete: false,
+                                    rename: None,
+                                });
+                            }
+                            KeyCode::Char('x') => {
+                                let snapshot = self.replay_db.render_snapshot(&self.view);
+                                self.toasts.push_back(Toast::new(
+                                    match std::fs::write(SNAPSHOT_PATH, snapshot) {
+                                        Ok(()) => format!("Exported table to {SNAPSHOT_PATH}"),
+                                        Err(err) => format!("Failed to export table: {err}"),
+                                    },
+                                ));
+                            }
+                            KeyCode::Char('X') => {
+                                let entity = table_state
+                                    .selected()
+                                    .and_then(|row| self.replay_db.nth_entity(row, &self.view));
+                                if let Some(entity) = entity {
+                                    let path = Path::new(ROW_EXPORT_PATH);
+                                    self.toasts.push_back(Toast::new(
+                                        match self.replay_db.export_row(entity, path) {
+                                            Ok(()) => format!("Exported row to {ROW_EXPORT_PATH}"),
+                                            Err(err) => format!("Failed to export row: {err}"),
+                                        },
+                                    ));
+                                }
+                            }
+                            KeyCode::Char('I') => {
+                                let path = Path::new(ROW_EXPORT_PATH);
+                                let message = match self.replay_db.import_row(path) {
+                                    Ok(entity) => {
+                                        self.history.record(&self.replay_db, entity, None);
+                                        format!("Imported row from {ROW_EXPORT_PATH}")
+                                    }
+                                    Err(err) => format!("Failed to import row: {err}"),
+                                };
+                                self.toasts.push_back(Toast::new(message));
+                            }
+                            KeyCode::Char('a') => {
+                                let entity = table_state
+                                    .selected()
+                                    .and_then(|row| self.replay_db.nth_entity(row, &self.view));
+                                let label = table_state
+                                    .selected_column()
+                                    .and_then(|col| self.replay_db.labels().get(col));
+
+                                if let (Some(entity), Some(label)) = (entity, label) {
+                                    let note = self
+                                        .replay_db
+                                        .note(entity, label)
+                                        .unwrap_or_default()
+                                        .to_string();
+
+                                    *note_editor = Some(NoteEditor {
+                                        entity,
+                                        label: label.clone(),
+                                        input: Input::new(note),
+                                    });
+                                }
+                            }
+                            KeyCode::Char(c @ ('o' | 'O')) => {
+                                let placement = match c {
+                                    'O' => RowPlacement::Above,
+                                    _ => RowPlacement::Below,
+                                };
+                                let anchor = table_state
+                                    .selected()
+                                    .and_then(|row| self.replay_db.nth_entity(row, &self.view));
+                                let selected_entity = match anchor {
+                                    Some(anchor) => self.replay_db.spawn_at(anchor, placement),
+                                    None => self.replay_db.spawn(),
+                                };
+                                self.history.record(&self.replay_db, selected_entity, None);
+                                self.open_editor(selected_entity);
+                            }
+                            _ => (),
+                        }
+                    }
+                }
+                AppState::ReplayInfoEditor(ReplayInfoEditor {
+                    entity,
+                    focus,
+                    labels,
+                    addable,
+                    addable_filter,
+                    suggestions,
+                    errors,
+                    new_label,
+                    text_area,
+                    opened_with: _,
+                }) => {
+                    // Can't be opened while read only, but if it ever is only leaving does
+                    // anything
+                    if self.read_only {
+                        if let Event::Key(key) = event
+                            && self.keys.editor(&key) == Some(EditorAction::Back)
+                        {
+                            self.close_editor(None);
+                        }
+                        continue;
+                    }
+
+                    // `AddableLabel` indexes into the filtered list
+                    let shown = Self::filter_addable(addable, addable_filter.value());
+
+                    // Clicking something focuses it, buttons are then pressed as if by Enter (or
+                    // whatever `Press` is bound to)
+                    let event = match event {
+                        Event::Mouse(MouseEvent {
+                            kind: MouseEventKind::Down(MouseButton::Left),
+                            column,
+                            row,
+                            ..
+                        }) if !matches!(
+                            focus,
+                            ReplayInfoEditorFocus::NewLabel | ReplayInfoEditorFocus::TextArea(_)
+                        ) =>
+                        {
+                            let target = self
+                                .click_areas
+                                .editor
+                                .iter()
+                                .find(|(area, _)| area.contains(Position::new(column, row)));
+                            let Some(&(_, target)) = target else {
+                                continue;
+                            };
+
+                            *focus = target;
+                            if let ReplayInfoEditorFocus::LabelData(_) = target {
+                                continue;
+                            }
+                            Event::Key(self.keys.editor_key(EditorAction::Press).into())
+                        }
+                        Event::Mouse(_) => continue,
+                        event => event,
+                    };
+
+                    if let Event::Key(key) = event
+                        && let ReplayInfoEditorFocus::NewLabel = focus
+                    {
+                        match key.code {
+                            KeyCode::Esc => {
+                                *focus = ReplayInfoEditorFocus::AddableLabel(addable.len())
+                            }
+                            KeyCode::Down => {
+                                new_label.kind = (new_label.kind + 1) % NEW_LABEL_KINDS.len();
+                            }
+                            KeyCode::Up => {
+                                new_label.kind = (new_label.kind + NEW_LABEL_KINDS.len() - 1)
+                                    % NEW_LABEL_KINDS.len();
+                            }
+                            KeyCode::Tab | KeyCode::BackTab => {
+                                let forward = key.code == KeyCode::Tab;
+                                let len = new_label.completions.len();
+                                if len == 0 {
+                                    new_label.completions =
+                                        self.replay_db.names_starting_with(new_label.name.value());
+                                    new_label.completion = if forward {
+                                        0
+                                    } else {
+                                        new_label.completions.len().saturating_sub(1)
+                                    };
+                                } else if forward {
+                                    new_label.completion = (new_label.completion + 1) % len;
+                                } else {
+                                    new_label.completion = (new_label.completion + len - 1) % len;
+                                }
+
+                                if let Some(name) = new_label.completions.get(new_label.completion)
+                                {
+                                    new_label.name = TextField::new(name.clone());
+                                    new_label.error = None;
+                                }
+                            }
+                            KeyCode::Enter => {
+                                let kind = NEW_LABEL_KINDS[new_label.kind].clone();
+                                match self.replay_db.add_column(new_label.name.value(), kind) {
+                                    Ok(label) => {
+                                        labels.push(LabelInput {
+                                            label,
+                                            data: TextField::default(),
+                                        });
+                                        *focus = ReplayInfoEditorFocus::LabelData(labels.len() - 1);
+                                    }
+                                    Err(err) => new_label.error = Some(err),
+                                }
+                            }
+                            _ => {
+                                if let Some(changed) = new_label.name.handle_event(&event) {
+                                    new_label.error = None;
+                                    if changed.value {
+                                        new_label.completions.clear();
+                                    }
+                                }
+                            }
+                        }
+                        continue;
+                    }
+
+                    // Closing the popup only puts the text back in the form, it's saved with
+                    // everything else
+                    if let ReplayInfoEditorFocus::TextArea(n) = *focus {
+                        match event {
+                            Event::Key(key) if key.code == KeyCode::Esc => {
+                                labels[n].data = TextField::new(text_area.value());
+                                *focus = ReplayInfoEditorFocus::LabelData(n);
+                                ReplayInfoEditor::revalidate(
+                                    &self.replay_db,
+                                    *entity,
+                                    labels,
+                                    errors,
+                                    n,
+                                );
+                            }
+                            event => {
+                                text_area.handle_event(&event);
+                            }
+                        }
+                        continue;
+                    }
+
+                    // Typing or pasting into a multi-line field opens its popup with that
+                    // already in it
+                    if let ReplayInfoEditorFocus::LabelData(n) = *focus
+                        && self.replay_db.is_multiline(&labels[n].label)
+                        && !self.replay_db.is_locked(&labels[n].label)
+                        && match &event {
+                            Event::Paste(_) => true,
+                            Event::Key(key) => {
+                                matches!(key.code, KeyCode::Char(_))
+                                    && !key.modifiers.contains(KeyModifiers::CONTROL)
+                                    && self.keys.editor(key).is_none()
+                            }
+                            _ => false,
+                        }
+                    {
+                        *text_area = TextArea::new(labels[n].data.value());
+                        text_area.handle_event(&event);
+                        *focus = ReplayInfoEditorFocus::TextArea(n);
+                        continue;
+                    }
+
+                    if let Event::Key(key) = event
+                        && let ReplayInfoEditorFocus::LabelData(n) = *focus
+                    {
+                        if suggestions.field != n {
+                            *suggestions = Suggestions {
+                                field: n,
+                                ..Suggestions::default()
+                            };
+                        }
+
+                        let list = Self::text_suggestions(&self.replay_db, *entity, &labels[n]);
+                        if !suggestions.dismissed && !list.is_empty() {
+                            suggestions.selected = suggestions.selected.min(list.len() - 1);
+
+                            match key.code {
+                                KeyCode::Up => {
+                                    suggestions.selected =
+                                        (suggestions.selected + list.len() - 1) % list.len();
+                                    continue;
+                                }
+                                KeyCode::Down => {
+                                    suggestions.selected = (suggestions.selected + 1) % list.len();
+                                    continue;
+                                }
+                                KeyCode::Tab => {
+                                    labels[n].data =
+                                        TextField::new(list[suggestions.selected].clone());
+                                    suggestions.dismissed = true;
+                                    ReplayInfoEditor::revalidate(
+                                        &self.replay_db,
+                                        *entity,
+                                        labels,
+                                        errors,
+                                        n,
+                                    );
+                                    continue;
+                                }
+                                KeyCode::Esc => {
+                                    suggestions.dismissed = true;
+                                    continue;
+                                }
+                                _ => (),
+                            }
+                        }
+                    }
+
+                    // Pasted text is checked like typed text
+                    if let Event::Paste(_) = event
+                        && let ReplayInfoEditorFocus::LabelData(n) = *focus
+                    {
+                        if Self::edit_label_input(&self.replay_db, &mut labels[n], &event) {
+                            ReplayInfoEditor::revalidate(
+                                &self.replay_db,
+                                *entity,
+                                labels,
+                                errors,
+                                n,
+                            );
+                        }
+                        continue;
+                    }
+
+                    if let Event::Key(key) = event {
+                        let action = self.keys.editor(&key);
+                        match key.code {
+                            KeyCode::F(1) => self.show_help = true,
+                            _ if action == Some(EditorAction::Back) => {
+                                if let ReplayInfoEditorFocus::AddableLabel(_) = focus {
+                                    *addable_filter = TextField::default();
+                                    *focus = ReplayInfoEditorFocus::LabelAdd;
+                                } else if ReplayInfoEditor::has_changes(
+                                    &self.replay_db,
+                                    *entity,
+                                    labels,
+                                ) {
+                                    self.confirm = Some(Confirm {
+                                        message: "Discard unsaved changes?".to_string(),
+                                        action: PendingAction::DiscardChanges,
+                                    });
+                                } else {
+                                    self.apply_confirmed(PendingAction::DiscardChanges);
+                                }
+                            }
+                            _ if action == Some(EditorAction::PrevField)
+                                || key.code == KeyCode::BackTab =>
+                            {
+                                *focus = focus.step(&self.replay_db, labels, shown.len() + 1, false)
+                            }
+                            _ if action == Some(EditorAction::NextField)
+                                || key.code == KeyCode::Tab =>
+                            {
+                                *focus = focus.step(&self.replay_db, labels, shown.len() + 1, true)
+                            }
+                            _ if action == Some(EditorAction::Press) => match *focus {
+                                ReplayInfoEditorFocus::LabelData(n)
+                                    if self.replay_db.is_multiline(&labels[n].label)
+                                        && !self.replay_db.is_locked(&labels[n].label) =>
+                                {
+                                    *text_area = TextArea::new(labels[n].data.value());
+                                    *focus = ReplayInfoEditorFocus::TextArea(n);
+                                }
+                                ReplayInfoEditorFocus::LabelData(n) => {
+                                    *focus =
+                                        focus.step(&self.replay_db, labels, shown.len() + 1, true);
+                                }
+                                ReplayInfoEditorFocus::LabelRemove(n)
+                                    if self.replay_db.is_locked(&labels[n].label)
+                                        || self.replay_db.is_required(&labels[n].label) => {}
+                                ReplayInfoEditorFocus::LabelRemove(n) => {
+                                    self.confirm = Some(Confirm {
+                                        message: format!("Remove {}?", labels[n].label.name),
+                                        action: PendingAction::RemoveLabel(n),
+                                    });
+                                }
+                                // Whatever was being searched for is likely the new label's name
+                                ReplayInfoEditorFocus::AddableLabel(n) if n == shown.len() => {
+                                    *new_label = NewLabel {
+                                        name: std::mem::take(addable_filter),
+                                        ..NewLabel::default()
+                                    };
+                                    *focus = ReplayInfoEditorFocus::NewLabel;
+                                }
+                                ReplayInfoEditorFocus::AddableLabel(n) => {
+                                    Self::add_label(labels, addable, shown[n]);
+                                    *addable_filter = TextField::default();
+
+                                    *focus = ReplayInfoEditorFocus::LabelData(labels.len() - 1);
+                                }
+                                ReplayInfoEditorFocus::LabelAdd => {
+                                    *focus = ReplayInfoEditorFocus::AddableLabel(0);
+                                }
+                                // Handled before any other keys
+                                ReplayInfoEditorFocus::NewLabel
+                                | ReplayInfoEditorFocus::TextArea(_) => (),
+                                ReplayInfoEditorFocus::SaveChanges => self.save_editor(),
+                            },
+                            _ => match focus {
+                                ReplayInfoEditorFocus::LabelData(n) => {
+                                    if Self::edit_label_input(
+                                        &self.replay_db,
+                                        &mut labels[*n],
+                                        &event,
+                                    ) {
+                                        suggestions.selected = 0;
+                                        suggestions.dismissed = false;
+                                        ReplayInfoEditor::revalidate(
+                                            &self.replay_db,
+                                            *entity,
+                                            labels,
+                                            errors,
+                                            *n,
+                                        );
+                                    }
+                                }
+                                // Typing over the list filters it, starting again from the first match
+                                ReplayInfoEditorFocus::AddableLabel(_) => {
+                                    if addable_filter.handle_event(&event).is_some() {
+                                        *focus = ReplayInfoEditorFocus::AddableLabel(0);
+                                    }
+                                }
+                                ReplayInfoEditorFocus::SaveChanges
+                                | ReplayInfoEditorFocus::LabelRemove(_)
+                                | ReplayInfoEditorFocus::LabelAdd
+                                | ReplayInfoEditorFocus::NewLabel
+                                | ReplayInfoEditorFocus::TextArea(_) => (),
+                            },
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn next_row(&mut self) {
+        let rows = self.replay_db.row_count(&self.view);
+        let AppState::ReplayDBViewer {
+            table_state,
+            scroll_state,
+            ..
+        } = &mut self.state
+        else {
+            return;
+        };
+
+        *scroll_state = scroll_state.content_length(rows);
+        if rows == 0 {
+            table_state.select(None);
+            return;
+        }
+
+        let i = match table_state.selected() {
+            Some(i) => {
+                if i + 1 >= rows {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => 0,
+        };
+        table_state.select(Some(i));
+        *scroll_state = scroll_state.position(i);
+    }
+
+    /// Selects the row numbered `text`, counting from 1 like the status bar. Anything that
+    /// isn't the number of a shown row leaves the selection where it is.
+    fn jump_to_row(&mut self, text: &str) -> Result<(), String> {
+        let rows = self.replay_db.row_count(&self.view);
+        let row = text
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| format!("`{text}` isn't a row number"))?;
+        if !(1..=rows).contains(&row) {
+            return Err(format!("There's no row {row}, only {rows} are shown"));
+        }
+        self.select_row(row - 1);
+        Ok(())
+    }
+
+    /// Selects `row` in the viewer, or the last row if there aren't that many
+    fn select_row(&mut self, row: usize) {
+        let rows = self.replay_db.row_count(&self.view);
+        let AppState::ReplayDBViewer {
+            table_state,
+            scroll_state,
+            ..
+        } = &mut self.state
+        else {
+            return;
+        };
+
+        let selected = (rows > 0).then(|| row.min(rows - 1));
+        table_state.select(selected);
+        *scroll_state = scroll_state
+            .content_length(rows)
+            .position(selected.unwrap_or(0));
+    }
+
+    /// How many rows fit in the viewer, going by the table's size when it was last drawn
+    fn page_rows(&self) -> usize {
+        (self.click_areas.table_rows.height / self.view.row_height()).max(1) as usize
+    }
+
+    /// Moves the selection a page up or down, stopping at the first and last row
+    fn move_page(&mut self, down: bool) {
+        let AppState::ReplayDBViewer { table_state, .. } = &self.state else {
+            return;
+        };
+        let row = table_state.selected().unwrap_or(0);
+        let page = self.page_rows();
+        self.select_row(if down {
+            row.saturating_add(page)
+        } else {
+            row.saturating_sub(page)
+        });
+    }
+
+    /// Opens the editor on `entity`, putting the viewer aside as it is
+    fn open_editor(&mut self, entity: Entity) {
+        let editor = AppState::ReplayInfoEditor(ReplayInfoEditor::new(&self.replay_db, entity));
+        self.suspended_viewer = Some(std::mem::replace(&mut self.state, editor));
+    }
+
+    /// Saves the editor's fields to its entity and goes back to the viewer, or leaves it open
+    /// on the first field that can't be saved
+    fn save_editor(&mut self) {
+        let AppState::ReplayInfoEditor(ReplayInfoEditor {
+            entity,
+            focus,
+            labels,
+            errors,
+            opened_with,
+            ..
+        }) = &mut self.state
+        else {
+            return;
+        };
+        let entity = *entity;
+        let _span = tracing::debug_span!("save_changes", ?entity).entered();
+
+        // Deleted from under the editor, writing to it now would touch a dead entity
+        if !self.replay_db.is_alive(entity) {
+            tracing::warn!("entity was despawned while being edited");
+            self.confirm = Some(Confirm {
+                message: "This row was deleted while it was being edited, save it as a new row?"
+                    .to_string(),
+                action: PendingAction::RespawnEntity,
+            });
+            return;
+        }
+
+        // Nothing is touched unless every field is valid so an entity is never left half saved
+        if let Err(invalid) = ReplayInfoEditor::validate(&self.replay_db, entity, labels) {
+            tracing::debug!(?invalid, "validation failed");
+            self.toasts.push_back(Toast::new(match &*invalid {
+                [(n, error)] => format!("{} {error}", labels[*n].label.name),
+                _ => format!("{} fields need fixing", invalid.len()),
+            }));
+            *focus = ReplayInfoEditorFocus::LabelData(invalid[0].0);
+            *errors = invalid;
+            return;
+        }
+
+        if labels.is_empty() {
+            self.confirm = Some(Confirm {
+                message: "No labels left, delete this row?".to_string(),
+                action: PendingAction::DespawnEntity,
+            });
+            return;
+        }
+
+        // Already validated above
+        let values = ReplayInfoEditor::staged_values(labels, opened_with);
+
+        let before = self.replay_db.row_file(entity);
+        match self.replay_db.set_values(entity, &values) {
+            Ok(0) => (),
+            Ok(_) => {
+                self.replay_db.touch(entity);
+                self.history.record(&self.replay_db, entity, Some(before));
+            }
+            // Left open since nothing was saved
+            Err(err) => {
+                self.toasts.push_back(Toast::new(err));
+                return;
+            }
+        }
+
+        // Wherever the row is now the edit may have re-sorted it
+        let row = self.replay_db.row_of(entity, &self.view);
+        self.close_editor(row);
+    }
+
+    /// Goes back to the viewer the editor was opened from, with its scroll position, column and
+    /// marked rows as they were. `row` is selected, or the row that was selected before if
+    /// `None`, either moved up to the last row if there are fewer rows now.
+    fn close_editor(&mut self, row: Option<usize>) {
+        self.state = match self.suspended_viewer.take() {
+            Some(viewer) => viewer,
+            None => self.viewer_state(0),
+        };
+        let AppState::ReplayDBViewer {
+            table_state,
+            marked,
+            ..
+        } = &mut self.state
+        else {
+            return;
+        };
+        marked.retain(|&entity| self.replay_db.is_alive(entity));
+        let row = row.or(table_state.selected()).unwrap_or(0);
+        self.select_row(row);
+    }
+
+    /// The viewer with `row` selected, moved up to the last row if there are fewer rows now
+    fn viewer_state(&self, row: usize) -> AppState {
+        let rows = self.replay_db.row_count(&self.view);
+        let selected = (rows > 0).then(|| row.min(rows - 1));
+        AppState::ReplayDBViewer {
+            table_state: TableState::default().with_selected(selected),
+            scroll_state: ScrollbarState::new(rows).position(selected.unwrap_or(0)),
+            note_editor: None,
+            cell_editor: None,
+            replace_editor: None,
+            schema_editor: None,
+            search_input: None,
+            goto_input: None,
+            batch_editor: None,
+            histogram: None,
+            problems: None,
+            marked: HashSet::new(),
+        }
+    }
+
+    fn prev_row(&mut self) {
+        let rows = self.replay_db.row_count(&self.view);
+        let AppState::ReplayDBViewer {
+            table_state,
+            scroll_state,
+            ..
+        } = &mut self.state
+        else {
+            return;
+        };
+
+        *scroll_state = scroll_state.content_length(rows);
+        if rows == 0 {
+            table_state.select(None);
+            return;
+        }
+
+        let i = match table_state.selected() {
+            Some(i) => {
+                if i == 0 || i >= rows {
+                    rows - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        table_state.select(Some(i));
+        *scroll_state = scroll_state.position(i);
+    }
+
+    /// Handles a key pressed while [`App::confirm`] is shown, returning whether to quit.
+    /// Quitting with unsaved changes takes `s` to save them first, `d` to throw them away or
+    /// `c` to stay, every other prompt takes `y` or `n`. Saving is refused while read only.
+    fn answer_confirm(&mut self, key: KeyCode) -> bool {
+        let Some(confirm) = &self.confirm else {
+            return false;
+        };
+        match (&confirm.action, key) {
+            (PendingAction::Quit, KeyCode::Char('s')) if self.read_only => {
+                self.confirm = None;
+                self.toasts
+                    .push_back(Toast::new("Read only, press 'W' to allow changes"));
+            }
+            (PendingAction::Quit, KeyCode::Char('s')) => {
+                self.confirm = None;
+                match self.save_on_exit() {
+                    Ok(()) => return true,
+                    Err(err) => self
+                        .toasts
+                        .push_back(Toast::new(format!("Saving failed: {err}"))),
+                }
+            }
+            (PendingAction::Quit, KeyCode::Char('d')) => {
+                self.discard_on_exit = true;
+                return true;
+            }
+            (PendingAction::Quit, KeyCode::Char('c') | KeyCode::Esc) => self.confirm = None,
+            (PendingAction::Quit, _) => (),
+            (_, KeyCode::Char('y')) => {
+                let confirm = self.confirm.take().unwrap();
+                self.apply_confirmed(confirm.action);
+            }
+            (_, KeyCode::Char('n') | KeyCode::Esc) => {
+                let confirm = self.confirm.take().unwrap();
+                // There's nothing left to edit either way
+                if let PendingAction::RespawnEntity = confirm.action {
+                    self.toasts
+                        .push_back(Toast::new("Row was deleted, edits discarded"));
+                    self.close_editor(None);
+                }
+            }
+            _ => (),
+        }
+        false
+    }
+
+    /// Performs an action once the user has answered `y` to its [`Confirm`] prompt
+    fn apply_confirmed(&mut self, action: PendingAction) {
+        tracing::debug!(?action, "confirmed");
+
+        match (action, &mut self.state) {
+            (
+                PendingAction::DespawnMarked,
+                AppState::ReplayDBViewer {
+                    table_state,
+                    marked,
+                    ..
+                },
+            ) => {
+                for entity in self.replay_db.ordered_entities() {
+                    if marked.contains(&entity) {
+                        self.history.record_despawn(&self.replay_db, entity);
+                        self.replay_db.despawn(entity);
+                    }
+                }
+                marked.clear();
+
+                let rows = self.replay_db.row_count(&self.view);
+                let row = table_state.selected().unwrap_or(0);
+                table_state.select((rows > 0).then(|| row.min(rows - 1)));
+            }
+            (
+                PendingAction::DespawnDuplicates(duplicates),
+                AppState::ReplayDBViewer {
+                    table_state,
+                    marked,
+                    ..
+                },
+            ) => {
+                for &entity in &duplicates {
+                    self.history.record_despawn(&self.replay_db, entity);
+                    self.replay_db.despawn(entity);
+                    marked.remove(&entity);
+                }
+                self.status = Some(format!("Removed {} duplicate rows", duplicates.len()));
+
+                let rows = self.replay_db.row_count(&self.view);
+                let row = table_state.selected().unwrap_or(0);
+                table_state.select((rows > 0).then(|| row.min(rows - 1)));
+            }
+            (PendingAction::RemoveLabel(n), AppState::ReplayInfoEditor(editor)) => {
+                let len = (editor.labels.len(), editor.addable.len() + 1);
+                editor.focus = editor.focus.next_focus(len.0, len.1, true);
+                Self::remove_label(&self.replay_db, &mut editor.labels, &mut editor.addable, n);
+                // Indices after `n` have shifted
+                editor.errors.clear();
+            }
+            (PendingAction::DespawnEntity, AppState::ReplayInfoEditor(editor)) => {
+                let entity = editor.entity;
+                let row = self.replay_db.row_of(entity, &self.view);
+                self.history.record_despawn(&self.replay_db, entity);
+                self.replay_db.despawn(entity);
+                self.close_editor(row);
+            }
+            (PendingAction::RespawnEntity, AppState::ReplayInfoEditor(editor)) => {
+                editor.entity = self.replay_db.spawn();
+                // Every field is new to the respawned entity
+                editor.opened_with.clear();
+                self.history.record(&self.replay_db, editor.entity, None);
+                self.save_editor();
+            }
+            (PendingAction::DiscardChanges, AppState::ReplayInfoEditor(editor)) => {
+                let row = self.replay_db.row_of(editor.entity, &self.view);
+                self.close_editor(row);
+            }
+            // Prompts are only raised from the state their action applies to
+            _ => (),
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [state_area, bar_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+        self.draw_state(frame, state_area);
+        self.draw_toasts(frame);
+        frame.render_widget(
+            Line::raw(self.status_bar_text())
+                .fg(self.theme.text)
+                .bg(self.theme.status_bg),
+            bar_area,
+        );
+
+        if let Some(confirm) = &self.confirm {
+            let area = centered_rect(frame.area(), 50, 3);
+            let title = match confirm.action {
+                PendingAction::Quit => " (s)ave & quit, (d)iscard & quit, (c)ancel ",
+                _ => " Are you sure? (y/n) ",
+            };
+            frame.render_widget(Clear, area);
+            frame.render_widget(
+                Paragraph::new(Line::raw(&confirm.message).bold())
+                    .block(Block::bordered().title(title))
+                    .bg(self.theme.popup_bg),
+                area,
+            );
+        }
+
+        if let Some(import) = &self.import
+            && !import.new_columns.is_empty()
+        {
+            self.draw_new_columns(frame, import);
+        }
+
+        if self.show_help {
+            self.draw_help(frame);
+        }
+    }
+
+    /// Lists the columns `import` will create with the kind each will get, and how many of the
+    /// values sampled from the file fit that kind
+    fn draw_new_columns(&self, frame: &mut Frame, import: &CsvImport) {
+        let lines = import
+            .new_columns
+            .iter()
+            .enumerate()
+            .map(|(n, column)| {
+                let example = match column.samples.first() {
+                    Some(value) => format!(", e.g. `{value}`"),
+                    None => String::new(),
+                };
+                let line = Line::raw(format!(
+                    "{}: ◂ {:?} ▸ {}/{} sampled values fit{example}",
+                    column.name,
+                    NEW_LABEL_KINDS[column.kind],
+                    column.fitting_samples(),
+                    column.samples.len()
+                ));
+                if n == import.selected {
+                    line.reversed()
+                } else {
+                    line
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let area = centered_rect(frame.area(), 70, lines.len() as u16 + 2);
+        let block = Block::bordered()
+            .title(format!(" New columns in {} ", import.path.display()))
+            .title_bottom(
+                " ↑/↓ to pick, ←/→ to change kind, Enter to create & import, Esc to cancel ",
+            )
+            .bg(self.theme.popup_bg);
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    /// Stacks the toasts in the top right corner, newest first
+    fn draw_toasts(&self, frame: &mut Frame) {
+        let area = frame.area();
+        let width = (area.width / 2).max(20) as usize;
+        let mut y = area.y;
+        for toast in self.toasts.iter().rev() {
+            let text = wrap_text(&toast.message, width.saturating_sub(2), 3);
+            let lines = text
+                .lines()
+                .map(|line| Line::raw(format!(" {line} ")))
+                .collect::<Vec<_>>();
+            let toast_width = lines.iter().map(Line::width).max().unwrap_or(0) as u16;
+            let toast_area = Rect {
+                x: area.right().saturating_sub(toast_width),
+                y,
+                width: toast_width,
+                height: lines.len() as u16,
+            }
+            .intersection(area);
+            y += lines.len() as u16;
+
+            let mut style = Style::new().fg(self.theme.text).bg(self.theme.popup_bg);
+            if toast.shown_at.elapsed() + TOAST_FADE >= TOAST_DURATION {
+                style = style.dim();
+            }
+            frame.render_widget(Clear, toast_area);
+            frame.render_widget(Paragraph::new(lines).style(style), toast_area);
+        }
+    }
+
+    fn draw_help(&self, frame: &mut Frame) {
+        let key_lines = |keys: &[(&'static str, &'static str)]| {
+            keys.iter()
+                .map(|(key, action)| Line::from_iter([format!("{key:>12}  "), action.to_string()]))
+                .collect::<Vec<_>>()
+        };
+
+        // The current mode goes first so its keys are still visible when the terminal is too
+        // short to fit everything
+        let mut sections = [("Viewer", VIEWER_KEYS), ("Editor", EDITOR_KEYS)];
+        if let AppState::ReplayInfoEditor(_) = self.state {
+            sections.reverse();
+        }
+
+        let read_only = if self.read_only { " (read only)" } else { "" };
+        let mut lines = vec![
+            Line::raw(format!("ddreplayer v{}", env!("CARGO_PKG_VERSION"))).bold(),
+            Line::raw(format!("{}{read_only}", self.db_path.display())),
+        ];
+        for (n, (title, keys)) in sections.into_iter().enumerate() {
+            let title = if n == 0 {
+                format!("{title} (current)")
+            } else {
+                title.to_string()
+            };
+            lines.push(Line::raw(""));
+            lines.push(Line::raw(title).bold());
+            lines.extend(key_lines(keys));
+        }
+
+        let area = centered_rect(frame.area(), 60, lines.len() as u16 + 2);
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(
+                    Block::bordered()
+                        .title(" Help ")
+                        .title_bottom(" Press any key to close "),
+                )
+                .bg(self.theme.popup_bg),
+            area,
+        );
+    }
+
+    /// Whether `key` changes the database from the viewer, which [`App::read_only`] refuses.
+    /// `action` is what `key` is bound to, which takes precedence over the fixed keys.
+    fn edits_database(key: &KeyEvent, action: Option<ViewerAction>) -> bool {
+        if let Some(action) = action {
+            return matches!(action, ViewerAction::EditEntity | ViewerAction::NewEntity);
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            // Moving a column, redoing and editing the file
+            KeyCode::Left | KeyCode::Right | KeyCode::Char('r' | 'e') => ctrl,
+            KeyCode::Enter | KeyCode::Delete => true,
+            KeyCode::Char(c) => "u aoOEdDKSRLI".contains(c),
+            _ => false,
+        }
+    }
+
+    /// Where the user is, shown at the bottom of every mode
+    fn status_bar_text(&self) -> String {
+        let entities = self.replay_db.ordered_entities().len();
+        let text = match &self.state {
+            AppState::ReplayDBViewer { table_state, .. } => {
+                let rows = self.replay_db.row_count(&self.view);
+                let position = match table_state.selected() {
+                    Some(row) if rows > 0 => format!("row {} of {rows}", row + 1),
+                    _ => "no rows".to_string(),
+                };
+                format!(" Viewer │ {entities} entities │ {position}")
+            }
+            AppState::ReplayInfoEditor(editor) => {
+                let changes = if ReplayInfoEditor::has_changes(
+                    &self.replay_db,
+                    editor.entity,
+                    &editor.labels,
+                ) {
+                    "unsaved changes"
+                } else {
+                    "no changes"
+                };
+                format!(
+                    " Editor │ {entities} entities │ entity {} │ {changes}",
+                    entity_id(editor.entity)
+                )
+            }
+        };
+        if self.read_only {
+            format!("{text} │ [READ ONLY]")
+        } else {
+            text
+        }
+    }
+
+    /// Draws the current mode into `area`, popups are centered on the whole frame
+    fn draw_state(&mut self, frame: &mut Frame, area: Rect) {
+        let theme = &self.theme;
+        match &mut self.state {
+            AppState::ReplayDBViewer {
+                table_state,
+                scroll_state,
+                note_editor,
+                cell_editor,
+                replace_editor,
+                schema_editor,
+                search_input,
+                goto_input,
+                batch_editor,
+                histogram,
+                problems,
+                marked,
+            } => {
+                let header_style = Style::default().fg(theme.header_fg).bg(theme.header_bg);
+                let selected_row_style = Style::default()
+                    .add_modifier(Modifier::REVERSED)
+                    .fg(theme.selected_row);
+                let selected_col_style = Style::default().fg(theme.selected_column);
+                let selected_cell_style = Style::default()
+                    .add_modifier(Modifier::REVERSED)
+                    .fg(theme.selected_cell);
+
+                // The id column isn't one of the database's so it's only added here
+                let id_column = self.view.show_ids.then_some(ID_HEADER);
+                let id_width = id_column.map(|header| {
+                    self.replay_db
+                        .visible_entities(&self.view)
+                        .into_iter()
+                        .map(|entity| entity_id(entity).width())
+                        .fold(header.width(), usize::max)
+                        .min(MAX_COLUMN_WIDTH) as u16
+                });
+                let column_widths = self.replay_db.column_widths(&self.view);
+                let bar = " █ ";
+
+                // Hidden columns keep their index into `header_names` etc. but aren't drawn.
+                // Pinned columns always are, the rest are scrolled just far enough to keep the
+                // selected column on screen. Each column is followed by a space, and the
+                // scrollbar takes the last column.
+                let (pinned, scrolling): (Vec<_>, Vec<_>) = self
+                    .replay_db
+                    .shown_columns(&self.view)
+                    .into_iter()
+                    .partition(|&col| {
+                        self.replay_db
+                            .labels()
+                            .get(col)
+                            .is_some_and(|label| self.replay_db.is_pinned(label))
+                    });
+                let drawn_width = |cols: &[usize]| {
+                    cols.iter()
+                        .map(|&col| column_widths[col] as usize + 1)
+                        .sum::<usize>()
+                };
+                let available = (area.width as usize).saturating_sub(
+                    bar.width() + id_width.map_or(0, |w| w as usize + 1) + drawn_width(&pinned) + 1,
+                );
+                let selected = table_state
+                    .selected_column()
+                    .and_then(|col| scrolling.iter().position(|&c| c == col));
+                let mut first = 0;
+                if let Some(selected) = selected {
+                    while first < selected && drawn_width(&scrolling[first..=selected]) > available
+                    {
+                        first += 1;
+                    }
+                }
+                let shown = pinned
+                    .into_iter()
+                    .chain(scrolling.into_iter().skip(first))
+                    .collect::<Vec<_>>();
+
+                let header_names = self.replay_db.header_names(&self.view);
+                let header = id_column
+                    .map(widgets::Cell::from)
+                    .into_iter()
+                    .chain(
+                        shown
+                            .iter()
+                            .map(|&col| widgets::Cell::from(header_names[col].clone())),
+                    )
+                    .collect::<Row>()
+                    .style(header_style)
+                    .height(1);
+
+                // Matches are only highlighted while the query is being typed
+                let highlight = search_input
+                    .is_some()
+                    .then(|| self.view.search.clone())
+                    .flatten();
+                let search_regex = self.view.search_regex();
+                let rows = self
+                    .replay_db
+                    .visible_entities(&self.view)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, e)| {
+                        let color = match i % 2 {
+                            _ if marked.contains(&e) => theme.marked_row_bg,
+                            0 => theme.row_bg,
+                            _ => theme.alt_row_bg,
+                        };
+
+                        let row_data = self.replay_db.row_cells(e, &self.view);
+                        let id = id_column.map(|_| {
+                            let padding = "\n".repeat(self.view.cell_padding() as usize);
+                            widgets::Cell::from(Text::from(format!("{padding}{}", entity_id(e))))
+                                .fg(theme.dim)
+                        });
+
+                        id.into_iter()
+                            .chain(shown.iter().map(|&col| (col, row_data[col].clone())).map(
+                                |(col, content)| {
+                                    let has_note =
+                                        self.replay_db.labels().get(col).is_some_and(|label| {
+                                            self.replay_db.note(e, label).is_some()
+                                        });
+                                    let marker = if has_note { NOTE_MARKER } else { "" };
+
+                                    // The note marker goes in the top padding when there is some
+                                    let padding = self.view.cell_padding() as usize;
+                                    let is_text = self
+                                        .replay_db
+                                        .labels()
+                                        .get(col)
+                                        .is_some_and(|label| label.data == LabelDataKind::Text);
+                                    let content = if is_text {
+                                        let lines = self.view.row_height() as usize - padding * 2;
+                                        let width = column_widths[col] as usize;
+                                        if padding == 0 {
+                                            wrap_text(
+                                                &content,
+                                                width.saturating_sub(marker.width()),
+                                                lines,
+                                            )
+                                        } else {
+                                            wrap_text(&content, width, lines)
+                                        }
+                                    } else {
+                                        content
+                                    };
+                                    let text = if padding == 0 {
+                                        format!("{content}{marker}")
+                                    } else {
+                                        format!("{marker}{}{content}", "\n".repeat(padding))
+                                    };
+                                    let cell =
+                                        widgets::Cell::from(match (&highlight, &search_regex) {
+                                            (Some(_), Some(Ok(regex))) => Text::from_iter(
+                                                text.lines()
+                                                    .map(|line| highlight_regex(line, regex)),
+                                            ),
+                                            // An invalid pattern doesn't match anything yet
+                                            (Some(_), Some(Err(_))) | (None, _) => Text::from(text),
+                                            (Some(query), None) => Text::from_iter(
+                                                text.lines()
+                                                    .map(|line| highlight_matches(line, query)),
+                                            ),
+                                        });
+
+                                    // Set flags stand out from the rest of the row
+                                    let unit_color = self
+                                        .replay_db
+                                        .labels()
+                                        .get(col)
+                                        .filter(|label| {
+                                            label.data == LabelDataKind::Unit
+                                                && self.replay_db.get_value(label, e).is_some()
+                                        })
+                                        .map(|label| self.replay_db.config(label).unit_color());
+                                    match unit_color {
+                                        Some(color) => cell.fg(color),
+                                        None => cell,
+                                    }
+                                },
+                            ))
+                            .collect::<Row>()
+                            .style(Style::new().fg(theme.text).bg(color))
+                            .height(self.view.row_height())
+                    });
+
+                // Only mark the lines between the top and bottom padding
+                let (height, padding) = (self.view.row_height(), self.view.cell_padding());
+                let highlight_symbol = (0..height)
+                    .map(|line| {
+                        if line >= padding && line < height - padding {
+                            Line::raw(bar)
+                        } else {
+                            Line::raw("")
+                        }
+                    })
+                    .collect::<Text>();
+                let shown_widths = id_width
+                    .into_iter()
+                    .chain(shown.iter().map(|&col| column_widths[col]))
+                    .collect::<Vec<_>>();
+                let widths = shown_widths.iter().copied().map(Constraint::Min);
+                let footer_cells = self.replay_db.footer_cells(&self.view);
+                let footer = id_column
+                    .map(|_| widgets::Cell::from(""))
+                    .into_iter()
+                    .chain(
+                        shown
+                            .iter()
+                            .map(|&col| widgets::Cell::from(Text::from(footer_cells[col].clone()))),
+                    )
+                    .collect::<Row>()
+                    .style(header_style)
+                    .height(FOOTER_HEIGHT);
+
+                let table = Table::new(rows, widths)
+                    .header(header)
+                    .footer(footer)
+                    .row_highlight_style(selected_row_style)
+                    .column_highlight_style(selected_col_style)
+                    .cell_highlight_style(selected_cell_style)
+                    .highlight_symbol(highlight_symbol)
+                    .bg(theme.row_bg)
+                    .highlight_spacing(HighlightSpacing::Always);
+
+                // Action feedback takes priority over the filter summaries
+                let rows = self.replay_db.row_count(&self.view);
+                let search_prefix = if self.view.regex_search {
+                    "regex/"
+                } else {
+                    "/"
+                };
+                let status = match (search_input, goto_input) {
+                    (_, Some(input)) => Some(format!(":{}", input.value())),
+                    (Some(input), None) => Some(match &search_regex {
+                        // Only the last line of the error says what's wrong, the rest points at
+                        // where in the pattern
+                        Some(Err(err)) => format!(
+                            "{search_prefix}{}  {}",
+                            input.value(),
+                            err.to_string().lines().last().unwrap_or_default()
+                        ),
+                        _ => format!("{search_prefix}{}", input.value()),
+                    }),
+                    (None, None) => self.status.clone(),
+                }
+                .or_else(|| {
+                    Some(match self.view.missing.as_ref()? {
+                        MissingFilter::Any => format!("{rows} incomplete rows (m to show all)"),
+                        MissingFilter::Column(label) => {
+                            format!("{rows} rows missing {} (M to show all)", label.name)
+                        }
+                    })
+                })
+                .or_else(|| {
+                    let query = self.view.search.as_ref()?;
+                    Some(match &search_regex {
+                        Some(Err(_)) => {
+                            format!("\"{query}\" isn't a valid regex (/ then Esc to show all)")
+                        }
+                        Some(Ok(_)) => {
+                            format!("{rows} rows matching /{query}/ (/ then Esc to show all)")
+                        }
+                        None => {
+                            format!("{rows} rows matching \"{query}\" (/ then Esc to show all)")
+                        }
+                    })
+                });
+
+                let [table_area, status_area] = Layout::vertical([
+                    Constraint::Fill(1),
+                    Constraint::Length(status.is_some() as u16),
+                ])
+                .areas(area);
+
+                if self.replay_db.ordered_entities().is_empty() {
+                    // A bare header gives no hint of what to do next
+                    let key = self.keys.viewer_key(ViewerAction::NewEntity);
+                    let message = format!("No replays yet — press '{key}' to add one");
+                    let area = centered_rect(table_area, message.width() as u16, 1);
+                    frame.render_widget(Line::raw(message).fg(theme.dim), area);
+                } else {
+                    // The table only knows about the columns it's given
+                    let col = table_state
+                        .selected_column()
+                        .and_then(|col| shown.iter().position(|&c| c == col))
+                        .map(|col| col + id_column.is_some() as usize);
+                    let mut shown_state = table_state.clone().with_selected_column(col);
+                    frame.render_stateful_widget(table, table_area, &mut shown_state);
+                    *table_state = shown_state.with_selected_column(table_state.selected_column());
+                }
+
+                // Rows can be added/removed/filtered by any action so resync every frame
+                *scroll_state = scroll_state
+                    .content_length(rows)
+                    .position(table_state.selected().unwrap_or(0));
+                let [_, rows_area, _] = Layout::vertical([
+                    Constraint::Length(1),
+                    Constraint::Fill(1),
+                    Constraint::Length(FOOTER_HEIGHT),
+                ])
+                .areas(table_area);
+                self.click_areas.table_rows = rows_area;
+                frame.render_stateful_widget(
+                    Scrollbar::new(ScrollbarOrientation::VerticalRight),
+                    rows_area,
+                    scroll_state,
+                );
+
+                if let Some(editor) = cell_editor
+                    && let (Some(row), Some(col)) =
+                        (table_state.selected(), table_state.selected_column())
+                {
+                    // Split the same way the table splits its columns, after the highlight
+                    // symbol and with a space between each
+                    let [_, columns_area] = Layout::horizontal([
+                        Constraint::Length(bar.width() as u16),
+                        Constraint::Fill(1),
+                    ])
+                    .areas(rows_area);
+                    let columns =
+                        Layout::horizontal(shown_widths.iter().copied().map(Constraint::Min))
+                            .flex(Flex::Start)
+                            .spacing(1)
+                            .split(columns_area);
+                    let col = shown.iter().position(|&c| c == col).unwrap_or(0)
+                        + id_column.is_some() as usize;
+                    let y = rows_area.y
+                        + (row - table_state.offset()) as u16 * self.view.row_height()
+                        + self.view.cell_padding();
+                    let area = Rect {
+                        y,
+                        height: 1,
+                        ..columns[col]
+                    }
+                    .intersection(rows_area);
+
+                    frame.render_widget(Clear, area);
+                    frame.render_widget(editor.input.data.line().bg(theme.input_bg), area);
+                    frame.set_cursor_position(area.offset(layout::Offset {
+                        x: editor.input.data.visual_cursor() as i32,
+                        y: 0,
+                    }));
+                }
+                if let Some(status) = &status {
+                    frame.render_widget(
+                        Line::raw(status).fg(theme.header_fg).bg(theme.header_bg),
+                        status_area,
+                    );
+                }
+                if let Some(input) = search_input {
+                    frame.set_cursor_position(status_area.offset(layout::Offset {
+                        x: (search_prefix.len() + input.visual_cursor()) as i32,
+                        y: 0,
+                    }));
+                }
+                if let Some(input) = goto_input {
+                    frame.set_cursor_position(status_area.offset(layout::Offset {
+                        x: 1 + input.visual_cursor() as i32,
+                        y: 0,
+                    }));
+                }
+
+                if let Some(editor) = note_editor {
+                    let area = centered_rect(frame.area(), 60, 3);
+                    let block = Block::bordered()
+                        .title(format!(" Note on {} ", editor.label.name))
+                        .title_bottom(" Enter to save, empty to remove, Esc to cancel ")
+                        .bg(theme.popup_bg);
+                    let input_area = block.inner(area);
+
+                    frame.render_widget(Clear, area);
+                    frame.render_widget(Paragraph::new(editor.input.value()).block(block), area);
+                    frame.set_cursor_position(input_area.offset(layout::Offset {
+                        x: editor.input.visual_cursor() as i32,
+                        y: 0,
+                    }));
+                }
+
+                if let Some(label) = histogram {
+                    let area = centered_rect(frame.area(), 70, 20);
+                    let block = Block::bordered()
+                        .title(format!(" {} ", label.name))
+                        .title_bottom(" Rows per range of values, Esc to close ")
+                        .bg(theme.popup_bg);
+                    let buckets = self.replay_db.histogram(label);
+                    // Spread the bars over the whole popup with a space between each
+                    let count = (buckets.len() as u16).max(1);
+                    let bar_width =
+                        (block.inner(area).width.saturating_sub(count - 1) / count).max(1);
+                    let bars = buckets
+                        .into_iter()
+                        .map(|(start, rows)| {
+                            Bar::default()
+                                .value(rows)
+                                .label(Line::raw(start))
+                                .style(Style::default().fg(theme.selected_column))
+                                .value_style(
+                                    Style::default()
+                                        .fg(theme.popup_bg)
+                                        .bg(theme.selected_column),
+                                )
+                        })
+                        .collect::<Vec<_>>();
+
+                    frame.render_widget(Clear, area);
+                    frame.render_widget(
+                        BarChart::default()
+                            .block(block)
+                            .bar_width(bar_width)
+                            .bar_gap(1)
+                            .label_style(Style::default().fg(theme.text))
+                            .data(BarGroup::default().bars(&bars)),
+                        area,
+                    );
+                }
+
+                if let Some(list) = problems {
+                    // Scrolled just far enough to keep the selected problem in view
+                    let height = list.problems.len().min(PROBLEM_LIST_HEIGHT);
+                    let first = (list.selected + 1).saturating_sub(height);
+                    let lines = list
+                        .problems
+                        .iter()
+                        .enumerate()
+                        .skip(first)
+                        .take(height)
+                        .map(|(n, (entity, label, err))| {
+                            let row = match self.replay_db.row_of(*entity, &self.view) {
+                                Some(row) => format!("Row {}", row + 1),
+                                None => "Filtered out row".to_string(),
+                            };
+                            let line = Line::raw(format!("{row}, {}: {err}", label.name));
+                            if n == list.selected {
+                                line.reversed()
+                            } else {
+                                line
+                            }
+                        })
+                        .collect::<Vec<_>>();
+
+                    let area = centered_rect(frame.area(), 70, height as u16 + 2);
+                    let block = Block::bordered()
+                        .title(format!(" {} problems ", list.problems.len()))
+                        .title_bottom(" Enter to go to the cell, Esc to close ")
+                        .bg(theme.popup_bg);
+                    frame.render_widget(Clear, area);
+                    frame.render_widget(Paragraph::new(lines).block(block), area);
+                }
+
+                if let Some(input) = batch_editor {
+                    let area = centered_rect(frame.area(), 60, 3);
+                    let hint = match input.label.data {
+                        LabelDataKind::Unit | LabelDataKind::Bool => {
+                            " Space to toggle, Enter to apply, Esc to cancel "
+                        }
+                        _ => " Enter to apply, Esc to cancel ",
+                    };
+                    let block = Block::bordered()
+                        .title(format!(
+                            " Set {} on {} marked rows ",
+                            input.label.name,
+                            marked.len()
+                        ))
+                        .title_bottom(hint)
+                        .bg(theme.popup_bg);
+                    let input_area = block.inner(area);
+
+                    frame.render_widget(Clear, area);
+                    let (line, cursor) = if input.label.data == LabelDataKind::Unit {
+                        let ticked = !input.data.value().is_empty();
+                        (Line::raw(if ticked { "[x]" } else { "[ ]" }), 1)
+                    } else {
+                        (input.data.line(), input.data.visual_cursor())
+                    };
+                    frame.render_widget(Paragraph::new(line).block(block), area);
+                    frame.set_cursor_position(input_area.offset(layout::Offset {
+                        x: cursor as i32,
+                        y: 0,
+                    }));
+                }
+
+                if let Some(editor) = replace_editor {
+                    let preview = self.replay_db.replace_preview(
+                        &editor.label,
+                        editor.find.value(),
+                        editor.replace.value(),
+                        editor.whole_value,
+                    );
+
+                    let mut find = editor.find.line();
+                    find.spans.insert(0, Span::raw("   Find: ").bold());
+                    let mut replace = editor.replace.line();
+                    replace.spans.insert(0, Span::raw("Replace: ").bold());
+                    let mode = if editor.whole_value {
+                        "whole values"
+                    } else {
+                        "anywhere in values"
+                    };
+                    let mut lines = vec![
+                        find,
+                        replace,
+                        Line::raw(format!("Matching {mode} (Ctrl+W to change)")).dim(),
+                        Line::raw(format!("{} rows will change", preview.len())),
+                    ];
+                    lines.extend(
+                        preview
+                            .iter()
+                            .take(MAX_REPLACE_PREVIEW)
+                            .map(|(_, old, new)| Line::raw(format!("  {old} → {new}"))),
+                    );
+                    if preview.len() > MAX_REPLACE_PREVIEW {
+                        lines.push(Line::raw("  …"));
+                    }
+
+                    let area = centered_rect(frame.area(), 60, lines.len() as u16 + 2);
+                    let block = Block::bordered()
+                        .title(format!(" Replace in {} ", editor.label.name))
+                        .title_bottom(" Enter to apply, Tab to switch field, Esc to cancel ")
+                        .bg(theme.popup_bg);
+                    let input_area = block.inner(area);
+
+                    let (row, cursor) = if editor.replace_focused {
+                        (1, editor.replace.visual_cursor())
+                    } else {
+                        (0, editor.find.visual_cursor())
+                    };
+                    frame.render_widget(Clear, area);
+                    frame.render_widget(Paragraph::new(lines).block(block), area);
+                    frame.set_cursor_position(input_area.offset(layout::Offset {
+                        x: "Replace: ".len() as i32 + cursor as i32,
+                        y: row,
+                    }));
+                }
+
+                if let Some(editor) = schema_editor {
+                    let mut lines = self
+                        .replay_db
+                        .labels()
+                        .iter()
+                        .enumerate()
+                        .map(|(n, label)| {
+                            let lock = if self.replay_db.is_locked(label) {
+                                " (locked)"
+                            } else {
+                                ""
+                            };
+                            let hidden = if self.replay_db.is_visible(label) {
+                                ""
+                            } else {
+                                " (hidden)"
+                            };
+                            let pinned = if self.replay_db.is_pinned(label) {
+                                " (pinned)"
+                            } else {
+                                ""
+                            };
+                            let count = self.replay_db.label_count(label);
+                            let line = Line::raw(format!(
+                                "{} [{:?}] in {count} rows{lock}{hidden}{pinned}",
+                                label.name, label.data
+                            ));
+                            if n == editor.selected {
+                                line.reversed()
+                            } else {
+                                line
+                            }
+                        })
+                        .collect::<Vec<_>>();
+                    if let Some(name) = &editor.rename {
+                        let mut line = name.line();
+                        line.spans.insert(0, Span::raw("New name: ").bold());
+                        lines.push(Line::raw(""));
+                        lines.push(line);
+                    }
+                    if editor.confirm_delete {
+                        let label = &self.replay_db.labels()[editor.selected];
+                        let (name, count) = (&label.name, self.replay_db.label_count(label));
+                        lines.push(Line::raw(""));
+                        lines.push(
+                            Line::raw(format!("Delete {name} and its {count} values? (y/n)"))
+                                .fg(theme.error)
+                                .bold(),
+                        );
+                    }
+
+                    let area = centered_rect(frame.area(), 64, lines.len() as u16 + 2);
+                    let block = Block::bordered()
+                        .title(" Columns ")
+                        .title_bottom(" r rename, v show/hide, p pin, s sort, d delete, Esc close ")
+                        .bg(theme.popup_bg);
+
+                    frame.render_widget(Clear, area);
+                    frame.render_widget(Paragraph::new(lines).block(block), area);
+                }
+            }
+
+            AppState::ReplayInfoEditor(ReplayInfoEditor {
+                entity,
+                focus,
+                labels,
+                addable,
+                addable_filter,
+                suggestions,
+                errors,
+                new_label,
+                text_area,
+                opened_with: _,
+            }) => {
+                let shown = Self::filter_addable(addable, addable_filter.value());
+                // The filter only takes up a line while it's in use
+                let filtering = matches!(focus, ReplayInfoEditorFocus::AddableLabel(_))
+                    || !addable_filter.value().is_empty();
+
+                // The addable list is only as wide as its longest entry so the form gets the
+                // rest, but never more than half so narrow terminals still fit both
+                let list_width = addable
+                    .iter()
+                    .map(|label| label.name.width())
+                    .chain([NEW_LABEL_ITEM.width()])
+                    .chain(filtering.then(|| addable_filter.value().width() + 2))
+                    .max()
+                    .unwrap_or(0) as u16;
+                let [label_edit_area, add_label_area] = Layout::horizontal([
+                    Constraint::Fill(1),
+                    Constraint::Length(list_width.min(area.width / 2)),
+                ])
+                .spacing(2)
+                .areas(area);
+                let targets = &mut self.click_areas.editor;
+                targets.clear();
+
+                // The timestamps are kept up to date by the database so they're only shown
+                let [label_edit_area, timestamps_area] =
+                    Layout::vertical([Constraint::Fill(1), Constraint::Length(2)])
+                        .areas(label_edit_area);
+                let timestamps = [
+                    ("Created", self.replay_db.created_at(*entity)),
+                    ("Modified", self.replay_db.modified_at(*entity)),
+                ]
+                .map(|(name, timestamp)| {
+                    let timestamp = timestamp.map_or("unknown".to_string(), format_timestamp);
+                    Line::raw(format!("{name}: {timestamp}")).dim()
+                });
+                frame.render_widget(Text::from_iter(timestamps), timestamps_area);
+
+                // edit labels, a line each for the fields, their delete buttons and the two
+                // buttons at the end. When that's more lines than fit the form scrolls just far
+                // enough to keep the focused line on screen, lines scrolled off get empty areas.
+                let focused_line = match focus {
+                    ReplayInfoEditorFocus::LabelData(n) | ReplayInfoEditorFocus::TextArea(n) => {
+                        n * 2
+                    }
+                    ReplayInfoEditorFocus::LabelRemove(n) => n * 2 + 1,
+                    ReplayInfoEditorFocus::LabelAdd => labels.len() * 2,
+                    ReplayInfoEditorFocus::SaveChanges => labels.len() * 2 + 1,
+                    ReplayInfoEditorFocus::AddableLabel(_) | ReplayInfoEditorFocus::NewLabel => 0,
+                };
+                let height = label_edit_area.height as usize;
+                let scroll = (focused_line + 1).saturating_sub(height);
+                let edit_labels_areas = (0..labels.len() * 2 + 2)
+                    .map(|line| match line.checked_sub(scroll) {
+                        Some(row) if row < height => Rect {
+                            y: label_edit_area.y + row as u16,
+                            height: 1,
+                            ..label_edit_area
+                        },
+                        _ => Rect::default(),
+                    })
+                    .collect::<Vec<_>>();
+
+                for (n, label) in labels.iter().enumerate() {
+                    // Draw the label name + user input
+                    let area = edit_labels_areas[n * 2];
+
+                    let locked = self.replay_db.is_locked(&label.label);
+                    let error = errors.iter().find(|(field, _)| *field == n);
+                    let style = if error.is_some() {
+                        theme.error.into()
+                    } else if let ReplayInfoEditorFocus::LabelData(n2) = focus
+                        && *n2 == n
+                    {
+                        theme.field_focus.into()
+                    } else if locked {
+                        Style::default().dim()
+                    } else {
+                        Style::default()
+                    };
+
+                    let constraints = [
+                        Constraint::Length(label.label.name.width() as u16 + 2),
+                        Constraint::Fill(0),
+                    ];
+                    let [label_area, value_area] = Layout::horizontal(constraints).areas(area);
+
+                    let line = Line::from_iter([&*label.label.name, ": "])
+                        .bold()
+                        .style(style);
+                    frame.render_widget(line, label_area);
+                    if label.label.data == LabelDataKind::Unit {
+                        let ticked = !label.data.value().is_empty();
+                        let checkbox = if ticked { "[x]" } else { "[ ]" };
+                        frame.render_widget(Line::raw(checkbox), value_area);
+                    } else if let Some((first, _)) = label.data.value().split_once('\n') {
+                        // The rest is only shown in the popup
+                        frame.render_widget(Line::raw(format!("{first}…")), value_area);
+                    } else {
+                        frame.render_widget(label.data.line(), value_area);
+                    }
+                    targets.push((area, ReplayInfoEditorFocus::LabelData(n)));
+
+                    // Draw the delete label "button", with why the field was rejected beside it
+                    let area = edit_labels_areas[n * 2 + 1];
+                    let required = self.replay_db.is_required(&label.label);
+                    let text = if self.read_only {
+                        ""
+                    } else if required {
+                        "Required"
+                    } else if locked {
+                        "Locked"
+                    } else {
+                        "[Delete Label]"
+                    };
+                    let [button_area, error_area] = Layout::horizontal([
+                        Constraint::Length(text.width() as u16),
+                        Constraint::Fill(1),
+                    ])
+                    .spacing(2)
+                    .areas(area);
+                    if let Some((_, error)) = error {
+                        frame.render_widget(Line::raw(error.as_str()).fg(theme.error), error_area);
+                    }
+
+                    if required || self.read_only {
+                        // Never focused, so there's no point offering to click it either
+                        frame.render_widget(Line::raw(text).fg(theme.dim), button_area);
+                        continue;
+                    }
+                    let style: Style = if let ReplayInfoEditorFocus::LabelRemove(n2) = focus
+                        && *n2 == n
+                    {
+                        theme.remove_focus.into()
+                    } else {
+                        theme.button.into()
+                    };
+                    let line = Line::raw(text).style(style).bold();
+                    frame.render_widget(line, button_area);
+                    targets.push((button_area, ReplayInfoEditorFocus::LabelRemove(n)));
+                }
+
+                // Nothing can be changed so there's nothing to press
+                if !self.read_only {
+                    // Draw the add label "button"
+                    let area = edit_labels_areas[labels.len() * 2];
+                    let style: Style = if let ReplayInfoEditorFocus::LabelAdd = focus {
+                        theme.add_focus.into()
+                    } else {
+                        theme.button.into()
+                    };
+                    let line = Line::raw("[Add Label]").style(style).bold();
+                    frame.render_widget(line, area);
+                    targets.push((area, ReplayInfoEditorFocus::LabelAdd));
+
+                    // Draw the save changes "button"
+                    let area = edit_labels_areas[labels.len() * 2 + 1];
+                    let style: Style = if let ReplayInfoEditorFocus::SaveChanges = focus {
+                        theme.save_focus.into()
+                    } else {
+                        theme.button.into()
+                    };
+                    let line = Line::raw("[Save Changes]").style(style).bold();
+                    frame.render_widget(line, area);
+                    targets.push((area, ReplayInfoEditorFocus::SaveChanges));
+                }
+
+                // add labels list
+                let [filter_area, add_label_area] =
+                    Layout::vertical([Constraint::Length(filtering as u16), Constraint::Fill(1)])
+                        .areas(add_label_area);
+                let mut filter = addable_filter.line();
+                filter.spans.insert(0, Span::raw("/ ").dim());
+                frame.render_widget(filter, filter_area);
+
+                let addable_labels_areas = layout::Layout::vertical(Constraint::from_lengths(
+                    shown
+                        .iter()
+                        .map(|_| /* label.name.len() as u16 */ 1)
+                        .chain([1]),
+                ))
+                .split(add_label_area);
+
+                for (n, label) in shown.iter().map(|&n| &addable[n]).enumerate() {
+                    let style: Style = if let ReplayInfoEditorFocus::AddableLabel(selected_n) =
+                        focus
+                        && *selected_n == n
+                    {
+                        theme.list_focus.into()
+                    } else {
+                        theme.list.into()
+                    };
+                    let line = Line::raw(&label.name).style(style).bold();
+                    frame.render_widget(line, addable_labels_areas[n]);
+                    targets.push((
+                        addable_labels_areas[n],
+                        ReplayInfoEditorFocus::AddableLabel(n),
+                    ));
+                }
+
+                let n = shown.len();
+                let style: Style = if let ReplayInfoEditorFocus::AddableLabel(selected_n) = focus
+                    && *selected_n == n
+                {
+                    theme.list_focus.into()
+                } else {
+                    theme.list.into()
+                };
+                let line = Line::raw(NEW_LABEL_ITEM).style(style).italic();
+                frame.render_widget(line, addable_labels_areas[n]);
+                targets.push((
+                    addable_labels_areas[n],
+                    ReplayInfoEditorFocus::AddableLabel(n),
+                ));
+
+                match focus {
+                    ReplayInfoEditorFocus::LabelData(n) => {
+                        let area = edit_labels_areas[*n * 2];
+                        let label = &labels[*n];
+                        // Inside a Unit field's checkbox, and at the start of a multi-line field
+                        // whose cursor could be on any line
+                        let cursor_offset = if label.label.data == LabelDataKind::Unit {
+                            1
+                        } else if self.replay_db.is_multiline(&label.label) {
+                            0
+                        } else {
+                            label.data.visual_cursor()
+                        };
+                        frame.set_cursor_position(area.offset(layout::Offset {
+                            x: label.label.name.width() as i32 + 2 + cursor_offset as i32,
+                            y: 0,
+                        }));
+
+                        let list = Self::text_suggestions(&self.replay_db, *entity, label);
+                        if !list.is_empty() && !suggestions.dismissed && suggestions.field == *n {
+                            let width = list
+                                .iter()
+                                .map(|text| Line::raw(text).width())
+                                .max()
+                                .unwrap_or(0);
+                            let popup_area = Rect {
+                                x: area.x + label.label.name.width() as u16 + 2,
+                                y: area.y + 1,
+                                width: width as u16 + 2,
+                                height: list.len() as u16 + 2,
+                            }
+                            .intersection(frame.area());
+
+                            let lines = list
+                                .iter()
+                                .enumerate()
+                                .map(|(i, text)| {
+                                    let line = Line::raw(text);
+                                    if i == suggestions.selected {
+                                        line.reversed()
+                                    } else {
+                                        line
+                                    }
+                                })
+                                .collect::<Vec<_>>();
+
+                            frame.render_widget(Clear, popup_area);
+                            frame.render_widget(
+                                Paragraph::new(lines)
+                                    .block(Block::bordered().title_bottom(" Tab "))
+                                    .bg(theme.popup_bg),
+                                popup_area,
+                            );
+                        }
+                    }
+
+                    ReplayInfoEditorFocus::NewLabel => {
+                        let kinds = NEW_LABEL_KINDS.iter().enumerate().map(|(n, kind)| {
+                            let span = Span::raw(format!(" {kind:?} "));
+                            if n == new_label.kind {
+                                span.reversed()
+                            } else {
+                                span
+                            }
+                        });
+                        let mut name = new_label.name.line();
+                        name.spans.insert(0, Span::raw("Name: ").bold());
+                        let lines = vec![
+                            name,
+                            Line::from_iter(
+                                std::iter::once(Span::raw("Kind:").bold()).chain(kinds),
+                            ),
+                            Line::raw(new_label.error.as_deref().unwrap_or_default())
+                                .fg(theme.error),
+                        ];
+
+                        let area = centered_rect(frame.area(), 60, lines.len() as u16 + 2);
+                        let block = Block::bordered()
+                            .title(" New label ")
+                            .title_bottom(" Enter to create, Tab to complete, ↑/↓ to change kind, Esc to cancel ")
+                            .bg(theme.popup_bg);
+                        let input_area = block.inner(area);
+
+                        frame.render_widget(Clear, area);
+                        frame.render_widget(Paragraph::new(lines).block(block), area);
+                        frame.set_cursor_position(input_area.offset(layout::Offset {
+                            x: "Name: ".len() as i32 + new_label.name.visual_cursor() as i32,
+                            y: 0,
+                        }));
+                    }
+
+                    ReplayInfoEditorFocus::TextArea(n) => {
+                        let lines = text_area.lines().iter().map(TextField::line);
+                        let height = text_area.lines().len().max(3) as u16 + 2;
+                        let area = centered_rect(frame.area(), 60, height);
+                        let block = Block::bordered()
+                            .title(format!(" {} ", labels[*n].label.name))
+                            .title_bottom(" Enter for a new line, Esc when done ")
+                            .bg(theme.popup_bg);
+                        let input_area = block.inner(area);
+
+                        // Scrolled just far enough to keep the cursor's line in the popup
+                        let row = text_area.row();
+                        let scroll = (row + 1).saturating_sub(input_area.height as usize);
+                        frame.render_widget(Clear, area);
+                        frame.render_widget(
+                            Paragraph::new(lines.collect::<Vec<_>>())
+                                .block(block)
+                                .scroll((scroll as u16, 0)),
+                            area,
+                        );
+                        frame.set_cursor_position(input_area.offset(layout::Offset {
+                            x: text_area.lines()[row].visual_cursor() as i32,
+                            y: (row - scroll) as i32,
+                        }));
+                    }
+
+                    ReplayInfoEditorFocus::AddableLabel(_) => {
+                        frame.set_cursor_position(filter_area.offset(layout::Offset {
+                            x: "/ ".len() as i32 + addable_filter.visual_cursor() as i32,
+                            y: 0,
+                        }));
+                    }
+
+                    ReplayInfoEditorFocus::SaveChanges
+                    | ReplayInfoEditorFocus::LabelRemove(_)
+                    | ReplayInfoEditorFocus::LabelAdd => (),
+                }
+            }
+        }
+    }
+}
+
+/// An [`App`] started with the command line `args`
+#[cfg(test)]
+fn app(args: &[&str]) -> App {
+    let args = cli::Args::parse(args.iter().map(|arg| arg.to_string())).unwrap();
+    App::with_db(
+        &args,
+        ReplayDB::open(&args.db_path, args.sample_rows).unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn editor_focus_cycle_wraps() {
+    for max_labels in [0, 1, 5] {
+        let start = if max_labels > 0 {
+            ReplayInfoEditorFocus::LabelData(0)
+        } else {
+            ReplayInfoEditorFocus::LabelAdd
+        };
+
+        // Every label has a data and remove focus, plus the add and save buttons
+        let mut seen = vec![];
+        let mut focus = start;
+        for _ in 0..(max_labels * 2 + 2) {
+            assert!(!seen.contains(&focus), "{focus:?} visited twice");
+            seen.push(focus);
+            focus = focus.next_focus(max_labels, 0, false);
+        }
+        assert_eq!(focus, start);
+
+        for expected in seen.iter().rev() {
+            focus = focus.prev_focus(max_labels, 0);
+            assert_eq!(focus, *expected);
+        }
+    }
+}
+
+#[test]
+fn required_labels_cant_be_removed() {
+    let mut db = ReplayDB::new();
+    let entity = db.spawn();
+
+    // Name is required so a new entity gets an empty Name field to fill in
+    let editor = ReplayInfoEditor::new(&db, entity);
+    assert_eq!(editor.labels[0].label, db.labels()[0]);
+    assert_eq!(editor.labels[0].data.value(), "");
+
+    let step = |focus: ReplayInfoEditorFocus, forward| focus.step(&db, &editor.labels, 1, forward);
+    assert_eq!(
+        step(ReplayInfoEditorFocus::LabelData(0), true),
+        ReplayInfoEditorFocus::LabelData(1)
+    );
+    assert_eq!(
+        step(ReplayInfoEditorFocus::LabelData(1), false),
+        ReplayInfoEditorFocus::LabelData(0)
+    );
+}
+
+#[test]
+fn locked_columns_reject_edits() {
+    let mut db = ReplayDB::new();
+    db.seed_demo_data(10);
+    let entity = db.nth_entity(0, &ViewOptions::default()).unwrap();
+
+    for label in db.labels().to_vec() {
+        assert!(db.toggle_lock(&label));
+        assert!(db.is_locked(&label));
+        assert!(App::addable_labels(&db, &[]).all(|addable| addable != label));
+
+        let before = db.get_value(&label, entity);
+        if label.data == LabelDataKind::Unit {
+            assert!(db.toggle_unit(&label, entity).is_err());
+        }
+        assert_eq!(db.get_value(&label, entity), before);
+
+        let mut input = LabelInput {
+            label: label.clone(),
+            data: TextField::new("1".to_string()),
+        };
+        // Unit fields are ticked rather than typed into
+        let (key, edited) = match label.data {
+            LabelDataKind::Unit => (' ', ""),
+            _ => ('2', "12"),
+        };
+        let event = Event::Key(KeyCode::Char(key).into());
+        assert!(!App::edit_label_input(&db, &mut input, &event));
+        assert_eq!(input.data.value(), "1");
+
+        assert!(!db.toggle_lock(&label));
+        if label.data == LabelDataKind::Unit {
+            assert!(db.toggle_unit(&label, entity).is_ok());
+            assert_ne!(db.get_value(&label, entity), before);
+        }
+        assert!(App::edit_label_input(&db, &mut input, &event));
+        assert_eq!(input.data.value(), edited);
+    }
+}
+
+#[test]
+fn unit_fields_toggle_with_space() {
+    let mut db = ReplayDB::new();
+    let pb = db.labels()[2].clone();
+    let entity = db.spawn();
+
+    // Unset Unit columns still get an unticked field, like the required Name
+    let mut editor = ReplayInfoEditor::new(&db, entity);
+    let [_, input] = &mut editor.labels[..] else {
+        panic!("expected only the Name and PB fields");
+    };
+    assert_eq!((&input.label, input.data.value()), (&pb, ""));
+
+    let space = Event::Key(KeyCode::Char(' ').into());
+    assert!(App::edit_label_input(&db, input, &space));
+    assert_eq!(input.data.value(), UNIT_SET);
+    assert!(!App::edit_label_input(
+        &db,
+        input,
+        &Event::Key(KeyCode::Char('a').into())
+    ));
+    assert!(ReplayInfoEditor::has_changes(&db, entity, &editor.labels));
+
+    db.insert_value(&pb, entity, Value::Unit);
+    let editor = ReplayInfoEditor::new(&db, entity);
+    assert_eq!(editor.labels[1].data.value(), UNIT_SET);
+}
+
+#[test]
+fn addable_filter_fuzzy_matches_names() {
+    let db = ReplayDB::new();
+    let addable = db.labels().to_vec();
+    let names = |filter| {
+        App::filter_addable(&addable, filter)
+            .into_iter()
+            .map(|n| addable[n].name.as_str())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(names(""), ["Name", "800 Split", "PB", "Replay"]);
+    assert_eq!(names("spt"), ["800 Split"]);
+    assert_eq!(names("a"), ["Name", "Replay"]);
+    assert_eq!(names("pb"), ["PB"]);
+    assert!(names("split800").is_empty());
+}
+
+#[test]
+fn addable_labels_cache_tracks_edits() {
+    let mut db = ReplayDB::new();
+    db.seed_demo_data(10);
+    let entity = db.nth_entity(0, &ViewOptions::default()).unwrap();
+    let locked = db.labels()[1].clone();
+    db.toggle_lock(&locked);
+
+    let mut editor = ReplayInfoEditor::new(&db, entity);
+    let fresh = |db: &ReplayDB, editor: &ReplayInfoEditor| {
+        App::addable_labels(db, &editor.labels).collect::<Vec<_>>()
+    };
+    assert_eq!(editor.addable, fresh(&db, &editor));
+
+    // Remove every unlocked label from the front, then add them all back from the middle
+    while let Some(n) = editor
+        .labels
+        .iter()
+        .position(|input| !db.is_locked(&input.label))
+    {
+        App::remove_label(&db, &mut editor.labels, &mut editor.addable, n);
+        assert_eq!(editor.addable, fresh(&db, &editor));
+    }
+    while !editor.addable.is_empty() {
+        let n = editor.addable.len() / 2;
+        App::add_label(&mut editor.labels, &mut editor.addable, n);
+        assert_eq!(editor.addable, fresh(&db, &editor));
+    }
+}
+
+#[test]
+fn missing_filter_keeps_incomplete_rows() {
+    let mut db = ReplayDB::new();
+    db.seed_demo_data(10);
+    let label = db.labels()[0].clone();
+    let value = Value::Text("filled".to_string());
+    for entity in db.ordered_entities() {
+        db.remove_value(&label, entity);
+        db.insert_value(&label, entity, value.clone());
+    }
+
+    let entities = db.ordered_entities();
+    db.remove_value(&label, entities[3]);
+
+    let mut view = ViewOptions {
+        missing: Some(MissingFilter::Column(label.clone())),
+        ..ViewOptions::default()
+    };
+    assert_eq!(db.visible_entities(&view), vec![entities[3]]);
+    assert_eq!(db.nth_entity(0, &view), Some(entities[3]));
+
+    view.missing = Some(MissingFilter::Any);
+    assert!(db.visible_entities(&view).contains(&entities[3]));
+    for entity in db.visible_entities(&view) {
+        assert!(
+            db.labels()
+                .iter()
+                .any(|l| db.get_value(l, entity).is_none())
+        );
+    }
+
+    view.missing = None;
+    assert_eq!(db.row_count(&view), entities.len());
+}
+
+#[test]
+fn replace_text_updates_matching_values() {
+    let mut db = ReplayDB::new();
+    let name = db.labels()[0].clone();
+    let values = ["any%", "Any%", "any% glitchless", "100%"];
+    let entities = values
+        .map(|value| {
+            let entity = db.spawn();
+            db.insert_value(&name, entity, Value::Text(value.to_string()));
+            entity
+        })
+        .to_vec();
+
+    // Whole value matching leaves values merely containing `find` alone
+    let preview = db.replace_preview(&name, "any%", "Any%", true);
+    assert_eq!(
+        preview,
+        vec![(entities[0], "any%".to_string(), "Any%".to_string())]
+    );
+
+    let mut history = History::default();
+    let changed = db.replace_text(&name, "any%", "Any%", false).unwrap();
+    assert_eq!(changed.len(), 2);
+    for (entity, before) in changed {
+        history.record(&db, entity, Some(before));
+    }
+    let texts = entities
+        .iter()
+        .map(|&entity| db.get_value(&name, entity))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        texts,
+        ["Any%", "Any%", "Any% glitchless", "100%"].map(|text| Some(Value::Text(text.to_string())))
+    );
+
+    assert!(db.replace_preview(&name, "", "x", false).is_empty());
+
+    // Each changed row is undone on its own
+    assert!(history.undo(&mut db));
+    assert_eq!(
+        db.get_value(&name, entities[2]),
+        Some(Value::Text("any% glitchless".to_string()))
+    );
+    assert!(history.undo(&mut db));
+    assert_eq!(
+        db.get_value(&name, entities[0]),
+        Some(Value::Text("any%".to_string()))
+    );
+    assert!(!history.undo(&mut db));
+}
+
+#[test]
+fn read_only_sessions_never_write() {
+    let path = std::env::temp_dir().join(format!("ddreplayer-ro-{}.ron", std::process::id()));
+    let backup = ReplayDB::backup_path(&path);
+    let seeded = app(&["--sample", "2", "--db", path.to_str().unwrap()]);
+    // Saved twice so there's a backup to clobber
+    seeded.replay_db.save_to_path(&path).unwrap();
+    seeded.replay_db.save_to_path(&path).unwrap();
+    let read = |path: &Path| std::fs::read(path).unwrap();
+    let (before, backup_before) = (read(&path), read(&backup));
+
+    let mut app = app(&["--read-only", "--db", path.to_str().unwrap()]);
+    app.replay_db.spawn();
+    app.view.compact = true;
+    app.last_save = Instant::now() - AUTOSAVE_INTERVAL;
+    app.autosave();
+    assert!(app.save_on_exit().is_err());
+    assert_eq!(app.save_view(), Ok(()));
+
+    let unchanged = (read(&path), read(&backup));
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&backup).unwrap();
+    assert_eq!(unchanged, (before, backup_before));
+    assert!(!path.with_extension("ron.tmp").exists());
+}
+
+#[test]
+fn quitting_can_discard_unsaved_changes() {
+    let path = std::env::temp_dir().join(format!("ddreplayer-quit-{}.ron", std::process::id()));
+    let mut app = app(&["--db", path.to_str().unwrap()]);
+    app.replay_db.spawn();
+    let quit = || Confirm {
+        message: String::new(),
+        action: PendingAction::Quit,
+    };
+
+    // Cancelling stays in the viewer and other keys leave the prompt up
+    app.confirm = Some(quit());
+    assert!(!app.answer_confirm(KeyCode::Char('y')));
+    assert!(app.confirm.is_some());
+    assert!(!app.answer_confirm(KeyCode::Char('c')));
+    assert!(app.confirm.is_none());
+
+    // A database that was never saved isn't created either
+    app.confirm = Some(quit());
+    assert!(app.answer_confirm(KeyCode::Char('d')));
+    assert_eq!(app.save_on_exit(), Ok(()));
+    assert!(!path.exists());
+
+    app.discard_on_exit = false;
+    app.confirm = Some(quit());
+    assert!(app.answer_confirm(KeyCode::Char('s')));
+    assert!(!app.replay_db.is_dirty());
+    assert_eq!(
+        ReplayDB::load_from_path(&path)
+            .unwrap()
+            .ordered_entities()
+            .len(),
+        1
+    );
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn read_only_quits_dont_drop_changes() {
+    let path = std::env::temp_dir().join(format!("ddreplayer-roquit-{}.ron", std::process::id()));
+    let mut app = app(&["--db", path.to_str().unwrap()]);
+    app.replay_db.spawn();
+    app.read_only = true;
+    app.confirm = Some(Confirm {
+        message: String::new(),
+        action: PendingAction::Quit,
+    });
+
+    assert!(!app.answer_confirm(KeyCode::Char('s')));
+    assert!(app.confirm.is_none());
+    assert_eq!(
+        app.toasts.back().map(|toast| toast.message.as_str()),
+        Some("Read only, press 'W' to allow changes")
+    );
+    assert!(app.replay_db.is_dirty());
+    assert!(!path.exists());
+}
+
+#[test]
+fn ctrl_tab_cycles_recent_databases() {
+    let dir = std::env::temp_dir().join(format!("ddreplayer-recents-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let paths = ["a.ron", "b.ron", "c.ron"].map(|name| dir.join(name));
+    for path in &paths {
+        let mut launched = app(&["--sample", "1", "--db", path.to_str().unwrap()]);
+        launched.replay_db.save_to_path(path).unwrap();
+        launched.load_recents();
+        launched.save_recents().unwrap();
+    }
+
+    // c was launched last so it's first, then it's back in launch order
+    let [a, b, c] = paths.clone();
+    let mut app = app(&["--db", c.to_str().unwrap()]);
+    app.load_recents();
+    app.view.compact = true;
+    let mut order = Vec::new();
+    for _ in 0..3 {
+        app.switch_db().unwrap();
+        order.push(app.db_path.clone());
+        if app.db_path == b {
+            // b only has its own settings, not those c left in the shared view file
+            assert!(!app.view.compact);
+        }
+    }
+    assert_eq!(order, [b, a, c.clone()]);
+    assert!(app.view.compact);
+    assert_eq!(app.status, Some(format!("Switched to {}", c.display())));
+
+    // The next launch sees the same list
+    let saved = Recents::load_from_path(&dir.join(view_state::RECENTS_FILE));
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(saved, Ok(app.recents.clone()));
+}
+
+#[test]
+fn reloading_picks_up_outside_edits() {
+    let path = std::env::temp_dir().join(format!("ddreplayer-reload-{}.ron", std::process::id()));
+    let mut app = app(&["--sample", "3", "--db", path.to_str().unwrap()]);
+    let entity = app.replay_db.ordered_entities()[0];
+    app.history.record(&app.replay_db, entity, None);
+    app.view.sort = vec![(app.replay_db.labels()[1].clone(), SortDirection::Ascending)];
+    if let AppState::ReplayDBViewer { marked, .. } = &mut app.state {
+        marked.insert(entity);
+    }
+
+    // Cut down to one row and column in an editor
+    std::fs::write(
+        &path,
+        r#"(columns: [(name: "Name", kind: Text)], rows: [(values: {"Name": Text("only")})])"#,
+    )
+    .unwrap();
+    let reloaded = app.reload();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(reloaded, Ok(()));
+
+    assert_eq!(app.replay_db.ordered_entities().len(), 1);
+    assert!(app.view.sort.is_empty());
+    assert!(!app.history.undo(&mut app.replay_db));
+    let AppState::ReplayDBViewer { marked, .. } = &app.state else {
+        unreachable!()
+    };
+    assert!(marked.is_empty());
+}
+
+#[test]
+fn autosave_only_writes_changes() {
+    let path = std::env::temp_dir().join(format!("ddreplayer-autosave-{}.ron", std::process::id()));
+    let mut app = app(&["--sample", "1", "--db", path.to_str().unwrap()]);
+    let long_ago = Instant::now() - AUTOSAVE_INTERVAL;
+
+    app.last_save = long_ago;
+    app.autosave();
+    assert!(!path.exists());
+
+    // A view settings file that can't be written is only reported once an interval
+    app.db_path = PathBuf::from("/does/not/exist.ron");
+    app.view.compact = true;
+    app.last_save = long_ago;
+    for _ in 0..3 {
+        app.autosave();
+    }
+    assert_eq!(app.toasts.len(), 1);
+    app.db_path = path.clone();
+    app.view.compact = false;
+    app.last_save = long_ago;
+
+    // Typing in the editor doesn't change the database until it's saved
+    let entity = app.replay_db.ordered_entities()[0];
+    let mut editor = ReplayInfoEditor::new(&app.replay_db, entity);
+    editor.labels[0].data = TextField::new("half typ".to_string());
+    app.state = AppState::ReplayInfoEditor(editor);
+    app.autosave();
+    assert!(!path.exists());
+
+    app.last_save = Instant::now();
+    app.replay_db.spawn();
+    app.autosave();
+    assert!(!path.exists(), "saved before the interval was up");
+    app.last_save = long_ago;
+    app.autosave();
+    assert!(!app.replay_db.is_dirty());
+    let saved = ReplayDB::load_from_path(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(saved.ordered_entities().len(), 2);
+}
+
+#[test]
+fn viewer_selection_stays_in_bounds() {
+    let mut app = app(&["--sample", "3", "--db", "/does/not/exist.ron"]);
+
+    let selected = |app: &App| match &app.state {
+        AppState::ReplayDBViewer { table_state, .. } => table_state.selected(),
+        AppState::ReplayInfoEditor(_) => unreachable!(),
+    };
+
+    app.state = app.viewer_state(5);
+    assert_eq!(selected(&app), Some(2));
+    app.next_row();
+    assert_eq!(selected(&app), Some(0));
+    app.prev_row();
+    assert_eq!(selected(&app), Some(2));
+
+    // Deleting every row leaves nothing selected rather than a stale index
+    for entity in app.replay_db.ordered_entities() {
+        app.replay_db.despawn(entity);
+    }
+    app.state = app.viewer_state(2);
+    assert_eq!(selected(&app), None);
+    app.next_row();
+    app.prev_row();
+    assert_eq!(selected(&app), None);
+}
+
+#[test]
+fn validate_rejects_bad_numbers() {
+    let mut db = ReplayDB::new();
+    let entity = db.spawn();
+    let input = |n: usize, value: &str| LabelInput {
+        label: db.labels()[n].clone(),
+        data: TextField::new(value.to_string()),
+    };
+
+    let labels = [input(0, "abc"), input(1, "182"), input(2, "")];
+    assert_eq!(ReplayInfoEditor::validate(&db, entity, &labels), Ok(()));
+
+    let labels = [
+        input(1, "abc"),
+        input(0, "abc"),
+        input(1, "40000"),
+        input(1, "1.5"),
+    ];
+    let errors = ReplayInfoEditor::validate(&db, entity, &labels).unwrap_err();
+    assert_eq!(
+        errors.iter().map(|(n, _)| *n).collect::<Vec<_>>(),
+        vec![0, 2, 3]
+    );
+    assert_eq!(errors[1].1, "must be between -32768 and 32767");
+}
+
+#[test]
+fn masked_fields_fill_in_separators() {
+    let mut db = ReplayDB::new();
+    let name = db.labels()[0].clone();
+    db.config_mut(&name).mask = Some("AA-##".to_string());
+    let mut input = LabelInput {
+        label: name.clone(),
+        data: TextField::default(),
+    };
+    let mut press =
+        |code: KeyCode| App::edit_label_input(&db, &mut input, &Event::Key(code.into()));
+
+    // Characters that don't fit their slot are refused
+    assert!(press(KeyCode::Char('a')));
+    assert!(!press(KeyCode::Char('1')));
+    assert!(press(KeyCode::Char('b')));
+    assert!(press(KeyCode::Char('1')));
+    assert!(press(KeyCode::Char('2')));
+    assert!(!press(KeyCode::Char('3')));
+    assert_eq!(input.data.value(), "ab-12");
+    assert_eq!(input.data.cursor(), 5);
+
+    // A separator left at the end by deleting is dropped too
+    assert!(press(KeyCode::Backspace));
+    assert!(press(KeyCode::Backspace));
+    assert_eq!(input.data.value(), "ab");
+
+    // Pasting is masked the same way, with or without separators
+    input.data = TextField::default();
+    let paste = |text: &str| Event::Paste(text.to_string());
+    assert!(App::edit_label_input(&db, &mut input, &paste("xy34")));
+    assert_eq!(input.data.value(), "xy-34");
+    input.data = TextField::default();
+    assert!(App::edit_label_input(&db, &mut input, &paste("xy-34")));
+    assert_eq!(input.data.value(), "xy-34");
+    input.data = TextField::default();
+    assert!(!App::edit_label_input(&db, &mut input, &paste("x1-34")));
+    assert_eq!(input.data.value(), "");
+
+    // Anything that got in another way is caught on save
+    let entity = db.spawn();
+    assert_eq!(
+        db.check_value(&name, Some(entity), &Value::Text("ab-12".to_string())),
+        Ok(())
+    );
+    for text in ["ab-1", "ab12", "a1-12", "ab-123"] {
+        assert_eq!(
+            db.check_value(&name, Some(entity), &Value::Text(text.to_string())),
+            Err("must look like `AA-##`".to_string())
+        );
+    }
+}
+
+#[test]
+fn numeric_fields_ignore_stray_keys() {
+    let db = ReplayDB::new();
+    let mut input = LabelInput {
+        label: db.labels()[1].clone(),
+        data: TextField::default(),
+    };
+    for c in "-1a-2.3".chars() {
+        App::edit_label_input(&db, &mut input, &Event::Key(KeyCode::Char(c).into()));
+    }
+    assert_eq!(input.data.value(), "-123");
+    // Moving the cursor and deleting still work
+    assert!(App::edit_label_input(
+        &db,
+        &mut input,
+        &Event::Key(KeyCode::Home.into())
+    ));
+    assert!(!App::edit_label_input(
+        &db,
+        &mut input,
+        &Event::Key(KeyCode::Char('-').into())
+    ));
+    assert!(App::edit_label_input(
+        &db,
+        &mut input,
+        &Event::Key(KeyCode::Delete.into())
+    ));
+    assert_eq!(input.data.value(), "123");
+
+    assert!(could_be_number("-1.5", true));
+    assert!(could_be_number(".", true));
+    assert!(!could_be_number("1.5.", true));
+    assert!(!could_be_number("1.5", false));
+    assert!(!could_be_number("1-", false));
+}
+
+#[test]
+fn set_cells_checks_the_value_once() {
+    let mut db = ReplayDB::new();
+    let mut history = History::default();
+    let [name, split, ..] = db.labels().to_vec().try_into().unwrap();
+    let entities = [db.spawn(), db.spawn(), db.spawn()];
+    db.insert_value(&split, entities[0], Value::Number(7));
+    let input = |label: &Label, text: &str| LabelInput {
+        label: label.clone(),
+        data: TextField::new(text.to_string()),
+    };
+
+    assert_eq!(
+        App::set_cells(&mut db, &mut history, &entities, &input(&split, "seven")),
+        Err("800 Split not a whole number".to_string())
+    );
+    assert_eq!(db.get_value(&split, entities[1]), None);
+
+    // Rows that already have the value aren't changed
+    assert_eq!(
+        App::set_cells(&mut db, &mut history, &entities, &input(&split, "7")),
+        Ok(2)
+    );
+    assert!(
+        entities
+            .iter()
+            .all(|&entity| db.get_value(&split, entity) == Some(Value::Number(7)))
+    );
+    assert_eq!(
+        App::set_cells(&mut db, &mut history, &entities[1..], &input(&split, "")),
+        Ok(2)
+    );
+    assert_eq!(db.get_value(&split, entities[2]), None);
+
+    db.config_mut(&name).unique = true;
+    assert_eq!(
+        App::set_cells(&mut db, &mut history, &entities, &input(&name, "Tower")),
+        Err("Name is unique so it can't be set on 3 rows".to_string())
+    );
+}
+
+#[test]
+fn set_cell_only_changes_one_column() {
+    let mut db = ReplayDB::new();
+    let mut history = History::default();
+    let [name, split, ..] = db.labels().to_vec().try_into().unwrap();
+    let entity = db.spawn();
+    db.insert_value(&name, entity, Value::Text("Tower".to_string()));
+    let input = |text: &str| LabelInput {
+        label: split.clone(),
+        data: TextField::new(text.to_string()),
+    };
+
+    App::set_cell(&mut db, &mut history, entity, &input("42")).unwrap();
+    assert_eq!(db.get_value(&split, entity), Some(Value::Number(42)));
+    assert_eq!(
+        db.get_value(&name, entity),
+        Some(Value::Text("Tower".to_string()))
+    );
+
+    assert_eq!(
+        App::set_cell(&mut db, &mut history, entity, &input("4.2")),
+        Err("800 Split not a whole number".to_string())
+    );
+    App::set_cell(&mut db, &mut history, entity, &input("")).unwrap();
+    assert_eq!(db.get_value(&split, entity), None);
+
+    assert!(history.undo(&mut db));
+    assert_eq!(db.get_value(&split, entity), Some(Value::Number(42)));
+}
+
+#[test]
+fn blank_numbers_clear_but_zero_is_kept() {
+    let mut db = ReplayDB::new();
+    let split = db.labels()[1].clone();
+    let entity = db.spawn();
+    db.insert_value(&split, entity, Value::Number(5));
+    let save = |db: &mut ReplayDB, text: &str| {
+        let mut editor = ReplayInfoEditor::new(db, entity);
+        editor.labels[1].data = TextField::new(text.to_string());
+        // Name is required so fill it in first
+        editor.labels[0].data = TextField::new("Tower".to_string());
+        assert_eq!(
+            ReplayInfoEditor::validate(db, entity, &editor.labels),
+            Ok(())
+        );
+        let values = ReplayInfoEditor::staged_values(&editor.labels, &editor.opened_with);
+        db.set_values(entity, &values).unwrap();
+        db.get_value(&split, entity)
+    };
+
+    assert_eq!(save(&mut db, "0"), Some(Value::Number(0)));
+    assert_eq!(save(&mut db, "  "), None);
+}
+
+#[test]
+fn validate_applies_column_rules() {
+    let mut db = ReplayDB::new();
+    let replay = db.labels()[3].clone();
+    db.config_mut(&replay).max_len = Some(5);
+    let entity = db.spawn();
+    let input = |n: usize, value: &str| LabelInput {
+        label: db.labels()[n].clone(),
+        data: TextField::new(value.to_string()),
+    };
+
+    let labels = [input(0, "x"), input(3, "a.rec")];
+    assert_eq!(ReplayInfoEditor::validate(&db, entity, &labels), Ok(()));
+
+    // Name is required in the default schema
+    let labels = [input(0, "  "), input(3, "ab.rec")];
+    assert_eq!(
+        ReplayInfoEditor::validate(&db, entity, &labels),
+        Err(vec![
+            (0, "can't be left blank".to_string()),
+            (1, "must be at most 5 characters".to_string()),
+        ])
+    );
+}
+
+#[test]
+fn field_errors_show_below_until_fixed() {
+    let mut app = app(&["--sample", "0", "--db", "/does/not/exist.ron"]);
+    let split = app.replay_db.labels()[1].clone();
+    let entity = app.replay_db.spawn();
+    app.replay_db.insert_value(&split, entity, Value::Number(0));
+    let mut editor = ReplayInfoEditor::new(&app.replay_db, entity);
+    editor.labels[1].data = TextField::new("abc".to_string());
+    editor.errors = ReplayInfoEditor::validate(&app.replay_db, entity, &editor.labels).unwrap_err();
+    app.state = AppState::ReplayInfoEditor(editor);
+
+    let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 12)).unwrap();
+    let frame = terminal.draw(|frame| app.draw(frame)).unwrap();
+    let line = |y| {
+        (0..40)
+            .map(|x| frame.buffer[(x, y)].symbol())
+            .collect::<String>()
+    };
+    // The blank Name is rejected too, each error on the line under its field
+    assert!(line(1).starts_with("Required  can't be left blank"));
+    assert!(line(3).starts_with("[Delete Label]  not a whole number"));
+    // Unfocused buttons are still readable on a dark background
+    assert_eq!(frame.buffer[(1, 3)].fg, Theme::dark().button);
+    assert_ne!(Theme::dark().button, ratatui::style::Color::Black);
+
+    let AppState::ReplayInfoEditor(editor) = &mut app.state else {
+        unreachable!()
+    };
+    let fix = |editor: &mut ReplayInfoEditor, n: usize, value: &str| {
+        editor.labels[n].data = TextField::new(value.to_string());
+        ReplayInfoEditor::revalidate(
+            &app.replay_db,
+            entity,
+            &editor.labels,
+            &mut editor.errors,
+            n,
+        );
+    };
+    fix(editor, 1, "40000");
+    assert_eq!(editor.errors[1].1, "must be between -32768 and 32767");
+    fix(editor, 1, "40");
+    fix(editor, 0, "Tower");
+    assert!(editor.errors.is_empty());
+}
+
+#[test]
+fn short_terminals_scroll_the_editor_to_the_focus() {
+    let mut app = app(&["--sample", "0", "--db", "/does/not/exist.ron"]);
+    let split = app.replay_db.labels()[1].clone();
+    let entity = app.replay_db.spawn();
+    app.replay_db.insert_value(&split, entity, Value::Number(0));
+    app.state = AppState::ReplayInfoEditor(ReplayInfoEditor::new(&app.replay_db, entity));
+
+    // Too short for both fields, their delete buttons and the buttons at the end
+    let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 5)).unwrap();
+    let mut text = |app: &mut App| {
+        let frame = terminal.draw(|frame| app.draw(frame)).unwrap();
+        frame
+            .buffer
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>()
+    };
+    assert!(text(&mut app).contains("Name: "));
+    assert!(!text(&mut app).contains("Save Changes"));
+
+    let AppState::ReplayInfoEditor(editor) = &mut app.state else {
+        unreachable!()
+    };
+    editor.focus = ReplayInfoEditorFocus::SaveChanges;
+    assert!(text(&mut app).contains("Save Changes"));
+    assert!(!text(&mut app).contains("Name: "));
+}
+
+#[test]
+fn defaults_only_fill_missing_values() {
+    let mut db = ReplayDB::new();
+    let [name, split, _, _] = db.labels().to_vec().try_into().unwrap();
+    db.config_mut(&name).default = Some("any%".to_string());
+    db.config_mut(&split).default = Some("120".to_string());
+    let entity = db.spawn();
+    db.insert_value(&split, entity, Value::Number(16));
+
+    let fields = |db: &ReplayDB| {
+        ReplayInfoEditor::new(db, entity)
+            .labels
+            .iter()
+            .map(|input| (input.label.name.clone(), input.data.value().to_string()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        fields(&db)[..2],
+        [
+            ("Name".to_string(), "any%".to_string()),
+            ("800 Split".to_string(), "16".to_string())
+        ]
+    );
+
+    db.config_mut(&split).default = Some("sixteen".to_string());
+    assert!(ReplayDB::from_file(db.to_file()).is_err());
+    db.config_mut(&split).default = None;
+    assert!(ReplayDB::from_file(db.to_file()).is_ok());
+}
+
+#[test]
+fn validate_checks_numeric_bounds() {
+    let mut db = ReplayDB::new();
+    let split = db.labels()[1].clone();
+    let config = &mut db.config_mut(&split);
+    config.min = Some(-100.0);
+    config.max = Some(182.0);
+    let entity = db.spawn();
+    let check = |db: &ReplayDB, value: &str| {
+        let labels = [LabelInput {
+            label: split.clone(),
+            data: TextField::new(value.to_string()),
+        }];
+        ReplayInfoEditor::validate(db, entity, &labels).map_err(|mut errors| errors.remove(0).1)
+    };
+
+    assert_eq!(check(&db, "-100"), Ok(()));
+    assert_eq!(check(&db, "182"), Ok(()));
+    assert_eq!(
+        check(&db, "183"),
+        Err("must be between -100 and 182".to_string())
+    );
+
+    db.config_mut(&split).max = None;
+    assert_eq!(check(&db, "1000"), Ok(()));
+    assert_eq!(check(&db, "-101"), Err("must be at least -100".to_string()));
+}
+
+#[test]
+fn validate_rejects_duplicates_in_unique_columns() {
+    let mut db = ReplayDB::new();
+    let name = db.labels()[0].clone();
+    db.config_mut(&name).unique = true;
+    let first = db.spawn();
+    db.insert_value(&name, first, Value::Text("Tower".to_string()));
+    let second = db.spawn();
+    let labels = [LabelInput {
+        label: name.clone(),
+        data: TextField::new("Tower".to_string()),
+    }];
+
+    // Saving a row with its own value again isn't a duplicate
+    assert_eq!(ReplayInfoEditor::validate(&db, first, &labels), Ok(()));
+    assert_eq!(
+        ReplayInfoEditor::validate(&db, second, &labels),
+        Err(vec![(0, "is already used by another row".to_string())])
+    );
+}
+
+#[test]
+fn bool_columns_store_false() {
+    let label = Label {
+        name: "Verified".to_string(),
+        data: LabelDataKind::Bool,
+    };
+    let mut db =
+        ReplayDB::with_columns([(label.clone(), ddreplayer::LabelConfig::default())]).unwrap();
+    let [set, unset] = [db.spawn(), db.spawn()];
+
+    // False is a value of its own, unlike an absent Unit
+    db.insert_value(&label, set, Value::Bool(false));
+    assert_eq!(db.get_value(&label, set), Some(Value::Bool(false)));
+    assert_eq!(db.cell_text(&label, set, false), "false");
+    assert_eq!(db.cell_text(&label, unset, false), "");
+
+    let loaded = ReplayDB::from_file(db.to_file()).unwrap();
+    let set = loaded.ordered_entities()[0];
+    assert_eq!(loaded.get_value(&label, set), Some(Value::Bool(false)));
+
+    assert_eq!(Value::parse(&label.data, " true"), Ok(Value::Bool(true)));
+    assert!(Value::parse(&label.data, "yes").is_err());
+
+    let mut input = LabelInput {
+        label: label.clone(),
+        data: TextField::new("false".to_string()),
+    };
+    let space = Event::Key(KeyCode::Char(' ').into());
+    assert!(App::edit_label_input(&db, &mut input, &space));
+    assert_eq!(input.data.value(), "true");
+}
+
+#[test]
+fn a_column_is_always_visible() {
+    let file = ron::from_str(
+        r#"(columns: [(name: "Split", kind: Number, config: (visible: false))], rows: [])"#,
+    )
+    .unwrap();
+    assert_eq!(
+        ReplayDB::from_file(file).err().unwrap(),
+        "every column is hidden"
+    );
+
+    let mut db = ReplayDB::new();
+    let labels = db.labels().to_vec();
+    for label in &labels[1..] {
+        db.toggle_visible(label).unwrap();
+    }
+    assert_eq!(
+        db.delete_column(&labels[0]),
+        Err(format!("{} is the only visible column", labels[0].name))
+    );
+    db.delete_column(&labels[1]).unwrap();
+    assert_eq!(db.step_column(None, &ViewOptions::default(), true), 0);
+}
+
+#[test]
+fn search_matches_typed_values() {
+    let mut db = ReplayDB::new();
+    let [name, split, pb, _] = db.labels().to_vec().try_into().unwrap();
+    let tower = db.spawn();
+    db.insert_value(&name, tower, Value::Text("Tower of Trials".to_string()));
+    db.insert_value(&split, tower, Value::Number(182));
+    let pit = db.spawn();
+    db.insert_value(&name, pit, Value::Text("Pit".to_string()));
+    db.insert_value(&pb, pit, Value::Unit);
+
+    let search = |query: &str| {
+        let view = ViewOptions {
+            search: Some(query.to_string()),
+            ..ViewOptions::default()
+        };
+        db.visible_entities(&view)
+    };
+    assert_eq!(search("18"), vec![tower]);
+    assert_eq!(search("trials"), vec![tower]);
+    assert_eq!(search("T"), vec![tower, pit]);
+    // Unit cells have no text to match against
+    assert!(search("✓").is_empty());
+
+    let regex_search = |query: &str| {
+        let view = ViewOptions {
+            search: Some(query.to_string()),
+            regex_search: true,
+            ..ViewOptions::default()
+        };
+        db.visible_entities(&view)
+    };
+    assert_eq!(regex_search("^P"), vec![pit]);
+    assert_eq!(regex_search(r"^\d{3}$"), vec![tower]);
+    assert!(regex_search("trials").is_empty());
+    // Everything is shown until the pattern is valid
+    assert_eq!(regex_search("(Tower"), vec![tower, pit]);
+}
+
+#[test]
+fn sort_orders_rows_by_column() {
+    let mut db = ReplayDB::new();
+    let [name, split, pb, _] = db.labels().to_vec().try_into().unwrap();
+    let rows = [
+        ("b", Some(20), true),
+        ("c", None, false),
+        ("a", Some(-5), false),
+        ("d", Some(100), true),
+    ]
+    .map(|(text, number, unit)| {
+        let entity = db.spawn();
+        db.insert_value(&name, entity, Value::Text(text.to_string()));
+        if let Some(number) = number {
+            db.insert_value(&split, entity, Value::Number(number));
+        }
+        if unit {
+            db.insert_value(&pb, entity, Value::Unit);
+        }
+        entity
+    });
+
+    let sorted = |label: &Label, direction| {
+        let view = ViewOptions {
+            sort: vec![(label.clone(), direction)],
+            ..ViewOptions::default()
+        };
+        db.visible_entities(&view)
+    };
+    let [b, c, a, d] = rows;
+    assert_eq!(sorted(&name, SortDirection::Ascending), vec![a, b, c, d]);
+    assert_eq!(sorted(&name, SortDirection::Descending), vec![d, c, b, a]);
+    // Rows without a value sink to the bottom either way
+    assert_eq!(sorted(&split, SortDirection::Ascending), vec![a, b, d, c]);
+    assert_eq!(sorted(&split, SortDirection::Descending), vec![d, b, a, c]);
+    // Sorting is stable so rows with the same Unit presence keep their natural order
+    assert_eq!(sorted(&pb, SortDirection::Descending), vec![b, d, c, a]);
+
+    // Only the sorted column's header gets an arrow
+    let mut view = ViewOptions {
+        sort: vec![(split.clone(), SortDirection::Descending)],
+        ..ViewOptions::default()
+    };
+    assert_eq!(db.header_names(&view)[..3], ["Name", "800 Split ▼", "PB"]);
+
+    // Later keys only order rows the earlier ones tie on
+    view.sort.clear();
+    view.toggle_sort_key(pb.clone());
+    view.toggle_sort_key(name.clone());
+    view.toggle_sort_key(name.clone());
+    assert_eq!(db.visible_entities(&view), vec![c, a, d, b]);
+    assert_eq!(
+        db.header_names(&view)[..3],
+        ["Name ▼²", "800 Split", "PB ▲¹"]
+    );
+
+    // Once descending a key is removed again
+    view.toggle_sort_key(name.clone());
+    assert_eq!(view.sort, [(pb.clone(), SortDirection::Ascending)]);
+    assert_eq!(db.header_names(&view)[..3], ["Name", "800 Split", "PB ▲"]);
+}
+
+#[test]
+fn csv_imports_create_unknown_columns() {
+    let path = std::env::temp_dir().join(format!("ddreplayer-new-{}.csv", std::process::id()));
+    std::fs::write(&path, "Name,Time,Route\nfirst,1,\nsecond,2.5,Any%\n").unwrap();
+    let mut app = app(&[
+        "--db",
+        "/does/not/exist.ron",
+        "--import",
+        path.to_str().unwrap(),
+    ]);
+    let import = app.import.as_ref().unwrap();
+    let suggested = import
+        .new_columns
+        .iter()
+        .map(|column| {
+            let kind = &NEW_LABEL_KINDS[column.kind];
+            (
+                column.name.as_str(),
+                kind,
+                column.fitting_samples(),
+                column.samples.len(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        suggested,
+        [
+            ("Time", &LabelDataKind::Decimal, 2, 2),
+            ("Route", &LabelDataKind::Text, 1, 1)
+        ]
+    );
+    let report = dry_run_import(&mut ReplayDB::new(), &path);
+    assert_eq!(
+        report,
+        Ok(format!(
+            "would import 2 of 2 rows from {}\nwould create column `Time` as Decimal (2/2 \
+             sampled values fit)\nwould create column `Route` as Text (1/1 sampled values fit)",
+            path.display()
+        ))
+    );
+
+    // Nothing is imported until the kinds are accepted, here with Time changed to Text
+    app.step_import();
+    assert!(app.replay_db.ordered_entities().is_empty());
+    app.answer_new_columns(KeyCode::Left);
+    app.answer_new_columns(KeyCode::Left);
+    app.answer_new_columns(KeyCode::Enter);
+    app.step_import();
+    std::fs::remove_file(&path).unwrap();
+    assert!(app.import.is_none());
+
+    let time = app.replay_db.labels()[4].clone();
+    assert_eq!(time.name, "Time");
+    assert_eq!(time.data, LabelDataKind::Text);
+    let [first, second] = app.replay_db.ordered_entities().try_into().unwrap();
+    assert_eq!(
+        app.replay_db.get_value(&time, first),
+        Some(Value::Text("1".to_string()))
+    );
+    let route = app.replay_db.labels()[5].clone();
+    assert_eq!(app.replay_db.get_value(&route, first), None);
+    assert_eq!(
+        app.replay_db.get_value(&route, second),
+        Some(Value::Text("Any%".to_string()))
+    );
+}
+
+#[test]
+fn dry_runs_report_what_an_import_would_do() {
+    let mut db = ReplayDB::new();
+    let path = std::env::temp_dir().join(format!("ddreplayer-dry-{}.csv", std::process::id()));
+    std::fs::write(&path, "Name,800 Split\nok,1\nbad,1.5\n").unwrap();
+    let report = dry_run_import(&mut db, &path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        report,
+        Ok(format!(
+            "would import 1 of 2 rows from {}\nskipped row 3, column `800 Split`: not a whole \
+             number",
+            path.display()
+        ))
+    );
+}
+
+#[test]
+fn schemas_round_trip_without_rows() {
+    let mut db = ReplayDB::new();
+    db.seed_demo_data(3);
+    let split = db.labels()[1].clone();
+    db.config_mut(&split).max = Some(900.0);
+    let path = std::env::temp_dir().join(format!("ddreplayer-schema-{}.ron", std::process::id()));
+    db.export_schema(&path).unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    let columns = ReplayDB::read_schema(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert!(!text.contains("rows"));
+    assert_eq!(columns, Ok(db.column_files()));
+
+    // A new database is created with just the schema's columns
+    let schema = path.with_extension("schema.ron");
+    std::fs::write(&schema, r#"(columns: [(name: "Time", kind: Decimal)])"#).unwrap();
+    let args = [
+        "--db",
+        path.to_str().unwrap(),
+        "--import-schema",
+        schema.to_str().unwrap(),
+    ];
+    let args = cli::Args::parse(args.map(String::from).into_iter()).unwrap();
+    let mut db = ReplayDB::open(&args.db_path, args.sample_rows).unwrap();
+    let report = run_schema_args(&mut db, &args);
+    let saved = ReplayDB::load_from_path(&path);
+    std::fs::remove_file(&schema).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(report, Ok("added column `Time`".to_string()));
+    let saved = saved.unwrap();
+    assert_eq!(saved.labels().len(), 1);
+    assert_eq!(saved.labels()[0].data, LabelDataKind::Decimal);
+    assert!(saved.ordered_entities().is_empty());
+}
+
+#[test]
+fn confirm_keeps_editor_state_until_answered() {
+    let mut app = app(&["--sample", "1", "--db", "/does/not/exist.ron"]);
+    let entity = app.replay_db.ordered_entities()[0];
+    let mut editor = ReplayInfoEditor::new(&app.replay_db, entity);
+    assert!(!ReplayInfoEditor::has_changes(
+        &app.replay_db,
+        entity,
+        &editor.labels
+    ));
+
+    editor.labels[0].data = TextField::new("edited".to_string());
+    assert!(ReplayInfoEditor::has_changes(
+        &app.replay_db,
+        entity,
+        &editor.labels
+    ));
+    let label_count = editor.labels.len();
+    app.state = AppState::ReplayInfoEditor(editor);
+
+    app.apply_confirmed(PendingAction::RemoveLabel(0));
+    let AppState::ReplayInfoEditor(editor) = &app.state else {
+        panic!("removing a label left the editor");
+    };
+    assert_eq!(editor.labels.len(), label_count - 1);
+
+    app.apply_confirmed(PendingAction::DespawnEntity);
+    assert!(matches!(app.state, AppState::ReplayDBViewer { .. }));
+    assert!(app.replay_db.ordered_entities().is_empty());
+}
+
+#[test]
+fn saving_a_despawned_entity_offers_a_new_row() {
+    let mut app = app(&["--sample", "0", "--db", "/does/not/exist.ron"]);
+    let name = app.replay_db.labels()[0].clone();
+    let entity = app.replay_db.spawn();
+    app.replay_db
+        .insert_value(&name, entity, Value::Text("Tower".to_string()));
+    app.open_editor(entity);
+    let AppState::ReplayInfoEditor(editor) = &mut app.state else {
+        unreachable!();
+    };
+    editor.labels[0].data = TextField::new("Forest".to_string());
+
+    app.replay_db.despawn(entity);
+    assert!(!app.replay_db.is_alive(entity));
+    app.save_editor();
+    assert!(matches!(
+        app.confirm,
+        Some(Confirm {
+            action: PendingAction::RespawnEntity,
+            ..
+        })
+    ));
+    assert!(matches!(app.state, AppState::ReplayInfoEditor(_)));
+
+    app.confirm = None;
+    app.apply_confirmed(PendingAction::RespawnEntity);
+    assert!(matches!(app.state, AppState::ReplayDBViewer { .. }));
+    let entities = app.replay_db.ordered_entities();
+    assert_eq!(entities.len(), 1);
+    assert_eq!(
+        app.replay_db.get_value(&name, entities[0]),
+        Some(Value::Text("Forest".to_string()))
+    );
+}
+
+#[test]
+fn footer_summarises_visible_rows() {
+    let mut db = ReplayDB::new();
+    let [name, split, _, _] = db.labels().to_vec().try_into().unwrap();
+    for (n, value) in [(0, Some(10)), (1, None), (2, Some(-4))] {
+        let entity = db.spawn();
+        db.insert_value(&name, entity, Value::Text(format!("run {n}")));
+        if let Some(value) = value {
+            db.insert_value(&split, entity, Value::Number(value));
+        }
+    }
+
+    // Rows without a split aren't part of the average
+    let mut view = ViewOptions::default();
+    assert_eq!(
+        db.footer_cells(&view),
+        ["count 3", "sum 6\navg 3.000", "", ""]
+    );
+
+    view.search = Some("run 0".to_string());
+    assert_eq!(
+        db.footer_cells(&view)[..2],
+        ["count 1", "sum 10\navg 10.000"]
+    );
+}
+
+#[test]
+fn wrap_text_breaks_on_words() {
+    assert_eq!(
+        wrap_text("any% no major skips", 10, 3),
+        "any% no\nmajor\nskips"
+    );
+    // Too many lines for the cell
+    assert_eq!(wrap_text("one two three four", 9, 2), "one two\nthree…");
+    // Words wider than the cell are split, by display width
+    assert_eq!(wrap_text("日本語日本語", 4, 3), "日本\n語日\n本語");
+    assert_eq!(wrap_text("short", 10, 1), "short");
+    assert_eq!(wrap_text("", 10, 1), "");
+    // Line breaks are kept, blank lines included
+    assert_eq!(wrap_text("run notes\n\nslow", 5, 4), "run\nnotes\n\nslow");
+    assert_eq!(wrap_text("first\nsecond", 10, 1), "first…");
+}
+
+#[test]
+fn highlight_matches_ignores_case() {
+    let highlighted = |line: &str, query: &str| {
+        highlight_matches(line, query)
+            .spans
+            .iter()
+            .filter(|span| span.style.bg.is_some())
+            .map(|span| span.content.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(highlighted("Any% and ANY%", "any%"), ["Any%", "ANY%"]);
+    assert_eq!(highlighted("Ünïcödé", "ÖD"), ["öd"]);
+    assert_eq!(highlighted("nothing here", "xyz"), Vec::<String>::new());
+    assert_eq!(highlighted("nothing here", ""), Vec::<String>::new());
+    assert_eq!(highlight_matches("a b", "b").to_string(), "a b");
+}
+
+#[test]
+fn column_widths_fit_content() {
+    let mut db = ReplayDB::new();
+    let [name, split, _, _] = db.labels().to_vec().try_into().unwrap();
+    let entity = db.spawn();
+    db.insert_value(&name, entity, Value::Text("日本語".to_string()));
+    db.insert_value(&split, entity, Value::Number(-100));
+    db.set_note(entity, &split, "slow".to_string());
+    let other = db.spawn();
+    db.insert_value(&name, other, Value::Text("x".repeat(100)));
+
+    let mut view = ViewOptions::default();
+    // Wide characters count double, notes add their marker, the footer has to fit and long
+    // values are clamped
+    assert_eq!(db.column_widths(&view), [40, 12, 2, 6]);
+    db.despawn(other);
+    assert_eq!(db.column_widths(&view), [7, 12, 2, 6]);
+
+    view.show_timestamps = true;
+    assert_eq!(db.column_widths(&view)[4..], [16, 16]);
+}
+
+#[test]
+fn scrollbar_follows_selection() {
+    let mut app = app(&["--sample", "3", "--db", "/does/not/exist.ron"]);
+    let scroll = |app: &App| match &app.state {
+        AppState::ReplayDBViewer { scroll_state, .. } => *scroll_state,
+        AppState::ReplayInfoEditor(_) => unreachable!(),
+    };
+
+    app.next_row();
+    assert_eq!(scroll(&app), ScrollbarState::new(3).position(1));
+    app.prev_row();
+    app.prev_row();
+    assert_eq!(scroll(&app), ScrollbarState::new(3).position(2));
+
+    // Rows going away shrink the scrollbar on the next move
+    let last = app.replay_db.ordered_entities()[2];
+    app.replay_db.despawn(last);
+    app.next_row();
+    assert_eq!(scroll(&app), ScrollbarState::new(2).position(0));
+}
+
+#[test]
+fn bulk_changes_follow_column_rules() {
+    let mut db = ReplayDB::new();
+    let [name, split, _, _] = db.labels().to_vec().try_into().unwrap();
+    db.config_mut(&name).unique = true;
+    db.config_mut(&split).max = Some(100.0);
+    let first = db.spawn();
+    db.insert_value(&name, first, Value::Text("Any%".to_string()));
+    db.insert_value(&split, first, Value::Number(7));
+
+    // Unique values are left out of duplicates
+    let copy = db.duplicate_row(first);
+    assert_eq!(db.get_value(&name, copy), None);
+    assert_eq!(db.get_value(&split, copy), Some(Value::Number(7)));
+
+    let other = db.spawn();
+    db.insert_value(&name, other, Value::Text("any%".to_string()));
+    assert_eq!(
+        db.replace_text(&name, "any%", "Any%", true)
+            .map(|changed| changed.len()),
+        Err("`Any%` is already used by another row".to_string())
+    );
+    assert_eq!(
+        db.get_value(&name, other),
+        Some(Value::Text("any%".to_string()))
+    );
+
+    let path = std::env::temp_dir().join(format!("ddreplayer-rules-{}.csv", std::process::id()));
+    std::fs::write(&path, "Name,800 Split\nNew,1\nAny%,2\nOther,500\n").unwrap();
+    let mut import = db.start_csv_import(&path).unwrap();
+    while !db.import_csv_batch(&mut import, 1) {}
+    assert_eq!(import.imported, 1);
+    assert_eq!(
+        import.errors,
+        [
+            "row 3, column `Name`: is already used by another row",
+            "row 4, column `800 Split`: must be at most 100"
+        ]
+    );
+
+    db.config_mut(&split).required = true;
+    std::fs::write(&path, "Name\nNo split\n").unwrap();
+    assert_eq!(
+        db.start_csv_import(&path).err().unwrap(),
+        "required column `800 Split` is missing"
+    );
+    std::fs::remove_file(&path).unwrap();
+
+    let path = std::env::temp_dir().join(format!("ddreplayer-rules-{}.ron", std::process::id()));
+    db.export_row(first, &path).unwrap();
+    let imported = db.import_row(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        imported,
+        Err("column `Name`: is already used by another row".to_string())
+    );
+}
+
+#[test]
+fn closing_the_editor_restores_the_viewer() {
+    let mut app = app(&["--sample", "20", "--db", "/does/not/exist.ron"]);
+    let entities = app.replay_db.ordered_entities();
+    app.state = app.viewer_state(15);
+    let AppState::ReplayDBViewer {
+        table_state,
+        marked,
+        ..
+    } = &mut app.state
+    else {
+        unreachable!();
+    };
+    *table_state.offset_mut() = 10;
+    table_state.select_column(Some(2));
+    marked.extend([entities[3], entities[15]]);
+
+    app.open_editor(entities[15]);
+    app.apply_confirmed(PendingAction::DiscardChanges);
+    let AppState::ReplayDBViewer {
+        table_state,
+        marked,
+        ..
+    } = &app.state
+    else {
+        unreachable!();
+    };
+    assert_eq!(table_state.selected(), Some(15));
+    assert_eq!(table_state.selected_column(), Some(2));
+    assert_eq!(table_state.offset(), 10);
+    assert_eq!(marked.len(), 2);
+
+    // Deleting the last row from the editor selects the one before it
+    app.select_row(19);
+    app.open_editor(entities[19]);
+    app.apply_confirmed(PendingAction::DespawnEntity);
+    let AppState::ReplayDBViewer {
+        table_state,
+        marked,
+        ..
+    } = &app.state
+    else {
+        unreachable!();
+    };
+    assert_eq!(table_state.selected(), Some(18));
+    assert_eq!(marked.len(), 2);
+
+    app.open_editor(entities[15]);
+    app.apply_confirmed(PendingAction::DespawnEntity);
+    let AppState::ReplayDBViewer { marked, .. } = &app.state else {
+        unreachable!();
+    };
+    assert_eq!(marked.iter().collect::<Vec<_>>(), [&entities[3]]);
+}
+
+#[test]
+fn despawn_marked_rows() {
+    let mut app = app(&["--sample", "4", "--db", "/does/not/exist.ron"]);
+    let entities = app.replay_db.ordered_entities();
+    app.state = app.viewer_state(3);
+    let AppState::ReplayDBViewer { marked, .. } = &mut app.state else {
+        unreachable!();
+    };
+    marked.extend([entities[1], entities[3]]);
+
+    app.apply_confirmed(PendingAction::DespawnMarked);
+    assert_eq!(app.replay_db.ordered_entities(), [entities[0], entities[2]]);
+    let AppState::ReplayDBViewer {
+        table_state,
+        marked,
+        ..
+    } = &app.state
+    else {
+        unreachable!();
+    };
+    assert!(marked.is_empty());
+    assert_eq!(table_state.selected(), Some(1));
+}
+
+#[test]
+fn editor_cursor_counts_display_columns() {
+    let mut app = app(&["--sample", "0", "--db", "/does/not/exist.ron"]);
+    let label = Label {
+        name: "名前".to_string(),
+        data: LabelDataKind::Text,
+    };
+    app.replay_db =
+        ReplayDB::with_columns([(label.clone(), ddreplayer::LabelConfig::default())]).unwrap();
+    let entity = app.replay_db.spawn();
+    app.replay_db
+        .insert_value(&label, entity, Value::Text("café".to_string()));
+    app.state = AppState::ReplayInfoEditor(ReplayInfoEditor::new(&app.replay_db, entity));
+
+    let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 10)).unwrap();
+    let frame = terminal.draw(|frame| app.draw(frame)).unwrap();
+    // Timestamps are shown read-only above the status bar
+    let line = |y| {
+        (0..30)
+            .map(|x| frame.buffer[(x, y)].symbol())
+            .collect::<String>()
+    };
+    let created = format_timestamp(app.replay_db.created_at(entity).unwrap());
+    assert!(line(7).starts_with(&format!("Created: {created}")));
+    assert!(line(8).starts_with("Modified: "));
+
+    // `名前: ` takes 6 columns and `café` 4, even though they're 8 and 5 bytes long
+    assert_eq!(
+        terminal.get_cursor_position().unwrap(),
+        Position::new(10, 0)
+    );
+}
+
+#[test]
+fn click_areas_match_drawn_layout() {
+    let mut app = app(&["--sample", "2", "--db", "/does/not/exist.ron"]);
+    let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 24)).unwrap();
+
+    terminal.draw(|frame| app.draw(frame)).unwrap();
+    // Everything but the header, footer and the status bar
+    assert_eq!(app.click_areas.table_rows, Rect::new(0, 1, 80, 20));
+
+    let entity = app.replay_db.ordered_entities()[0];
+    let editor = ReplayInfoEditor::new(&app.replay_db, entity);
+    let fields = editor.labels.len() as u16;
+    app.state = AppState::ReplayInfoEditor(editor);
+    terminal.draw(|frame| app.draw(frame)).unwrap();
+
+    let target = |x, y| {
+        app.click_areas
+            .editor
+            .iter()
+            .find(|(area, _)| area.contains(Position::new(x, y)))
+            .map(|(_, focus)| *focus)
+    };
+    assert_eq!(target(5, 0), Some(ReplayInfoEditorFocus::LabelData(0)));
+    // Name is required so it has no delete button to click
+    assert_eq!(target(5, 1), None);
+    assert_eq!(target(5, 3), Some(ReplayInfoEditorFocus::LabelRemove(1)));
+    assert_eq!(
+        target(5, fields * 2 + 1),
+        Some(ReplayInfoEditorFocus::SaveChanges)
+    );
+    // The addable list is only as wide as `New Label…` and sits against the right edge
+    assert_eq!(target(45, 0), Some(ReplayInfoEditorFocus::LabelData(0)));
+    assert_eq!(target(75, 0), Some(ReplayInfoEditorFocus::AddableLabel(0)));
+    assert_eq!(target(69, 0), None);
+}
+
+#[test]
+fn empty_database_says_how_to_add_a_row() {
+    let mut app = app(&["--sample", "0", "--db", "/does/not/exist.ron"]);
+    let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 12)).unwrap();
+    let text = |app: &mut App, terminal: &mut ratatui::Terminal<_>| {
+        let frame = terminal.draw(|frame| app.draw(frame)).unwrap();
+        frame
+            .buffer
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>()
+    };
+
+    assert!(text(&mut app, &mut terminal).contains("No replays yet — press 'n' to add one"));
+    app.replay_db.spawn();
+    assert!(!text(&mut app, &mut terminal).contains("No replays yet"));
+}
+
+#[test]
+fn unit_cells_use_their_columns_color() {
+    let mut app = app(&["--sample", "0", "--db", "/does/not/exist.ron"]);
+    let pb = app.replay_db.labels()[2].clone();
+    let config = &mut app.replay_db.config_mut(&pb);
+    config.unit_present_glyph = "★".to_string();
+    config.unit_color = "magenta".to_string();
+    let entity = app.replay_db.spawn();
+    app.replay_db.insert_value(&pb, entity, Value::Unit);
+    // The selected row's highlight would colour over it
+    let AppState::ReplayDBViewer { table_state, .. } = &mut app.state else {
+        unreachable!()
+    };
+    table_state.select(None);
+
+    let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 12)).unwrap();
+    let frame = terminal.draw(|frame| app.draw(frame)).unwrap();
+    let star = frame
+        .buffer
+        .content()
+        .iter()
+        .find(|cell| cell.symbol() == "★")
+        .unwrap();
+    assert_eq!(star.fg, ratatui::style::Color::Magenta);
+
+    // Colours are checked on load rather than when drawing
+    let mut file = app.replay_db.to_file();
+    file.columns[2].config.unit_color = "not a colour".to_string();
+    assert_eq!(
+        ReplayDB::from_file(file).err().unwrap(),
+        "column `PB` has the invalid unit_color `not a colour`"
+    );
+}
+
+#[test]
+fn id_column_is_drawn_before_the_labels() {
+    let mut app = app(&["--sample", "0", "--db", "/does/not/exist.ron"]);
+    let name = app.replay_db.labels()[0].clone();
+    let entity = app.replay_db.spawn();
+    app.replay_db
+        .insert_value(&name, entity, Value::Text("Tower".to_string()));
+    let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 12)).unwrap();
+    let mut text = |app: &mut App| {
+        let frame = terminal.draw(|frame| app.draw(frame)).unwrap();
+        frame
+            .buffer
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>()
+    };
+
+    assert!(!text(&mut app).contains(&entity_id(entity)));
+    app.view.show_ids = true;
+    let shown = text(&mut app);
+    let id = shown.find(&entity_id(entity)).unwrap();
+    assert!(id < shown.find("Tower").unwrap());
+    // It's not a column of the database so nothing else sees it
+    assert_eq!(app.replay_db.header_names(&app.view)[0], name.name);
+}
+
+#[test]
+fn hidden_columns_keep_their_values() {
+    let mut app = app(&["--sample", "0", "--db", "/does/not/exist.ron"]);
+    let [name, split, pb, _] = app.replay_db.labels().to_vec().try_into().unwrap();
+    let entity = app.replay_db.spawn();
+    app.replay_db
+        .insert_value(&split, entity, Value::Number(4321));
+    let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 12)).unwrap();
+    let text = |app: &mut App, terminal: &mut ratatui::Terminal<_>| {
+        let frame = terminal.draw(|frame| app.draw(frame)).unwrap();
+        frame
+            .buffer
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>()
+    };
+    assert!(text(&mut app, &mut terminal).contains("4321"));
+
+    assert_eq!(app.replay_db.toggle_visible(&split), Ok(false));
+    assert!(!text(&mut app, &mut terminal).contains("4321"));
+    assert!(!text(&mut app, &mut terminal).contains(&split.name));
+    assert_eq!(app.replay_db.shown_columns(&app.view), [0, 2, 3]);
+    // Moving between columns steps over the hidden one
+    assert_eq!(app.replay_db.step_column(Some(0), &app.view, true), 2);
+    assert_eq!(app.replay_db.step_column(Some(2), &app.view, false), 0);
+    assert_eq!(app.replay_db.step_column(Some(3), &app.view, true), 3);
+
+    assert_eq!(app.replay_db.toggle_visible(&split), Ok(true));
+    assert!(text(&mut app, &mut terminal).contains("4321"));
+    assert_eq!(
+        app.replay_db.get_value(&split, entity),
+        Some(Value::Number(4321))
+    );
+
+    // Something always has to be left to show
+    for label in [&name, &split, &pb] {
+        app.replay_db.toggle_visible(label).unwrap();
+    }
+    let last = app.replay_db.labels()[3].clone();
+    assert_eq!(
+        app.replay_db.toggle_visible(&last),
+        Err(format!("{} is the only visible column", last.name))
+    );
+}
+
+#[test]
+fn pinned_columns_stay_on_screen() {
+    let mut app = app(&["--sample", "0", "--db", "/does/not/exist.ron"]);
+    let [name, _, pb, replay] = app.replay_db.labels().to_vec().try_into().unwrap();
+    let entity = app.replay_db.spawn();
+    app.replay_db
+        .insert_value(&name, entity, Value::Text("a".repeat(30)));
+    app.replay_db
+        .insert_value(&replay, entity, Value::FilePath("b".repeat(20)));
+
+    assert!(app.replay_db.toggle_pinned(&replay));
+    assert_eq!(app.replay_db.shown_columns(&app.view), [3, 0, 1, 2]);
+    // Moving between columns follows the order they're drawn in
+    assert_eq!(app.replay_db.step_column(Some(3), &app.view, true), 0);
+    assert_eq!(app.replay_db.step_column(Some(0), &app.view, false), 3);
+    assert_eq!(app.replay_db.step_column(Some(2), &app.view, true), 2);
+
+    // Scrolling over to PB pushes Name off screen but not the pinned Replay
+    let AppState::ReplayDBViewer { table_state, .. } = &mut app.state else {
+        unreachable!();
+    };
+    table_state.select_column(Some(2));
+    let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(40, 12)).unwrap();
+    let frame = terminal.draw(|frame| app.draw(frame)).unwrap();
+    let header = (0..40)
+        .map(|x| frame.buffer[(x, 0)].symbol())
+        .collect::<String>();
+    assert!(header.contains(&replay.name));
+    assert!(header.contains(&pb.name));
+    assert!(!header.contains(&name.name));
+}
+
+#[test]
+fn jump_to_row_rejects_rows_that_arent_shown() {
+    let mut app = app(&["--sample", "5", "--db", "/does/not/exist.ron"]);
+    let selected = |app: &App| match &app.state {
+        AppState::ReplayDBViewer { table_state, .. } => table_state.selected(),
+        _ => unreachable!(),
+    };
+
+    assert_eq!(app.jump_to_row("4"), Ok(()));
+    assert_eq!(selected(&app), Some(3));
+    assert_eq!(
+        app.jump_to_row("6"),
+        Err("There's no row 6, only 5 are shown".to_string())
+    );
+    assert_eq!(
+        app.jump_to_row("0"),
+        Err("There's no row 0, only 5 are shown".to_string())
+    );
+    assert_eq!(
+        app.jump_to_row("4a"),
+        Err("`4a` isn't a row number".to_string())
+    );
+    assert_eq!(selected(&app), Some(3));
+}
+
+#[test]
+fn jump_to_first_and_last_row() {
+    let mut app = app(&["--sample", "0", "--db", "/does/not/exist.ron"]);
+    let selected = |app: &App| match &app.state {
+        AppState::ReplayDBViewer {
+            table_state,
+            scroll_state,
+            ..
+        } => (table_state.selected(), *scroll_state),
+        _ => unreachable!(),
+    };
+
+    // Nothing to select in an empty database
+    app.select_row(usize::MAX);
+    assert_eq!(selected(&app).0, None);
+
+    for _ in 0..5 {
+        app.replay_db.spawn();
+    }
+    app.select_row(usize::MAX);
+    assert_eq!(
+        selected(&app),
+        (Some(4), ScrollbarState::new(5).position(4))
+    );
+    app.select_row(0);
+    assert_eq!(selected(&app), (Some(0), ScrollbarState::new(5)));
+}
+
+#[test]
+fn page_keys_move_by_visible_rows() {
+    let mut app = app(&["--sample", "30", "--db", "/does/not/exist.ron"]);
+    let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 24)).unwrap();
+    terminal.draw(|frame| app.draw(frame)).unwrap();
+    let selected = |app: &App| match &app.state {
+        AppState::ReplayDBViewer { table_state, .. } => table_state.selected(),
+        _ => unreachable!(),
+    };
+
+    let page = app.page_rows();
+    assert_eq!(page, 20 / app.view.row_height() as usize);
+    app.move_page(true);
+    assert_eq!(selected(&app), Some(page));
+
+    // Clamps rather than wrapping around
+    for _ in 0..30 {
+        app.move_page(true);
+    }
+    assert_eq!(selected(&app), Some(29));
+    app.move_page(false);
+    assert_eq!(selected(&app), Some(29 - page));
+    for _ in 0..30 {
+        app.move_page(false);
+    }
+    assert_eq!(selected(&app), Some(0));
+}
+
+#[test]
+fn copy_text_matches_viewer() {
+    let mut db = ReplayDB::new();
+    let [name, split, pb, _] = db.labels().to_vec().try_into().unwrap();
+    let entity = db.spawn();
+    db.insert_value(&name, entity, Value::Text("run".to_string()));
+    db.insert_value(&split, entity, Value::Number(26));
+    db.insert_value(&pb, entity, Value::Unit);
+    db.config_mut(&pb).unit_absent_glyph = "✗".to_string();
+
+    let mut view = ViewOptions::default();
+    assert_eq!(db.copy_text(entity, 0, &view), "run");
+    assert_eq!(db.copy_text(entity, 2, &view), "✓");
+    view.hex_numbers = true;
+    assert_eq!(db.copy_text(entity, 1, &view), "0x1a");
+
+    // Missing values copy as nothing rather than the absent glyph
+    db.remove_value(&pb, entity);
+    assert_eq!(db.copy_text(entity, 2, &view), "");
+    assert_eq!(db.copy_text(entity, 3, &view), "");
+}
+
+#[test]
+fn rows_copy_as_tsv_in_drawn_order() {
+    let mut db = ReplayDB::new();
+    let [name, split, pb, replay] = db.labels().to_vec().try_into().unwrap();
+    let entity = db.spawn();
+    db.insert_value(&name, entity, Value::Text("any%\tglitchless".to_string()));
+    db.insert_value(&pb, entity, Value::Unit);
+    db.insert_value(&replay, entity, Value::FilePath("run.rec".to_string()));
+    db.config_mut(&pb).unit_present_glyph = "yes".to_string();
+
+    let view = ViewOptions::default();
+    assert_eq!(db.row_tsv(entity, &view), "any% glitchless\t\tyes\trun.rec");
+    db.toggle_pinned(&replay);
+    db.toggle_visible(&split).unwrap();
+    assert_eq!(db.row_tsv(entity, &view), "run.rec\tany% glitchless\tyes");
+}
+
+#[test]
+fn status_bar_shows_position() {
+    let mut app = app(&["--sample", "3", "--db", "/does/not/exist.ron"]);
+    assert_eq!(app.status_bar_text(), " Viewer │ 3 entities │ row 1 of 3");
+
+    let entity = app.replay_db.ordered_entities()[0];
+    let mut editor = ReplayInfoEditor::new(&app.replay_db, entity);
+    app.state = AppState::ReplayInfoEditor(ReplayInfoEditor::new(&app.replay_db, entity));
+    assert!(app.status_bar_text().ends_with("│ no changes"));
+
+    editor.labels.pop();
+    app.state = AppState::ReplayInfoEditor(editor);
+    assert!(app.status_bar_text().ends_with("│ unsaved changes"));
+}
+
+#[test]
+fn read_only_refuses_edits() {
+    let app = app(&[
+        "--sample",
+        "3",
+        "--read-only",
+        "--db",
+        "/does/not/exist.ron",
+    ]);
+    assert_eq!(
+        app.status_bar_text(),
+        " Viewer │ 3 entities │ row 1 of 3 │ [READ ONLY]"
+    );
+
+    let edits = |code: KeyCode, modifiers: KeyModifiers| {
+        let key = KeyEvent::new(code, modifiers);
+        App::edits_database(&key, app.keys.viewer(&key))
+    };
+    assert!(edits(KeyCode::Char('e'), KeyModifiers::NONE));
+    assert!(edits(KeyCode::Char('n'), KeyModifiers::NONE));
+    assert!(edits(KeyCode::Delete, KeyModifiers::NONE));
+    assert!(edits(KeyCode::Char('u'), KeyModifiers::NONE));
+    assert!(edits(KeyCode::Right, KeyModifiers::CONTROL));
+    assert!(edits(KeyCode::Char('e'), KeyModifiers::CONTROL));
+    // Looking around is still fine
+    assert!(!edits(KeyCode::Right, KeyModifiers::NONE));
+    assert!(!edits(KeyCode::Char('s'), KeyModifiers::NONE));
+    assert!(!edits(KeyCode::Char('/'), KeyModifiers::NONE));
+    assert!(!edits(KeyCode::Char('b'), KeyModifiers::CONTROL));
+}
+
+#[test]
+fn help_lists_current_mode_first() {
+    let mut app = app(&["--sample", "1", "--db", "/does/not/exist.ron"]);
+    let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 12)).unwrap();
+    let help_text = |app: &mut App, terminal: &mut ratatui::Terminal<_>| {
+        app.show_help = true;
+        let frame = terminal.draw(|frame| app.draw(frame)).unwrap();
+        frame
+            .buffer
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>()
+    };
+
+    let text = help_text(&mut app, &mut terminal);
+    assert!(text.contains("Viewer (current)"));
+    assert!(text.contains("/does/not/exist.ron"));
+    assert!(!text.contains("(read only)"));
+    app.read_only = true;
+    assert!(help_text(&mut app, &mut terminal).contains("/does/not/exist.ron (read only)"));
+
+    let entity = app.replay_db.ordered_entities()[0];
+    app.state = AppState::ReplayInfoEditor(ReplayInfoEditor::new(&app.replay_db, entity));
+    assert!(help_text(&mut app, &mut terminal).contains("Editor (current)"));
+}
+
+#[test]
+fn enum_columns_store_variants() {
+    let file = ron::from_str(
+        r#"(
+            columns: [(name: "Category", kind: Enum(variants: ["Any%", "100%", "Low%"]))],
+            rows: [(values: {"Category": Enum("Low%")}), (values: {"Category": Enum("Any%")})],
+        )"#,
+    )
+    .unwrap();
+    let db = ReplayDB::from_file(file).unwrap();
+    let category = db.labels()[0].clone();
+    let [low, any] = db.ordered_entities().try_into().unwrap();
+    assert_eq!(db.cell_text(&category, low, false), "Low%");
+    assert_eq!(
+        db.row_file(any).values["Category"],
+        Value::Enum("Any%".to_string())
+    );
+
+    let view = ViewOptions {
+        sort: vec![(category.clone(), SortDirection::Ascending)],
+        ..ViewOptions::default()
+    };
+    assert_eq!(db.visible_entities(&view), [any, low]);
+
+    assert_eq!(
+        Value::parse(&category.data, "Glitchless"),
+        Err("must be one of Any%, 100%, Low%".to_string())
+    );
+
+    // The editor cycles through variants, wrapping around at either end
+    let mut input = LabelInput {
+        label: category.clone(),
+        data: TextField::new("Any%".to_string()),
+    };
+    let press = |code: KeyCode| Event::Key(code.into());
+    assert!(App::edit_label_input(
+        &db,
+        &mut input,
+        &press(KeyCode::Left)
+    ));
+    assert_eq!(input.data.value(), "Low%");
+    assert!(App::edit_label_input(
+        &db,
+        &mut input,
+        &press(KeyCode::Right)
+    ));
+    assert_eq!(input.data.value(), "Any%");
+    assert!(!App::edit_label_input(
+        &db,
+        &mut input,
+        &press(KeyCode::Char('x'))
+    ));
+    assert_eq!(input.data.value(), "Any%");
+}
+
+#[test]
+fn saving_leaves_columns_without_fields_alone() {
+    let mut db = ReplayDB::new();
+    let [name, split, pb, replay] = db.labels().to_vec().try_into().unwrap();
+    let entity = db.spawn();
+    db.insert_value(&name, entity, Value::Text("Tower".to_string()));
+    db.insert_value(&split, entity, Value::Number(42));
+
+    let mut editor = ReplayInfoEditor::new(&db, entity);
+    let n = editor
+        .labels
+        .iter()
+        .position(|input| input.label == split)
+        .unwrap();
+    App::remove_label(&db, &mut editor.labels, &mut editor.addable, n);
+    editor.labels[0].data = TextField::new("Forest".to_string());
+    // Written by something else while the editor was open, it never had a field for it
+    db.insert_value(&replay, entity, Value::FilePath("forest.rec".to_string()));
+
+    let values = ReplayInfoEditor::staged_values(&editor.labels, &editor.opened_with);
+    assert_eq!(db.set_values(entity, &values), Ok(2));
+    assert_eq!(
+        db.get_value(&name, entity),
+        Some(Value::Text("Forest".to_string()))
+    );
+    assert_eq!(db.get_value(&split, entity), None);
+    assert_eq!(db.get_value(&pb, entity), None);
+    assert_eq!(
+        db.get_value(&replay, entity),
+        Some(Value::FilePath("forest.rec".to_string()))
+    );
+}
+
+#[test]
+fn multiline_text_survives_saving_and_shows_its_first_line() {
+    let mut app = app(&["--sample", "0", "--db", "/does/not/exist.ron"]);
+    let name = app.replay_db.labels()[0].clone();
+    app.replay_db.config_mut(&name).multiline = true;
+    let entity = app.replay_db.spawn();
+    let notes = "clipped early\n\"died\", twice";
+    app.replay_db
+        .insert_value(&name, entity, Value::Text(notes.to_string()));
+
+    let path = std::env::temp_dir().join(format!("ddreplayer-lines-{}.ron", std::process::id()));
+    app.replay_db.save_to_path(&path).unwrap();
+    let loaded = ReplayDB::load_from_path(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let loaded_entity = loaded.nth_entity(0, &ViewOptions::default()).unwrap();
+    assert!(loaded.is_multiline(&name));
+    assert_eq!(
+        loaded.get_value(&name, loaded_entity),
+        Some(Value::Text(notes.to_string()))
+    );
+
+    app.state = AppState::ReplayInfoEditor(ReplayInfoEditor::new(&app.replay_db, entity));
+    let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 20)).unwrap();
+    let mut text = |app: &mut App| {
+        let frame = terminal.draw(|frame| app.draw(frame)).unwrap();
+        frame
+            .buffer
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect::<String>()
+    };
+    assert!(text(&mut app).contains("Name: clipped early…"));
+
+    let AppState::ReplayInfoEditor(editor) = &mut app.state else {
+        unreachable!()
+    };
+    editor.text_area = TextArea::new(editor.labels[0].data.value());
+    editor.focus = ReplayInfoEditorFocus::TextArea(0);
+    assert!(text(&mut app).contains("\"died\", twice"));
+}
+
+#[test]
+fn toasts_are_drawn_until_they_expire() {
+    let mut app = app(&["--sample", "0", "--db", "/does/not/exist.ron"]);
+    app.toasts.push_back(Toast {
+        message: "Saved".to_string(),
+        shown_at: Instant::now().checked_sub(TOAST_DURATION).unwrap(),
+    });
+    app.toasts.push_back(Toast::new("Exported table"));
+
+    let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(60, 10)).unwrap();
+    let mut line = |app: &mut App, y: u16| {
+        let frame = terminal.draw(|frame| app.draw(frame)).unwrap();
+        (0..60)
+            .map(|x| frame.buffer[(x, y)].symbol())
+            .collect::<String>()
+    };
+    // Newest first
+    assert!(line(&mut app, 0).ends_with(" Exported table "));
+    assert!(line(&mut app, 1).ends_with(" Saved "));
+
+    app.expire_toasts();
+    assert!(!line(&mut app, 1).contains("Saved"));
+
+    for n in 0..MAX_TOASTS + 1 {
+        app.toasts
+            .push_back(Toast::new(format!("Imported {n} rows")));
     }
+    app.expire_toasts();
+    assert_eq!(app.toasts.len(), MAX_TOASTS);
+    assert!(line(&mut app, 0).ends_with(&format!(" Imported {MAX_TOASTS} rows ")));
 }
@@ -1,18 +1,148 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug)]
+use crate::{LabelConfig, LabelDataKind, Value};
+
+/// A replay's file name along with the name it's displayed under
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "ReplayInfoFile")]
 pub struct ReplayInfo {
     raw_name: String,
     pretty_name: String,
 }
 
+/// How a [`ReplayInfo`] is read back, `pretty_name` can be left out to derive it from
+/// `raw_name`
+#[derive(Deserialize)]
+struct ReplayInfoFile {
+    raw_name: String,
+    #[serde(default)]
+    pretty_name: Option<String>,
+}
+
+impl From<ReplayInfoFile> for ReplayInfo {
+    fn from(file: ReplayInfoFile) -> Self {
+        match file.pretty_name {
+            Some(pretty_name) => Self {
+                raw_name: file.raw_name,
+                pretty_name,
+            },
+            None => Self::from_raw(file.raw_name),
+        }
+    }
+}
+
+impl ReplayInfo {
+    /// Derives the pretty name from the file name by dropping its extension and replacing
+    /// `_`s with spaces, e.g. `any%_pb.demo` becomes `any% pb`
+    pub fn from_raw(raw_name: String) -> Self {
+        let stem = raw_name
+            .rsplit_once('.')
+            .map_or(&*raw_name, |(stem, _)| stem);
+        Self {
+            pretty_name: stem.replace('_', " "),
+            raw_name,
+        }
+    }
+
+    /// Pairs `raw_name` with the pretty name stored in `raw`
+    #[cfg(test)]
+    pub fn from_parts(raw_name: String, raw: RawReplayInfo) -> Self {
+        Self {
+            raw_name,
+            pretty_name: raw.pretty_name,
+        }
+    }
+
+    /// The part of this that's stored separately from the file name
+    #[cfg(test)]
+    pub fn to_raw(&self) -> RawReplayInfo {
+        RawReplayInfo {
+            pretty_name: self.pretty_name.clone(),
+        }
+    }
+}
+
+/// A [`ReplayInfo`] without its file name, for when that's stored elsewhere
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RawReplayInfo {
     pretty_name: String,
 }
 
+/// The layout [`ReplayDBFile`]s are written with, bumped whenever a change to it means an
+/// older file needs migrating when it's loaded
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The on disk representation of a [`ReplayDB`](crate::ReplayDB), written as RON
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplayDBFile {
+    /// The [`FORMAT_VERSION`] this was written with
+    #[serde(default = "unversioned")]
+    pub version: u32,
+    pub columns: Vec<ColumnFile>,
+    /// Every entity, in the viewer's natural order
+    pub rows: Vec<RowFile>,
+}
+
+/// Just the version of a [`ReplayDBFile`], read before the rest so a file from a newer
+/// version can be refused rather than failing to parse or losing what it holds on the next save
+#[derive(Deserialize)]
+pub struct FileVersion {
+    #[serde(default = "unversioned")]
+    pub version: u32,
+}
+
+/// Files written before the format was versioned have the same layout as version 1
+fn unversioned() -> u32 {
+    1
+}
+
+/// Just the column definitions of a [`ReplayDBFile`], for sharing a schema without any rows
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct SchemaFile {
+    pub columns: Vec<ColumnFile>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ColumnFile {
+    pub name: String,
+    pub kind: LabelDataKind,
+    #[serde(default)]
+    pub config: LabelConfig,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct RowFile {
+    #[serde(default)]
+    pub created: Option<i64>,
+    #[serde(default)]
+    pub modified: Option<i64>,
+    /// Values keyed by column name, columns the entity has no component for are left out
+    pub values: BTreeMap<String, Value>,
+    #[serde(default)]
+    pub notes: BTreeMap<String, String>,
+}
+
 #[test]
-fn test() {
-    let x: RawReplayInfo = ron::from_str(r#"(pretty_name: "Foo")"#).unwrap();
-    dbg!(x);
+fn replay_info_round_trips() {
+    let info = ReplayInfo {
+        raw_name: "2024_any%.demo".to_string(),
+        pretty_name: "First sub 10".to_string(),
+    };
+    let text = ron::to_string(&info).unwrap();
+    assert_eq!(ron::from_str::<ReplayInfo>(&text).unwrap(), info);
+
+    let raw = info.to_raw();
+    assert_eq!(ReplayInfo::from_parts(info.raw_name.clone(), raw), info);
+    let raw: RawReplayInfo = ron::from_str(r#"(pretty_name: "Foo")"#).unwrap();
+    assert_eq!(raw.pretty_name, "Foo");
+
+    // Without a pretty name one is derived from the file name
+    let derived: ReplayInfo = ron::from_str(r#"(raw_name: "2024_any%.demo")"#).unwrap();
+    assert_eq!(derived.pretty_name, "2024 any%");
+    assert_eq!(
+        ReplayInfo::from_raw("no_ext".to_string()).pretty_name,
+        "no ext"
+    );
 }
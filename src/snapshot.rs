@@ -0,0 +1,73 @@
+use unicode_width::UnicodeWidthStr;
+
+/// Renders `header` and `rows` as a box-drawn table, e.g. for pasting into a chat message.
+/// Every row is expected to have one cell per header entry.
+pub fn render_table(header: &[String], rows: &[Vec<String>]) -> String {
+    // Multi-line cells would break the box so flatten them onto one line
+    let flatten = |row: &[String]| {
+        row.iter()
+            .map(|cell| cell.replace('\n', " "))
+            .collect::<Vec<_>>()
+    };
+    let header = flatten(header);
+    let rows = rows.iter().map(|row| flatten(row)).collect::<Vec<_>>();
+
+    let mut widths = header.iter().map(|cell| cell.width()).collect::<Vec<_>>();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.width());
+        }
+    }
+
+    let mut out = String::new();
+    push_border(&mut out, &widths, ['┌', '┬', '┐']);
+    push_row(&mut out, &widths, &header);
+    push_border(&mut out, &widths, ['├', '┼', '┤']);
+    for row in &rows {
+        push_row(&mut out, &widths, row);
+    }
+    push_border(&mut out, &widths, ['└', '┴', '┘']);
+    out
+}
+
+fn push_border(out: &mut String, widths: &[usize], [left, mid, right]: [char; 3]) {
+    out.push(left);
+    for (n, width) in widths.iter().enumerate() {
+        if n != 0 {
+            out.push(mid);
+        }
+        out.extend(std::iter::repeat_n('─', width + 2));
+    }
+    out.push(right);
+    out.push('\n');
+}
+
+fn push_row(out: &mut String, widths: &[usize], row: &[String]) {
+    out.push('│');
+    for (width, cell) in widths.iter().zip(row) {
+        out.push(' ');
+        out.push_str(cell);
+        out.extend(std::iter::repeat_n(' ', width - cell.width() + 1));
+        out.push('│');
+    }
+    out.push('\n');
+}
+
+#[test]
+fn renders_box_table() {
+    let header = ["Name".to_string(), "PB".to_string()];
+    let rows = [
+        vec!["aaa".to_string(), "✓".to_string()],
+        vec!["a".to_string(), "".to_string()],
+    ];
+
+    assert_eq!(
+        render_table(&header, &rows),
+        "┌──────┬────┐\n\
+         │ Name │ PB │\n\
+         ├──────┼────┤\n\
+         │ aaa  │ ✓  │\n\
+         │ a    │    │\n\
+         └──────┴────┘\n"
+    );
+}
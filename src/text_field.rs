@@ -0,0 +1,451 @@
+use std::ops::Range;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{
+    style::Stylize,
+    text::{Line, Span},
+};
+use tui_input::{Input, InputRequest, StateChanged, backend::crossterm::EventHandler};
+
+/// A single line [`Input`] that also supports selecting a range of text with Shift+arrows
+/// or Ctrl+A, which typing/Backspace/Delete then replace
+#[derive(Default, Debug, Clone)]
+pub struct TextField {
+    input: Input,
+    /// Where the selection started, the other end is always the cursor
+    anchor: Option<usize>,
+}
+
+impl TextField {
+    pub fn new(value: String) -> Self {
+        Self {
+            input: Input::new(value),
+            anchor: None,
+        }
+    }
+
+    /// The field with its cursor moved to char `cursor`
+    pub fn with_cursor(self, cursor: usize) -> Self {
+        Self {
+            input: self.input.with_cursor(cursor),
+            anchor: None,
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        self.input.value()
+    }
+
+    /// Cursor position in chars
+    pub fn cursor(&self) -> usize {
+        self.input.cursor()
+    }
+
+    /// Cursor position in terminal columns, for placing the terminal's cursor
+    pub fn visual_cursor(&self) -> usize {
+        self.input.visual_cursor()
+    }
+
+    /// The selected chars, `None` if nothing is selected
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.anchor?;
+        let cursor = self.cursor();
+        (anchor != cursor).then(|| anchor.min(cursor)..anchor.max(cursor))
+    }
+
+    /// Applies a key press or pasted text, returning `None` if it wasn't handled
+    pub fn handle_event(&mut self, event: &Event) -> Option<StateChanged> {
+        if let Event::Paste(text) = event {
+            // The field is a single line so pasted line breaks become spaces
+            let text = text.replace("\r\n", " ").replace(['\r', '\n'], " ");
+            let cursor = self.cursor();
+            let selection = self.selection().unwrap_or(cursor..cursor);
+            self.anchor = None;
+            return Some(self.replace(selection, &text));
+        }
+
+        let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press | KeyEventKind::Repeat,
+            ..
+        }) = *event
+        else {
+            return None;
+        };
+
+        let shift = modifiers.contains(KeyModifiers::SHIFT);
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        let alt = modifiers.contains(KeyModifiers::ALT);
+        let word = match code {
+            KeyCode::Left if ctrl => Some(self.prev_word()),
+            KeyCode::Right if ctrl => Some(self.next_word()),
+            KeyCode::Char('b') if alt => Some(self.prev_word()),
+            KeyCode::Char('f') if alt => Some(self.next_word()),
+            KeyCode::Char('w') if ctrl => {
+                let cursor = self.cursor();
+                let selection = self.selection().unwrap_or(self.prev_word()..cursor);
+                self.anchor = None;
+                return Some(self.replace(selection, ""));
+            }
+            KeyCode::Char('u') if ctrl => {
+                self.anchor = None;
+                return Some(self.replace(0..self.value().chars().count(), ""));
+            }
+            _ => None,
+        };
+        if let Some(target) = word {
+            if shift {
+                self.anchor.get_or_insert(self.input.cursor());
+            } else {
+                self.anchor = None;
+            }
+            self.input = std::mem::take(&mut self.input).with_cursor(target);
+            return Some(StateChanged {
+                value: false,
+                cursor: true,
+            });
+        }
+
+        let extend = match code {
+            KeyCode::Char('a') if ctrl => {
+                self.anchor = Some(0);
+                self.input.handle(InputRequest::GoToEnd);
+                return Some(StateChanged {
+                    value: false,
+                    cursor: true,
+                });
+            }
+            KeyCode::Left if shift => Some(InputRequest::GoToPrevChar),
+            KeyCode::Right if shift => Some(InputRequest::GoToNextChar),
+            KeyCode::Home if shift => Some(InputRequest::GoToStart),
+            KeyCode::End if shift => Some(InputRequest::GoToEnd),
+            _ => None,
+        };
+        if let Some(request) = extend {
+            self.anchor.get_or_insert(self.input.cursor());
+            return self.input.handle(request);
+        }
+
+        let Some(selection) = self.selection() else {
+            self.anchor = None;
+            return self.input.handle_event(event);
+        };
+        self.anchor = None;
+
+        match code {
+            KeyCode::Char(c) if !ctrl => Some(self.replace(selection, &c.to_string())),
+            KeyCode::Backspace | KeyCode::Delete => Some(self.replace(selection, "")),
+            _ => self.input.handle_event(event),
+        }
+    }
+
+    /// Where the word before the cursor starts, words being separated by whitespace
+    fn prev_word(&self) -> usize {
+        let chars = self.input.value().chars().collect::<Vec<_>>();
+        let mut idx = self.cursor();
+        while idx > 0 && chars[idx - 1].is_whitespace() {
+            idx -= 1;
+        }
+        while idx > 0 && !chars[idx - 1].is_whitespace() {
+            idx -= 1;
+        }
+        idx
+    }
+
+    /// Where the word after the cursor ends, words being separated by whitespace
+    fn next_word(&self) -> usize {
+        let chars = self.input.value().chars().collect::<Vec<_>>();
+        let mut idx = self.cursor();
+        while idx < chars.len() && chars[idx].is_whitespace() {
+            idx += 1;
+        }
+        while idx < chars.len() && !chars[idx].is_whitespace() {
+            idx += 1;
+        }
+        idx
+    }
+
+    fn replace(&mut self, selection: Range<usize>, with: &str) -> StateChanged {
+        let chars = self.input.value().chars();
+        let value = chars
+            .clone()
+            .take(selection.start)
+            .chain(with.chars())
+            .chain(chars.skip(selection.end))
+            .collect::<String>();
+        let cursor = selection.start + with.chars().count();
+        self.input = Input::new(value).with_cursor(cursor);
+        StateChanged {
+            value: true,
+            cursor: true,
+        }
+    }
+
+    /// The value with the selection drawn reversed
+    pub fn line(&self) -> Line<'_> {
+        let value = self.input.value();
+        let Some(selection) = self.selection() else {
+            return Line::raw(value);
+        };
+
+        let byte = |char_idx| {
+            value
+                .char_indices()
+                .nth(char_idx)
+                .map_or(value.len(), |(idx, _)| idx)
+        };
+        let (start, end) = (byte(selection.start), byte(selection.end));
+        Line::from_iter([
+            Span::raw(&value[..start]),
+            Span::raw(&value[start..end]).reversed(),
+            Span::raw(&value[end..]),
+        ])
+    }
+}
+
+/// A multi-line value edited as a [`TextField`] per line. Enter breaks the line at the
+/// cursor, Backspace at the start of a line joins it onto the one above.
+#[derive(Default, Debug, Clone)]
+pub struct TextArea {
+    lines: Vec<TextField>,
+    /// The line the cursor is on
+    row: usize,
+}
+
+impl TextArea {
+    /// Starts with the cursor at the end of `value`
+    pub fn new(value: &str) -> Self {
+        let lines = value
+            .split('\n')
+            .map(|line| TextField::new(line.to_string()))
+            .collect::<Vec<_>>();
+        let row = lines.len() - 1;
+        Self { lines, row }
+    }
+
+    pub fn value(&self) -> String {
+        let lines = self.lines.iter().map(TextField::value);
+        lines.collect::<Vec<_>>().join("\n")
+    }
+
+    pub fn lines(&self) -> &[TextField] {
+        &self.lines
+    }
+
+    /// The line the cursor is on
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    /// Applies a key press or pasted text, returning `None` if it wasn't handled
+    pub fn handle_event(&mut self, event: &Event) -> Option<StateChanged> {
+        if let Event::Paste(text) = event {
+            self.insert(&text.replace("\r\n", "\n").replace('\r', "\n"));
+            return Some(StateChanged {
+                value: true,
+                cursor: true,
+            });
+        }
+
+        let Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press | KeyEventKind::Repeat,
+            ..
+        }) = *event
+        else {
+            return None;
+        };
+
+        let line = &self.lines[self.row];
+        let selected = line.selection().is_some();
+        let target = match code {
+            KeyCode::Enter => {
+                self.insert("\n");
+                return Some(StateChanged {
+                    value: true,
+                    cursor: true,
+                });
+            }
+            KeyCode::Up if self.row > 0 => self.row - 1,
+            KeyCode::Down if self.row + 1 < self.lines.len() => self.row + 1,
+            KeyCode::Backspace if !selected && line.cursor() == 0 && self.row > 0 => {
+                return Some(self.join(self.row - 1));
+            }
+            KeyCode::Delete
+                if !selected
+                    && line.cursor() == line.value().chars().count()
+                    && self.row + 1 < self.lines.len() =>
+            {
+                return Some(self.join(self.row));
+            }
+            KeyCode::Up | KeyCode::Down => return None,
+            _ => return self.lines[self.row].handle_event(event),
+        };
+
+        // Up and Down keep the cursor in the same column where the line is long enough
+        let cursor = line.cursor();
+        self.row = target;
+        let line = std::mem::take(&mut self.lines[self.row]);
+        let cursor = cursor.min(line.value().chars().count());
+        self.lines[self.row] = line.with_cursor(cursor);
+        Some(StateChanged {
+            value: false,
+            cursor: true,
+        })
+    }
+
+    /// Replaces the selection or inserts at the cursor, splitting the line wherever `text` has
+    /// a line break
+    fn insert(&mut self, text: &str) {
+        let line = &self.lines[self.row];
+        let cursor = line.cursor();
+        let selection = line.selection().unwrap_or(cursor..cursor);
+        let chars = line.value().chars().collect::<Vec<_>>();
+
+        let mut pieces = text.split('\n').map(str::to_string).collect::<Vec<_>>();
+        let last = pieces.len() - 1;
+        pieces[0].insert_str(0, &chars[..selection.start].iter().collect::<String>());
+        let cursor = pieces[last].chars().count();
+        pieces[last].extend(&chars[selection.end..]);
+
+        self.lines
+            .splice(self.row..=self.row, pieces.into_iter().map(TextField::new));
+        self.row += last;
+        let line = std::mem::take(&mut self.lines[self.row]);
+        self.lines[self.row] = line.with_cursor(cursor);
+    }
+
+    /// Moves line `row + 1` onto the end of line `row`, leaving the cursor where they meet
+    fn join(&mut self, row: usize) -> StateChanged {
+        let next = self.lines.remove(row + 1);
+        let cursor = self.lines[row].value().chars().count();
+        let joined = format!("{}{}", self.lines[row].value(), next.value());
+        self.lines[row] = TextField::new(joined).with_cursor(cursor);
+        self.row = row;
+        StateChanged {
+            value: true,
+            cursor: true,
+        }
+    }
+}
+
+#[cfg(test)]
+fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+    Event::Key(KeyEvent::new(code, modifiers))
+}
+
+#[test]
+fn typing_replaces_selection() {
+    let mut field = TextField::new("any% glitchless".to_string());
+    field.handle_event(&key(KeyCode::Home, KeyModifiers::NONE));
+    for _ in 0..4 {
+        field.handle_event(&key(KeyCode::Right, KeyModifiers::SHIFT));
+    }
+    assert_eq!(field.selection(), Some(0..4));
+
+    field.handle_event(&key(KeyCode::Char('X'), KeyModifiers::SHIFT));
+    assert_eq!(field.value(), "X glitchless");
+    assert_eq!(field.cursor(), 1);
+    assert_eq!(field.selection(), None);
+}
+
+#[test]
+fn select_all_then_delete_clears() {
+    let mut field = TextField::new("120 star".to_string());
+    field.handle_event(&key(KeyCode::Char('a'), KeyModifiers::CONTROL));
+    assert_eq!(field.selection(), Some(0..8));
+
+    field.handle_event(&key(KeyCode::Delete, KeyModifiers::NONE));
+    assert_eq!(field.value(), "");
+
+    // Without a selection keys go straight to the input
+    field.handle_event(&key(KeyCode::Char('1'), KeyModifiers::NONE));
+    field.handle_event(&key(KeyCode::Left, KeyModifiers::NONE));
+    assert_eq!(field.value(), "1");
+    assert_eq!(field.cursor(), 0);
+}
+
+#[test]
+fn words_are_whitespace_delimited() {
+    let mut field = TextField::new("any%  no-clip run".to_string());
+    field.handle_event(&key(KeyCode::Home, KeyModifiers::NONE));
+
+    field.handle_event(&key(KeyCode::Right, KeyModifiers::CONTROL));
+    assert_eq!(field.cursor(), 4);
+    field.handle_event(&key(KeyCode::Char('f'), KeyModifiers::ALT));
+    assert_eq!(field.cursor(), 13);
+    field.handle_event(&key(
+        KeyCode::Left,
+        KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+    ));
+    assert_eq!(field.selection(), Some(6..13));
+    field.handle_event(&key(KeyCode::Char('b'), KeyModifiers::ALT));
+    assert_eq!((field.cursor(), field.selection()), (0, None));
+
+    field.handle_event(&key(KeyCode::End, KeyModifiers::NONE));
+    field.handle_event(&key(KeyCode::Char('w'), KeyModifiers::CONTROL));
+    assert_eq!(field.value(), "any%  no-clip ");
+    field.handle_event(&key(KeyCode::Char('w'), KeyModifiers::CONTROL));
+    assert_eq!(field.value(), "any%  ");
+}
+
+#[test]
+fn ctrl_u_clears_the_field() {
+    let mut field = TextField::new("any% no clip".to_string()).with_cursor(4);
+    field.handle_event(&key(KeyCode::Char('u'), KeyModifiers::CONTROL));
+    assert_eq!(field.value(), "");
+    assert_eq!(field.cursor(), 0);
+}
+
+#[test]
+fn paste_inserts_one_line() {
+    let mut field = TextField::new("16 star".to_string());
+    field.handle_event(&key(KeyCode::Home, KeyModifiers::NONE));
+    field.handle_event(&key(KeyCode::Right, KeyModifiers::SHIFT));
+    field.handle_event(&key(KeyCode::Right, KeyModifiers::SHIFT));
+
+    field.handle_event(&Event::Paste("70\r\nor\n1".to_string()));
+    assert_eq!(field.value(), "70 or 1 star");
+    assert_eq!(field.cursor(), 7);
+    assert_eq!(field.selection(), None);
+}
+
+#[test]
+fn text_area_splits_and_joins_lines() {
+    let mut area = TextArea::new("any%\nno clip");
+    assert_eq!((area.row(), area.lines()[1].cursor()), (1, 7));
+
+    area.handle_event(&key(KeyCode::Left, KeyModifiers::NONE));
+    area.handle_event(&key(KeyCode::Left, KeyModifiers::NONE));
+    area.handle_event(&key(KeyCode::Enter, KeyModifiers::NONE));
+    assert_eq!(area.value(), "any%\nno cl\nip");
+    assert_eq!((area.row(), area.lines()[2].cursor()), (2, 0));
+
+    // Up keeps the column where it can
+    area.handle_event(&key(KeyCode::End, KeyModifiers::NONE));
+    area.handle_event(&key(KeyCode::Up, KeyModifiers::NONE));
+    assert_eq!((area.row(), area.lines()[1].cursor()), (1, 2));
+    area.handle_event(&key(KeyCode::Up, KeyModifiers::NONE));
+    area.handle_event(&key(KeyCode::End, KeyModifiers::NONE));
+    area.handle_event(&key(KeyCode::Delete, KeyModifiers::NONE));
+    assert_eq!(area.value(), "any%no cl\nip");
+
+    area.handle_event(&key(KeyCode::Down, KeyModifiers::NONE));
+    area.handle_event(&key(KeyCode::Home, KeyModifiers::NONE));
+    area.handle_event(&key(KeyCode::Backspace, KeyModifiers::NONE));
+    assert_eq!(area.value(), "any%no clip");
+    assert_eq!((area.row(), area.lines()[0].cursor()), (0, 9));
+}
+
+#[test]
+fn text_area_paste_keeps_line_breaks() {
+    let mut area = TextArea::new("16 star");
+    area.handle_event(&key(KeyCode::Home, KeyModifiers::NONE));
+    area.handle_event(&key(KeyCode::Right, KeyModifiers::SHIFT));
+    area.handle_event(&key(KeyCode::Right, KeyModifiers::SHIFT));
+
+    area.handle_event(&Event::Paste("70\r\nor\n1".to_string()));
+    assert_eq!(area.value(), "70\nor\n1 star");
+    assert_eq!((area.row(), area.lines()[2].cursor()), (2, 1));
+}
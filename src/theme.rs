@@ -0,0 +1,164 @@
+use std::path::Path;
+
+use ratatui::style::{Color, palette::tailwind};
+use serde::Deserialize;
+
+/// Where a custom theme is read from, next to the database file
+pub const THEME_FILE: &str = "ddreplayer-theme.ron";
+
+/// The colours the viewer and editor are drawn with. In [`THEME_FILE`] colours are names like
+/// `red` or hex codes like `#fbbf24`, and any left out are taken from [`Theme::dark`].
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    /// Shown when switching themes, custom themes are always called `custom`
+    #[serde(skip)]
+    pub name: &'static str,
+    /// Cell text, and the status bar's
+    pub text: Color,
+    /// Entity ids and other less important text
+    pub dim: Color,
+    /// The header and footer rows, and viewer messages
+    pub header_fg: Color,
+    pub header_bg: Color,
+    /// Rows alternate between these
+    pub row_bg: Color,
+    pub alt_row_bg: Color,
+    pub marked_row_bg: Color,
+    pub selected_row: Color,
+    pub selected_column: Color,
+    pub selected_cell: Color,
+    pub status_bg: Color,
+    pub popup_bg: Color,
+    /// The in place cell editor
+    pub input_bg: Color,
+    /// The focused editor field's name
+    pub field_focus: Color,
+    /// Rejected editor fields and their errors
+    pub error: Color,
+    /// Editor buttons while they're focused
+    pub remove_focus: Color,
+    pub add_focus: Color,
+    pub save_focus: Color,
+    /// Editor buttons while they aren't focused, which need to stand out from the terminal's
+    /// background as much as the focused ones
+    pub button: Color,
+    /// Entries in the editor's addable label list
+    pub list: Color,
+    pub list_focus: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            name: "dark",
+            text: tailwind::SLATE.c200,
+            dim: tailwind::SLATE.c500,
+            header_fg: tailwind::SLATE.c200,
+            header_bg: tailwind::BLUE.c900,
+            row_bg: tailwind::SLATE.c950,
+            alt_row_bg: tailwind::SLATE.c900,
+            marked_row_bg: tailwind::INDIGO.c800,
+            selected_row: tailwind::BLUE.c400,
+            selected_column: tailwind::BLUE.c400,
+            selected_cell: tailwind::BLUE.c600,
+            status_bg: tailwind::SLATE.c800,
+            popup_bg: tailwind::SLATE.c900,
+            input_bg: tailwind::SLATE.c700,
+            field_focus: Color::Yellow,
+            error: Color::Red,
+            remove_focus: Color::Red,
+            add_focus: Color::Blue,
+            save_focus: Color::Green,
+            button: tailwind::SLATE.c400,
+            list: Color::White,
+            list_focus: Color::Green,
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            name: "light",
+            text: tailwind::SLATE.c900,
+            dim: tailwind::SLATE.c500,
+            header_fg: tailwind::SLATE.c50,
+            header_bg: tailwind::BLUE.c700,
+            row_bg: tailwind::SLATE.c50,
+            alt_row_bg: tailwind::SLATE.c200,
+            marked_row_bg: tailwind::INDIGO.c200,
+            selected_row: tailwind::BLUE.c700,
+            selected_column: tailwind::BLUE.c700,
+            selected_cell: tailwind::BLUE.c900,
+            status_bg: tailwind::SLATE.c300,
+            popup_bg: tailwind::SLATE.c100,
+            input_bg: tailwind::SLATE.c300,
+            field_focus: tailwind::AMBER.c700,
+            error: tailwind::RED.c700,
+            remove_focus: tailwind::RED.c700,
+            add_focus: tailwind::BLUE.c700,
+            save_focus: tailwind::GREEN.c700,
+            button: tailwind::SLATE.c500,
+            list: tailwind::SLATE.c900,
+            list_focus: tailwind::GREEN.c700,
+        }
+    }
+
+    /// Plain terminal colours only, with nothing drawn dim
+    pub fn high_contrast() -> Self {
+        Self {
+            name: "high contrast",
+            text: Color::White,
+            dim: Color::White,
+            header_fg: Color::Black,
+            header_bg: Color::White,
+            row_bg: Color::Black,
+            alt_row_bg: Color::Black,
+            marked_row_bg: Color::Blue,
+            selected_row: Color::LightYellow,
+            selected_column: Color::LightYellow,
+            selected_cell: Color::LightCyan,
+            status_bg: Color::Black,
+            popup_bg: Color::Black,
+            input_bg: Color::Blue,
+            field_focus: Color::LightYellow,
+            error: Color::LightRed,
+            remove_focus: Color::LightRed,
+            add_focus: Color::LightCyan,
+            save_focus: Color::LightGreen,
+            button: Color::White,
+            list: Color::White,
+            list_focus: Color::LightGreen,
+        }
+    }
+
+    /// The themes `T` cycles through, in order
+    pub fn built_in() -> Vec<Self> {
+        vec![Self::dark(), Self::light(), Self::high_contrast()]
+    }
+
+    pub fn load_from_path(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("failed to read `{}`: {err}", path.display()))?;
+        let theme: Self = ron::from_str(&text)
+            .map_err(|err| format!("failed to parse `{}`: {err}", path.display()))?;
+        Ok(Self {
+            name: "custom",
+            ..theme
+        })
+    }
+}
+
+#[test]
+fn custom_themes_start_from_dark() {
+    let theme: Theme = ron::from_str(r##"(header_bg: "#ff0000", button: "gray")"##).unwrap();
+    assert_eq!(theme.header_bg, Color::Rgb(255, 0, 0));
+    assert_eq!(theme.button, Color::Gray);
+    assert_eq!(theme.row_bg, Theme::dark().row_bg);
+    assert!(ron::from_str::<Theme>(r#"(text: "not a colour")"#).is_err());
+}
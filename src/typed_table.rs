@@ -0,0 +1,177 @@
+use std::{
+    alloc::Layout,
+    any::type_name,
+    mem::{ManuallyDrop, MaybeUninit},
+    ptr::slice_from_raw_parts,
+};
+
+use decentralecs::{Entity, World};
+use decentralecs_dynamic::DynamicTable;
+
+/// The biggest component a table stores, the largest of the types columns, row order and
+/// timestamps are stored as. A bigger one comes from a broken layout rather than a real column.
+pub const MAX_COMPONENT_SIZE: usize = {
+    let sizes = [
+        size_of::<String>(),
+        size_of::<f64>(),
+        size_of::<i64>(),
+        size_of::<i16>(),
+        size_of::<u16>(),
+        size_of::<bool>(),
+    ];
+    let (mut max, mut n) = (0, 0);
+    while n < sizes.len() {
+        if sizes[n] > max {
+            max = sizes[n];
+        }
+        n += 1;
+    }
+    max
+};
+
+/// A [`DynamicTable`] that remembers the layout it was created with, so components can be moved
+/// in and out as typed values rather than raw bytes
+pub struct TypedTable {
+    table: DynamicTable,
+    layout: Layout,
+}
+
+impl TypedTable {
+    /// Fails for components bigger than [`MAX_COMPONENT_SIZE`]
+    pub fn new(world: &mut World<'static>, layout: Layout) -> Result<Self, String> {
+        if layout.size() > MAX_COMPONENT_SIZE {
+            return Err(format!(
+                "{} byte components are bigger than the {MAX_COMPONENT_SIZE} a table can store",
+                layout.size()
+            ));
+        }
+        Ok(Self {
+            table: DynamicTable::new(world, layout),
+            layout,
+        })
+    }
+
+    /// Moves `value` into the table as `entity`'s component. The table owns it from then on,
+    /// [`TypedTable::remove`] hands it back so it can be dropped. An existing component is
+    /// overwritten without being dropped.
+    ///
+    /// Panics if `T` doesn't have the layout the table was created with.
+    pub fn insert<T>(&mut self, world: &mut World<'static>, entity: Entity, value: T) {
+        self.check_layout::<T>();
+        let value = ManuallyDrop::new(value);
+        // SAFETY: `value` is owned here so nothing can mutate it through an `UnsafeCell` while
+        // its bytes are copied, and it's never dropped so the copy is the only owner
+        let bytes = unsafe {
+            &*slice_from_raw_parts(
+                &value as *const ManuallyDrop<T> as *const MaybeUninit<u8>,
+                size_of::<T>(),
+            )
+        };
+        self.table.insert_component(world, entity, bytes);
+    }
+
+    /// Panics if `T` doesn't have the layout the table was created with, which catches a
+    /// column being accessed as the wrong type before any bytes are reinterpreted. Types with
+    /// the same layout, e.g. `i16` and `u16`, can't be told apart.
+    fn check_layout<T>(&self) {
+        assert_eq!(
+            Layout::new::<T>(),
+            self.layout,
+            "a {} doesn't fit in this table",
+            type_name::<T>()
+        );
+    }
+
+    pub fn contains(&self, world: &World<'_>, entity: Entity) -> bool {
+        self.table.get_component(world, entity).is_some()
+    }
+
+    /// The layout every component in the table has
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// How many bytes are stored for `entity`'s component, `None` if it has none
+    pub fn component_size(&self, world: &World<'_>, entity: Entity) -> Option<usize> {
+        Some(self.table.get_component(world, entity)?.len())
+    }
+
+    /// Panics if `T` doesn't have the layout the table was created with.
+    ///
+    /// SAFETY: every component in the table must be a `T`
+    pub unsafe fn get<'a, T>(&'a self, world: &'a World<'_>, entity: Entity) -> Option<&'a T> {
+        self.check_layout::<T>();
+        let raw_data = self.table.get_component(world, entity)?;
+        Some(unsafe { &*(raw_data as *const [MaybeUninit<u8>] as *const T) })
+    }
+
+    /// Takes `entity`'s component back out of the table. Panics if `T` doesn't have the layout
+    /// the table was created with.
+    ///
+    /// SAFETY: every component in the table must be a `T`
+    pub unsafe fn remove<T>(&mut self, world: &mut World<'static>, entity: Entity) -> Option<T> {
+        // The bytes are thrown away straight after so the returned value is the only owner
+        let value = unsafe {
+            self.get::<T>(world, entity)
+                .map(|value| std::ptr::read(value))
+        };
+        self.table.remove_component(world, entity);
+        value
+    }
+
+    /// Removes `entity`'s component without dropping it, which is only right for `Copy` types
+    pub fn forget(&mut self, world: &mut World<'static>, entity: Entity) {
+        self.table.remove_component(world, entity);
+    }
+}
+
+#[test]
+fn moves_values_in_and_out() {
+    let mut world = World::new();
+    let mut table = TypedTable::new(&mut world, Layout::new::<String>()).unwrap();
+    let entity = world.spawn().id();
+
+    table.insert(&mut world, entity, "hello".to_string());
+    assert_eq!(
+        unsafe { table.get::<String>(&world, entity) },
+        Some(&"hello".to_string())
+    );
+    assert_eq!(
+        unsafe { table.remove::<String>(&mut world, entity) },
+        Some("hello".to_string())
+    );
+    assert!(!table.contains(&world, entity));
+    assert_eq!(unsafe { table.remove::<String>(&mut world, entity) }, None);
+}
+
+#[test]
+#[should_panic = "a u16 doesn't fit in this table"]
+fn insert_checks_layout() {
+    let mut world = World::new();
+    let mut table = TypedTable::new(&mut world, Layout::new::<i64>()).unwrap();
+    let entity = world.spawn().id();
+    table.insert(&mut world, entity, 3_u16);
+}
+
+#[test]
+#[should_panic = "a alloc::string::String doesn't fit in this table"]
+fn get_checks_layout() {
+    let mut world = World::new();
+    let mut table = TypedTable::new(&mut world, Layout::new::<i16>()).unwrap();
+    let entity = world.spawn().id();
+    table.insert(&mut world, entity, 3_i16);
+    _ = unsafe { table.get::<String>(&world, entity) };
+}
+
+#[test]
+fn oversized_components_are_refused() {
+    let mut world = World::new();
+    let layout = Layout::from_size_align(MAX_COMPONENT_SIZE + 1, 1).unwrap();
+    assert_eq!(
+        TypedTable::new(&mut world, layout).err().unwrap(),
+        format!(
+            "{} byte components are bigger than the {MAX_COMPONENT_SIZE} a table can store",
+            MAX_COMPONENT_SIZE + 1
+        )
+    );
+}
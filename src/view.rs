@@ -0,0 +1,419 @@
+use std::cmp::Ordering;
+
+use ddreplayer::{
+    DECIMAL_PLACES, Label, LabelDataKind, ReplayDB, SortDirection, Value, compare_values,
+    format_timestamp,
+};
+use decentralecs::Entity;
+use regex::Regex;
+use unicode_width::UnicodeWidthStr;
+
+use crate::{MAX_COLUMN_WIDTH, NOTE_MARKER, snapshot};
+
+/// Which rows the "missing values" quick view keeps
+#[derive(Clone, Debug, PartialEq)]
+pub enum MissingFilter {
+    /// Rows lacking a value in at least one column
+    Any,
+    /// Rows lacking a value in this column
+    Column(Label),
+}
+
+/// Viewer display settings that don't affect the stored data
+#[derive(Clone, Debug)]
+pub struct ViewOptions {
+    /// Render rows on a single line without any padding, overriding `row_height` and
+    /// `cell_padding`
+    pub compact: bool,
+    pub row_height: u16,
+    /// Blank lines above and below the content of each cell
+    pub cell_padding: u16,
+    /// Display Number cells in hexadecimal, doesn't affect what the editor accepts
+    pub hex_numbers: bool,
+    /// Show each entity's created/modified timestamps after the label columns
+    pub show_timestamps: bool,
+    /// Show each entity's id before the label columns, see [`crate::entity_id`]
+    pub show_ids: bool,
+    /// Only show rows with missing values
+    pub missing: Option<MissingFilter>,
+    /// Only show rows with a value containing this, ignoring case, see
+    /// [`matches_search`]
+    pub search: Option<String>,
+    /// Match `search` as a regular expression rather than a substring
+    pub regex_search: bool,
+    /// Order rows by these columns' values rather than their natural order, later columns only
+    /// ordering rows the earlier ones consider equal
+    pub sort: Vec<(Label, SortDirection)>,
+}
+
+impl Default for ViewOptions {
+    fn default() -> Self {
+        Self {
+            compact: false,
+            row_height: 4,
+            cell_padding: 1,
+            hex_numbers: false,
+            show_timestamps: false,
+            show_ids: false,
+            missing: None,
+            search: None,
+            regex_search: false,
+            sort: Vec::new(),
+        }
+    }
+}
+
+impl ViewOptions {
+    /// `search` compiled as a regular expression, when in regex mode
+    pub fn search_regex(&self) -> Option<Result<Regex, regex::Error>> {
+        let query = self.search.as_ref().filter(|_| self.regex_search)?;
+        Some(Regex::new(query))
+    }
+
+    pub fn row_height(&self) -> u16 {
+        if self.compact { 1 } else { self.row_height }
+    }
+
+    pub fn cell_padding(&self) -> u16 {
+        if self.compact { 0 } else { self.cell_padding }
+    }
+
+    /// Sorts by `label` after the existing sort keys, or if it's already one reverses it, and
+    /// once it's descending removes it
+    pub fn toggle_sort_key(&mut self, label: Label) {
+        match self.sort.iter().position(|(sorted, _)| *sorted == label) {
+            None => self.sort.push((label, SortDirection::Ascending)),
+            Some(n) if self.sort[n].1 == SortDirection::Ascending => {
+                self.sort[n].1 = SortDirection::Descending;
+            }
+            Some(n) => _ = self.sort.remove(n),
+        }
+    }
+}
+
+/// How the viewer lays out a [`ReplayDB`]'s rows and columns for some [`ViewOptions`]
+pub trait ReplayDBView {
+    /// The entities `view` shows as rows, in natural order
+    fn visible_entities(&self, view: &ViewOptions) -> Vec<Entity>;
+
+    /// Which row `entity` is displayed on, `None` if `view` hides it
+    fn row_of(&self, entity: Entity, view: &ViewOptions) -> Option<usize>;
+
+    /// The number of rows in the viewer
+    fn row_count(&self, view: &ViewOptions) -> usize;
+
+    /// The `n`th entity in the order the viewer displays rows
+    fn nth_entity(&self, n: usize, view: &ViewOptions) -> Option<Entity>;
+
+    /// The names of the viewer's columns, sorted ones ending in ▲ or ▼ for their direction.
+    /// When sorting by several columns the arrows are numbered by priority, e.g. ▲¹ and ▼².
+    fn header_names(&self, view: &ViewOptions) -> Vec<String>;
+
+    /// Formats `entity`'s row the same way the viewer displays it, one cell per
+    /// [`ReplayDBView::header_names`] entry
+    fn row_cells(&self, entity: Entity, view: &ViewOptions) -> Vec<String>;
+
+    /// A summary of each [`ReplayDBView::header_names`] column over the visible rows: the sum and
+    /// mean of Number/Decimal columns and how many values other columns have. Rows without a
+    /// value for a column don't count towards it.
+    fn footer_cells(&self, view: &ViewOptions) -> Vec<String>;
+
+    /// `entity`'s shown cells joined with tabs in the order the viewer draws them, for pasting
+    /// into a spreadsheet. Cells are copied as by [`ReplayDBView::copy_text`] apart from FilePath
+    /// cells, which are just the path, and tabs and line breaks in a cell become spaces.
+    fn row_tsv(&self, entity: Entity, view: &ViewOptions) -> String;
+
+    /// The text copied for `entity`'s cell in the `col`th viewer column, what the viewer shows
+    /// or nothing if there's no value
+    fn copy_text(&self, entity: Entity, col: usize, view: &ViewOptions) -> String;
+
+    /// Display width of each [`ReplayDBView::header_names`] column, wide enough for the header,
+    /// footer and every visible cell but at most [`MAX_COLUMN_WIDTH`]
+    fn column_widths(&self, view: &ViewOptions) -> Vec<u16>;
+
+    /// Renders every row as a plain text box-drawn table matching the viewer
+    fn render_snapshot(&self, view: &ViewOptions) -> String;
+
+    /// Indices of the [`ReplayDBView::header_names`] columns the viewer draws, i.e. every one but
+    /// hidden labels, in the order they're drawn with pinned labels first
+    fn shown_columns(&self, view: &ViewOptions) -> Vec<usize>;
+
+    /// The shown column after (or before) `col` in the order they're drawn, staying put at
+    /// either end
+    fn step_column(&self, col: Option<usize>, view: &ViewOptions, forward: bool) -> usize;
+}
+
+impl ReplayDBView for ReplayDB {
+    fn visible_entities(&self, view: &ViewOptions) -> Vec<Entity> {
+        let mut entities = self.ordered_entities();
+        if let Some(filter) = &view.missing {
+            entities.retain(|&entity| is_missing(self, entity, filter));
+        }
+        match (&view.search, view.search_regex()) {
+            // Until the pattern is valid every row is shown
+            (_, Some(Err(_))) => (),
+            (_, Some(Ok(regex))) => {
+                entities
+                    .retain(|&entity| matches_search(self, entity, |text| regex.is_match(text)));
+            }
+            (Some(query), None) => {
+                let query = query.to_lowercase();
+                entities.retain(|&entity| {
+                    matches_search(self, entity, |text| text.to_lowercase().contains(&query))
+                });
+            }
+            (None, None) => (),
+        }
+        if !view.sort.is_empty() {
+            let mut keyed = entities
+                .into_iter()
+                .map(|entity| {
+                    let values = view
+                        .sort
+                        .iter()
+                        .map(|(label, _)| self.get_value(label, entity))
+                        .collect::<Vec<_>>();
+                    (values, entity)
+                })
+                .collect::<Vec<_>>();
+            keyed.sort_by(|(a, _), (b, _)| {
+                view.sort
+                    .iter()
+                    .zip(a.iter().zip(b))
+                    .map(|((label, direction), (a, b))| {
+                        compare_values(&label.data, a, b, *direction)
+                    })
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            });
+            entities = keyed.into_iter().map(|(_, entity)| entity).collect();
+        }
+        entities
+    }
+
+    fn row_of(&self, entity: Entity, view: &ViewOptions) -> Option<usize> {
+        self.visible_entities(view)
+            .iter()
+            .position(|&visible| visible == entity)
+    }
+
+    fn row_count(&self, view: &ViewOptions) -> usize {
+        self.visible_entities(view).len()
+    }
+
+    fn nth_entity(&self, n: usize, view: &ViewOptions) -> Option<Entity> {
+        self.visible_entities(view).get(n).copied()
+    }
+
+    fn header_names(&self, view: &ViewOptions) -> Vec<String> {
+        let mut names = self
+            .labels()
+            .iter()
+            .map(|label| {
+                let Some(n) = view.sort.iter().position(|(sorted, _)| sorted == label) else {
+                    return label.name.clone();
+                };
+                let arrow = match view.sort[n].1 {
+                    SortDirection::Ascending => "▲",
+                    SortDirection::Descending => "▼",
+                };
+                let priority = match view.sort.len() {
+                    1 => String::new(),
+                    _ => superscript(n + 1),
+                };
+                format!("{} {arrow}{priority}", label.name)
+            })
+            .collect::<Vec<_>>();
+        if view.show_timestamps {
+            names.extend(["Created".to_string(), "Modified".to_string()]);
+        }
+        names
+    }
+
+    fn row_cells(&self, entity: Entity, view: &ViewOptions) -> Vec<String> {
+        let mut cells = self
+            .handles()
+            .map(|(handle, _)| self.cell_text_at(handle, entity, view.hex_numbers))
+            .collect::<Vec<_>>();
+        if view.show_timestamps {
+            cells.extend(
+                [self.created_at(entity), self.modified_at(entity)]
+                    .map(|timestamp| timestamp.map(format_timestamp).unwrap_or_default()),
+            );
+        }
+        cells
+    }
+
+    fn footer_cells(&self, view: &ViewOptions) -> Vec<String> {
+        let entities = self.visible_entities(view);
+        let mut cells = self
+            .handles()
+            .map(|(handle, label)| {
+                let values = entities
+                    .iter()
+                    .filter_map(|&entity| self.value_at(handle, entity))
+                    .collect::<Vec<_>>();
+                let sum = values
+                    .iter()
+                    .map(|value| match value {
+                        Value::Number(n) => *n as f64,
+                        Value::Decimal(d) => *d,
+                        _ => 0.0,
+                    })
+                    .sum::<f64>();
+                let mean = sum / values.len() as f64;
+
+                match label.data {
+                    _ if values.is_empty() => String::new(),
+                    LabelDataKind::Number => format!("sum {sum}\navg {mean:.DECIMAL_PLACES$}"),
+                    LabelDataKind::Decimal => {
+                        format!("sum {sum:.DECIMAL_PLACES$}\navg {mean:.DECIMAL_PLACES$}")
+                    }
+                    _ => format!("count {}", values.len()),
+                }
+            })
+            .collect::<Vec<_>>();
+        if view.show_timestamps {
+            cells.extend([String::new(), String::new()]);
+        }
+        cells
+    }
+
+    fn row_tsv(&self, entity: Entity, view: &ViewOptions) -> String {
+        self.shown_columns(view)
+            .into_iter()
+            .map(|col| {
+                let value = self
+                    .labels()
+                    .get(col)
+                    .and_then(|label| self.get_value(label, entity));
+                let text = match value {
+                    Some(Value::FilePath(path)) => path,
+                    _ => self.copy_text(entity, col, view),
+                };
+                text.replace("\r\n", " ").replace(['\t', '\r', '\n'], " ")
+            })
+            .collect::<Vec<_>>()
+            .join("\t")
+    }
+
+    fn copy_text(&self, entity: Entity, col: usize, view: &ViewOptions) -> String {
+        match self.labels().get(col) {
+            Some(label) if self.get_value(label, entity).is_none() => String::new(),
+            _ => self
+                .row_cells(entity, view)
+                .into_iter()
+                .nth(col)
+                .unwrap_or_default(),
+        }
+    }
+
+    fn column_widths(&self, view: &ViewOptions) -> Vec<u16> {
+        let line_width = |text: &str| text.lines().map(|line| line.width()).max().unwrap_or(0);
+        let mut widths = self
+            .header_names(view)
+            .iter()
+            .zip(self.footer_cells(view))
+            .map(|(name, footer)| line_width(name).max(line_width(&footer)))
+            .collect::<Vec<_>>();
+
+        for entity in self.visible_entities(view) {
+            for (col, cell) in self.row_cells(entity, view).iter().enumerate() {
+                let has_note = self
+                    .labels()
+                    .get(col)
+                    .is_some_and(|label| self.note(entity, label).is_some());
+                let marker = if has_note { NOTE_MARKER.width() } else { 0 };
+                widths[col] = widths[col].max(line_width(cell) + marker);
+            }
+        }
+
+        widths
+            .into_iter()
+            .map(|width| width.min(MAX_COLUMN_WIDTH) as u16)
+            .collect()
+    }
+
+    fn render_snapshot(&self, view: &ViewOptions) -> String {
+        let header = self.header_names(view);
+        let rows = self
+            .visible_entities(view)
+            .into_iter()
+            .map(|entity| self.row_cells(entity, view))
+            .collect::<Vec<_>>();
+
+        snapshot::render_table(&header, &rows)
+    }
+
+    fn shown_columns(&self, view: &ViewOptions) -> Vec<usize> {
+        let timestamps = if view.show_timestamps { 2 } else { 0 };
+        let (mut pinned, rest): (Vec<_>, Vec<_>) = (0..self.labels().len() + timestamps)
+            .filter(|&col| {
+                self.labels()
+                    .get(col)
+                    .is_none_or(|label| self.is_visible(label))
+            })
+            .partition(|&col| self.labels().get(col).is_some_and(|l| self.is_pinned(l)));
+        pinned.extend(rest);
+        pinned
+    }
+
+    fn step_column(&self, col: Option<usize>, view: &ViewOptions, forward: bool) -> usize {
+        let shown = self.shown_columns(view);
+        let (first, last) = (shown[0], shown[shown.len() - 1]);
+        let Some(col) = col else {
+            return first;
+        };
+        match shown.iter().position(|&c| c == col) {
+            Some(n) if forward => shown.get(n + 1).copied().unwrap_or(last),
+            Some(n) => shown[n.saturating_sub(1)],
+            // Hidden, so the nearest shown column by index
+            None if forward => shown.into_iter().find(|&c| c > col).unwrap_or(last),
+            None => shown.into_iter().rev().find(|&c| c < col).unwrap_or(first),
+        }
+    }
+}
+
+/// Whether `entity` lacks a value for any column, or just the filtered one
+fn is_missing(db: &ReplayDB, entity: Entity, filter: &MissingFilter) -> bool {
+    match filter {
+        MissingFilter::Any => db
+            .handles()
+            .any(|(handle, _)| !db.has_value_at(handle, entity)),
+        MissingFilter::Column(label) => !db.has_value_at(db.handle(label), entity),
+    }
+}
+
+/// Whether `matches` accepts the text of any of `entity`'s values. Numbers are matched
+/// against their decimal representation so searching for `18` finds `182`.
+fn matches_search(db: &ReplayDB, entity: Entity, matches: impl Fn(&str) -> bool) -> bool {
+    db.handles().any(|(handle, _)| {
+        let text = match db.value_at(handle, entity) {
+            Some(Value::Number(n)) => n.to_string(),
+            Some(Value::Decimal(d)) => format!("{d:.DECIMAL_PLACES$}"),
+            Some(Value::Text(text) | Value::FilePath(text) | Value::Enum(text)) => text,
+            Some(Value::Bool(b)) => b.to_string(),
+            Some(Value::Unit) | None => return false,
+        };
+        matches(&text)
+    })
+}
+
+/// `n` written in superscript digits, e.g. `12` becomes `¹²`
+fn superscript(n: usize) -> String {
+    n.to_string()
+        .chars()
+        .map(|digit| match digit {
+            '1' => '¹',
+            '2' => '²',
+            '3' => '³',
+            '4' => '⁴',
+            '5' => '⁵',
+            '6' => '⁶',
+            '7' => '⁷',
+            '8' => '⁸',
+            '9' => '⁹',
+            _ => '⁰',
+        })
+        .collect()
+}
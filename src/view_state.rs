@@ -0,0 +1,263 @@
+use std::path::{Path, PathBuf};
+
+use ddreplayer::{ReplayDB, SortDirection};
+use serde::{Deserialize, Serialize};
+
+use crate::view::{MissingFilter, ViewOptions};
+
+/// Where the viewer's settings are kept between runs, next to the database file
+pub const VIEW_STATE_FILE: &str = "ddreplayer-view.ron";
+/// Where [`Recents`] is kept, next to [`VIEW_STATE_FILE`]
+pub const RECENTS_FILE: &str = "ddreplayer-recent.ron";
+/// How many databases [`Recents`] remembers
+pub const MAX_RECENTS: usize = 10;
+
+/// The parts of [`ViewOptions`] worth restoring on the next launch. Column order and
+/// visibility are part of the database's schema so they're saved with it instead, and the
+/// row height and padding come from the command line.
+///
+/// Columns are stored by name so a column that's been renamed or deleted since is dropped.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewState {
+    compact: bool,
+    hex_numbers: bool,
+    show_timestamps: bool,
+    show_ids: bool,
+    search: Option<String>,
+    regex_search: bool,
+    sort_keys: Vec<(String, SortDirection)>,
+    /// The one column rows were sorted by before they could be sorted by several, only read
+    /// from older files
+    #[serde(skip_serializing)]
+    sort: Option<(String, SortDirection)>,
+    missing: Option<MissingState>,
+}
+
+/// [`MissingFilter`] by column name
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum MissingState {
+    Any,
+    Column(String),
+}
+
+/// The databases opened before, most recently launched first, which Ctrl+Tab switches between.
+/// Several databases in one directory share a [`VIEW_STATE_FILE`] so each one's own settings
+/// are kept here too.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Recents {
+    files: Vec<RecentFile>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct RecentFile {
+    path: PathBuf,
+    /// The settings the database was last switched away from with, `None` if it hasn't been
+    view: Option<ViewState>,
+}
+
+impl Recents {
+    /// Moves `path` to the front of the list as it's just been launched, dropping the oldest
+    /// past [`MAX_RECENTS`]
+    pub fn opened(&mut self, path: &Path) {
+        let path = absolute(path);
+        let file = match self.files.iter().position(|file| file.path == path) {
+            Some(n) => self.files.remove(n),
+            None => RecentFile { path, view: None },
+        };
+        self.files.insert(0, file);
+        self.files.truncate(MAX_RECENTS);
+    }
+
+    /// Remembers `view` as the settings `path` was left with
+    pub fn set_view(&mut self, path: &Path, view: ViewState) {
+        let path = absolute(path);
+        match self.files.iter_mut().find(|file| file.path == path) {
+            Some(file) => file.view = Some(view),
+            None => self.files.push(RecentFile {
+                path,
+                view: Some(view),
+            }),
+        }
+    }
+
+    /// The settings `path` was left with, `None` if it's never been switched away from
+    pub fn view(&self, path: &Path) -> Option<&ViewState> {
+        let path = absolute(path);
+        let file = self.files.iter().find(|file| file.path == path)?;
+        file.view.as_ref()
+    }
+
+    /// The database after `path` in the list, wrapping around to the first. `None` if there
+    /// isn't another one.
+    pub fn next_after(&self, path: &Path) -> Option<&Path> {
+        let path = absolute(path);
+        let start = self
+            .files
+            .iter()
+            .position(|file| file.path == path)
+            .map_or(0, |n| n + 1);
+        self.files
+            .iter()
+            .cycle()
+            .skip(start)
+            .take(self.files.len())
+            .map(|file| file.path.as_path())
+            .find(|&next| next != path)
+    }
+
+    pub fn load_from_path(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("failed to read `{}`: {err}", path.display()))?;
+        ron::from_str(&text).map_err(|err| format!("failed to parse `{}`: {err}", path.display()))
+    }
+
+    pub fn save_to_path(&self, path: &Path) -> Result<(), String> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| format!("failed to serialize recent databases: {err}"))?;
+        std::fs::write(path, text)
+            .map_err(|err| format!("failed to write `{}`: {err}", path.display()))
+    }
+}
+
+/// `path` made absolute so it's found again from another working directory
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+impl ViewState {
+    pub fn from_view(view: &ViewOptions) -> Self {
+        Self {
+            compact: view.compact,
+            hex_numbers: view.hex_numbers,
+            show_timestamps: view.show_timestamps,
+            show_ids: view.show_ids,
+            search: view.search.clone(),
+            regex_search: view.regex_search,
+            sort_keys: view
+                .sort
+                .iter()
+                .map(|(label, direction)| (label.name.clone(), *direction))
+                .collect(),
+            sort: None,
+            missing: view.missing.as_ref().map(|missing| match missing {
+                MissingFilter::Any => MissingState::Any,
+                MissingFilter::Column(label) => MissingState::Column(label.name.clone()),
+            }),
+        }
+    }
+
+    /// Sets `view` to these settings, leaving out any for columns `db` no longer has
+    pub fn apply(&self, db: &ReplayDB, view: &mut ViewOptions) {
+        let label = |name: &str| db.labels().iter().find(|label| label.name == name).cloned();
+        view.compact = self.compact;
+        view.hex_numbers = self.hex_numbers;
+        view.show_timestamps = self.show_timestamps;
+        view.show_ids = self.show_ids;
+        view.search = self.search.clone();
+        view.regex_search = self.regex_search;
+        let sort_keys = match &self.sort {
+            Some(key) if self.sort_keys.is_empty() => std::slice::from_ref(key),
+            _ => &self.sort_keys,
+        };
+        view.sort = sort_keys
+            .iter()
+            .filter_map(|(name, direction)| Some((label(name)?, *direction)))
+            .collect();
+        view.missing = match &self.missing {
+            None => None,
+            Some(MissingState::Any) => Some(MissingFilter::Any),
+            Some(MissingState::Column(name)) => label(name).map(MissingFilter::Column),
+        };
+    }
+
+    pub fn load_from_path(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("failed to read `{}`: {err}", path.display()))?;
+        ron::from_str(&text).map_err(|err| format!("failed to parse `{}`: {err}", path.display()))
+    }
+
+    pub fn save_to_path(&self, path: &Path) -> Result<(), String> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| format!("failed to serialize view settings: {err}"))?;
+        std::fs::write(path, text)
+            .map_err(|err| format!("failed to write `{}`: {err}", path.display()))
+    }
+}
+
+#[test]
+fn settings_for_missing_columns_are_dropped() {
+    let db = ReplayDB::new();
+    let [name, split, ..] = db.labels().to_vec().try_into().unwrap();
+    let mut view = ViewOptions {
+        compact: true,
+        search: Some("tower".to_string()),
+        sort: vec![
+            (split.clone(), SortDirection::Descending),
+            (name.clone(), SortDirection::Ascending),
+        ],
+        missing: Some(MissingFilter::Column(split.clone())),
+        ..ViewOptions::default()
+    };
+    let state = ViewState::from_view(&view);
+    let text = ron::to_string(&state).unwrap();
+
+    let mut restored = ViewOptions::default();
+    ron::from_str::<ViewState>(&text)
+        .unwrap()
+        .apply(&db, &mut restored);
+    assert_eq!(ViewState::from_view(&restored), state);
+
+    // The split column has since been renamed
+    let text = text.replace(&split.name, "Old Split");
+    ron::from_str::<ViewState>(&text)
+        .unwrap()
+        .apply(&db, &mut view);
+    assert_eq!(view.sort, [(name.clone(), SortDirection::Ascending)]);
+    assert_eq!(view.missing, None);
+    assert!(view.compact);
+    assert_eq!(view.search.as_deref(), Some("tower"));
+}
+
+#[test]
+fn single_column_sorts_are_migrated() {
+    let db = ReplayDB::new();
+    let split = db.labels()[1].clone();
+    let text = format!(
+        r#"(compact: true, sort: Some(("{}", Descending)))"#,
+        split.name
+    );
+
+    let mut view = ViewOptions::default();
+    ron::from_str::<ViewState>(&text)
+        .unwrap()
+        .apply(&db, &mut view);
+    assert_eq!(view.sort, [(split, SortDirection::Descending)]);
+
+    // Saved again it's written the new way only
+    let text = ron::to_string(&ViewState::from_view(&view)).unwrap();
+    assert!(text.contains("sort_keys"));
+    assert!(!text.contains("sort:"));
+}
+
+#[test]
+fn recents_cycle_in_launch_order() {
+    let mut recents = Recents::default();
+    let [a, b, c] = ["a.ron", "b.ron", "c.ron"].map(|name| absolute(Path::new(name)));
+    assert_eq!(recents.next_after(&a), None);
+    for path in [&a, &b, &c, &b] {
+        recents.opened(path);
+    }
+
+    // Relative paths are the same databases
+    assert_eq!(recents.next_after(Path::new("b.ron")), Some(c.as_path()));
+    assert_eq!(recents.next_after(&c), Some(a.as_path()));
+    assert_eq!(recents.next_after(&a), Some(b.as_path()));
+
+    for n in 0..MAX_RECENTS {
+        recents.opened(Path::new(&format!("{n}.ron")));
+    }
+    assert_eq!(recents.files.len(), MAX_RECENTS);
+    assert!(recents.files.iter().all(|file| file.path != a));
+}